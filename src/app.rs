@@ -21,8 +21,23 @@ impl GothicOrganizer {
             None => state::ApplicationState::default(),
         };
 
-        let mut session = load_app_session!(user_data_dir.as_deref()).unwrap_or_default();
+        match crate::core::helpers::load_error_history(user_data_dir.as_deref()) {
+            Ok(errors) => state.errors.restore_history(errors),
+            Err(err) => {
+                state.errors.add_error(err);
+            }
+        }
+
+        let mut session = load_app_session!(user_data_dir.as_deref())
+            .unwrap_or_else(|err| {
+                state.errors.add_error(err);
+                None
+            })
+            .unwrap_or_default();
         session.custom_user_data_path = user_data_dir;
+        std::mem::take(&mut state.profile.load_errors).into_iter().for_each(|err| {
+            state.errors.add_error(err);
+        });
         Self::initialize_state(&mut session, &mut state);
 
         let app = Self { session, state };
@@ -30,6 +45,10 @@ impl GothicOrganizer {
     }
 
     fn initialize_state(session: &mut session::ApplicationSession, state: &mut state::ApplicationState) {
+        state.profile.profiles.values_mut().for_each(|profile| {
+            profile.resolve_portable(&crate::core::helpers::mod_storage_root(session, &profile.name))
+        });
+
         let active_profile_name = session.active_profile.as_ref();
         let active_profile = active_profile_name.and_then(|n| state.profile.profiles.get(n));
         let instances = active_profile.and_then(|p| p.instances.as_ref());
@@ -44,9 +63,18 @@ impl GothicOrganizer {
             .collect();
 
         state.profile.instance_choices = State::new(instance_names);
+        state.profile.refresh_snapshots(session);
         state.settings.theme_choices = State::new(themes);
         state.settings.renderer_choices = State::new(renderers);
         state.settings.zspy_level_field = zspy_level.into();
+        state.settings.log_dir_field = session
+            .log_settings
+            .directory
+            .as_ref()
+            .map(|directory| directory.display().to_string())
+            .unwrap_or_default();
+        state.diff.encoding_choices =
+            State::new(crate::core::diff::TextEncoding::into_iter().cloned().collect());
     }
 
     pub fn update(&mut self, message: message::Message) -> iced::Task<message::Message> {
@@ -76,19 +104,59 @@ impl GothicOrganizer {
             message::Message::Error(msg) => {
                 handlers::handle_error_message(&mut self.session, &mut self.state, msg)
             }
+
+            message::Message::Conflicts(msg) => {
+                handlers::handle_conflicts_message(&mut self.session, &mut self.state, msg)
+            }
+
+            message::Message::ModList(msg) => {
+                handlers::handle_modlist_message(&mut self.session, &mut self.state, msg)
+            }
+
+            message::Message::History(msg) => {
+                handlers::handle_history_message(&mut self.session, &mut self.state, msg)
+            }
+
+            message::Message::Logs(msg) => {
+                handlers::handle_logs_message(&mut self.session, &mut self.state, msg)
+            }
         }
     }
 
     pub fn subscription(&self) -> iced::Subscription<message::Message> {
-        iced::event::listen_with(|event, _, id| match event {
+        let log_updates = match self.state.ui.is_window_open(window::ApplicationWindow::Logs) {
+            true => iced::Subscription::run(crate::logger::updates)
+                .map(|_| message::Message::Logs(message::LogsMessage::Refresh)),
+            false => iced::Subscription::none(),
+        };
+
+        let events = iced::event::listen_with(|event, status, id| match event {
             iced::Event::Window(iced::window::Event::CloseRequested) => {
                 Some(message::Message::Window(message::WindowMessage::Close(id)))
             }
             iced::Event::Window(iced::window::Event::FileDropped(path)) => {
                 Some(message::Message::Mod(message::ModMessage::Add(Some(path))))
             }
+            iced::Event::Keyboard(iced::keyboard::Event::KeyPressed { key, modifiers, .. })
+                if status == iced::event::Status::Ignored && modifiers.command() =>
+            {
+                match key.as_ref() {
+                    iced::keyboard::Key::Character(c) if c.eq_ignore_ascii_case("z") && modifiers.shift() => {
+                        Some(message::Message::History(message::HistoryMessage::Redo))
+                    }
+                    iced::keyboard::Key::Character(c) if c.eq_ignore_ascii_case("z") => {
+                        Some(message::Message::History(message::HistoryMessage::Undo))
+                    }
+                    iced::keyboard::Key::Character(c) if c.eq_ignore_ascii_case("y") => {
+                        Some(message::Message::History(message::HistoryMessage::Redo))
+                    }
+                    _ => None,
+                }
+            }
             _ => None,
-        })
+        });
+
+        iced::Subscription::batch([events, log_updates])
     }
 
     pub fn theme(&self) -> iced::Theme {
@@ -104,7 +172,7 @@ impl GothicOrganizer {
             })
     }
 
-    pub fn view(&self, id: iced::window::Id) -> iced::Element<message::Message> {
+    pub fn view(&self, id: iced::window::Id) -> iced::Element<'_, message::Message> {
         if let Some((_, wnd_state)) = self.state.ui.windows.iter().find(|(wnd_id, _)| **wnd_id == id) {
             wnd_state.window_type.view(self)
         } else {
@@ -5,6 +5,7 @@ use crate::app::session;
 use crate::app::state;
 use crate::core::profile::Lookup;
 use crate::core::services;
+use crate::core::storage;
 use crate::lookup;
 
 pub fn handle_profile_message(
@@ -39,6 +40,71 @@ pub fn handle_profile_message(
             state.profile.profile_dir_field = input;
             iced::Task::none()
         }
+
+        message::ProfileMessage::UpdateProfileNameField(input) => {
+            state.profile.profile_name_field = input;
+            iced::Task::none()
+        }
+
+        message::ProfileMessage::SetNewProfileGameType(game_type) => {
+            state.profile.new_profile_game_type = game_type;
+            iced::Task::none()
+        }
+
+        message::ProfileMessage::CreateProfile => service.create_profile(),
+
+        message::ProfileMessage::RenameActiveProfile => service.rename_active_profile(),
+
+        message::ProfileMessage::DuplicateActiveProfile => service.duplicate_active_profile(),
+
+        message::ProfileMessage::DeleteActiveProfile => service.delete_active_profile(),
+
+        message::ProfileMessage::SetGameType(game_type) => service.set_game_type(game_type),
+
+        message::ProfileMessage::SetExecutable(executable) => service.set_executable(&executable),
+
+        message::ProfileMessage::SetLayoutDir(dir, path) => service.set_layout_dir(dir, &path),
+
+        message::ProfileMessage::ValidateGameDir => service.validate_game_dir(),
+
+        message::ProfileMessage::DuplicateActiveInstance => service.duplicate_instance(),
+
+        message::ProfileMessage::RenameActiveInstance => service.rename_instance(),
+
+        message::ProfileMessage::ToggleExportModFiles(include_mod_files) => {
+            state.profile.export_include_mod_files = include_mod_files;
+            iced::Task::none()
+        }
+
+        message::ProfileMessage::ExportActiveInstance => service.export_instance(),
+
+        message::ProfileMessage::ImportInstance(path) => service.import_instance(path),
+
+        message::ProfileMessage::DiscoverInstallations => service.discover_installations(),
+
+        message::ProfileMessage::UpdateSnapshotNameField(input) => {
+            state.profile.snapshot_name_field = input;
+            iced::Task::none()
+        }
+
+        message::ProfileMessage::UpdateSnapshotNoteField(input) => {
+            state.profile.snapshot_note_field = input;
+            iced::Task::none()
+        }
+
+        message::ProfileMessage::CreateSnapshot => service.create_snapshot(),
+
+        message::ProfileMessage::RestoreSnapshot(path) => service.restore_snapshot(&path),
+
+        message::ProfileMessage::DeleteSnapshot(path) => service.delete_snapshot(&path),
+
+        message::ProfileMessage::DeployActiveInstance => service.deploy_instance(),
+
+        message::ProfileMessage::PurgeDeployment => service.purge_deployment(),
+
+        message::ProfileMessage::UseInstallation(index) => service.use_installation(index),
+
+        message::ProfileMessage::RescanGameDir => service.rescan_game_dir(),
     }
 }
 
@@ -49,18 +115,23 @@ pub fn handle_mod_message(
 ) -> iced::Task<message::Message> {
     match message {
         message::ModMessage::Add(path) => {
+            services::history::HistoryService::new(session, state).record("Install mod");
             let mut profile_service = services::profile::ProfileService::new(session, state);
             let commit_changes = profile_service.commit_session_files();
             let mut mod_service = services::mods::ModService::new(session, state);
             let add_mod = mod_service.add_mod(path).map(message::Message::from);
+            services::history::HistoryService::new(session, state).discard_if_unchanged();
             commit_changes.chain(add_mod)
         }
 
         message::ModMessage::Toggle(index, new_state) => {
+            let description = if new_state { "Enable mod" } else { "Disable mod" };
+            services::history::HistoryService::new(session, state).record(description);
             let mut profile_service = services::profile::ProfileService::new(session, state);
             let commit_changes = profile_service.commit_session_files();
             let mut mod_service = services::mods::ModService::new(session, state);
             let toggle_mod = mod_service.toggle_mod(index, new_state);
+            services::history::HistoryService::new(session, state).discard_if_unchanged();
             commit_changes.chain(toggle_mod)
         }
 
@@ -76,8 +147,11 @@ pub fn handle_mod_message(
         }
 
         message::ModMessage::Uninstall(index) => {
+            let trash_dir = services::history::HistoryService::new(session, state).record("Uninstall mod");
             let mut service = services::mods::ModService::new(session, state);
-            service.remove_mod(index).map(message::Message::from)
+            let remove_mod = service.remove_mod(index, trash_dir.as_deref()).map(message::Message::from);
+            services::history::HistoryService::new(session, state).discard_if_unchanged();
+            remove_mod
         }
 
         message::ModMessage::Reload => {
@@ -94,6 +168,20 @@ pub fn handle_mod_message(
             state.mod_management.mods_dir_field = input;
             iced::Task::none()
         }
+
+        message::ModMessage::StorageTransfer(storage::TransferEvent::Progress(progress)) => {
+            state.mod_management.storage_transfer_progress = progress;
+            iced::Task::none()
+        }
+
+        message::ModMessage::StorageTransfer(storage::TransferEvent::Finished(result)) => {
+            let Some(transfer) = state.mod_management.storage_transfer.take() else {
+                return iced::Task::none();
+            };
+
+            let mut service = services::profile::ProfileService::new(session, state);
+            service.finish_mods_dir_transfer(transfer, result)
+        }
     }
 }
 
@@ -113,12 +201,14 @@ pub fn handle_ui_message(
         }
 
         message::UiMessage::ToggleFileEntry(entry_sate, path) => {
+            services::history::HistoryService::new(session, state).record("Toggle file");
             let mut service = services::ui::UiService::new(session, state);
             service.set_entry_state_with_children(Some(entry_sate), Some(&path));
             iced::Task::none()
         }
 
         message::UiMessage::ToggleAllFileEntries => {
+            services::history::HistoryService::new(session, state).record("Toggle all files");
             let mut service = services::ui::UiService::new(session, state);
             service.set_entry_state_with_children(None, None);
             iced::Task::none()
@@ -191,6 +281,19 @@ pub fn handle_settings_message(
             session.error_notifications_enabled = new_state;
             iced::Task::none()
         }
+
+        message::SettingsMessage::SetLogLevel(level) => {
+            services::session::SessionService::new(session, state).set_log_level(level)
+        }
+
+        message::SettingsMessage::UpdateLogDirField(input) => {
+            state.settings.log_dir_field = input;
+            iced::Task::none()
+        }
+
+        message::SettingsMessage::SetLogDir(directory) => {
+            services::session::SessionService::new(session, state).set_log_dir(directory)
+        }
     }
 }
 
@@ -277,3 +380,130 @@ pub fn handle_error_message(
         }
     }
 }
+
+pub fn handle_conflicts_message(
+    session: &mut session::ApplicationSession,
+    state: &mut state::ApplicationState,
+    message: message::ConflictsMessage,
+) -> iced::Task<message::Message> {
+    let mut service = services::conflicts::ConflictsService::new(session, state);
+
+    match message {
+        message::ConflictsMessage::OpenDiff(target_path) => service.open_diff(&target_path),
+
+        message::ConflictsMessage::SetDiffLeft(provider) => service.set_diff_providers(Some(provider), None),
+
+        message::ConflictsMessage::SetDiffRight(provider) => service.set_diff_providers(None, Some(provider)),
+
+        message::ConflictsMessage::SetDiffEncoding(encoding) => service.set_diff_encoding(encoding),
+
+        message::ConflictsMessage::SwapDiffSides => service.swap_diff_sides(),
+
+        message::ConflictsMessage::AnalyzeOverlaps => service.analyze_overlaps(),
+
+        message::ConflictsMessage::OpenComparison => service.open_comparison(),
+
+        message::ConflictsMessage::SetCompareLeft(instance) => {
+            service.set_compared_instances(Some(instance), None)
+        }
+
+        message::ConflictsMessage::SetCompareRight(instance) => {
+            service.set_compared_instances(None, Some(instance))
+        }
+    }
+}
+
+pub fn handle_modlist_message(
+    session: &mut session::ApplicationSession,
+    state: &mut state::ApplicationState,
+    message: message::ModListMessage,
+) -> iced::Task<message::Message> {
+    let mut service = services::modlist::ModListService::new(session, state);
+
+    match message {
+        message::ModListMessage::Export => service.export_modlist(),
+
+        message::ModListMessage::Load(path) => service.load_modlist(path),
+
+        message::ModListMessage::SetArchiveDir(path) => service.set_archive_dir(path),
+
+        message::ModListMessage::BuildInstance => service.build_instance(),
+        message::ModListMessage::ApplyModpack(path) => service.apply_modpack(path),
+    }
+}
+
+pub fn handle_logs_message(
+    session: &mut session::ApplicationSession,
+    state: &mut state::ApplicationState,
+    message: message::LogsMessage,
+) -> iced::Task<message::Message> {
+    match message {
+        message::LogsMessage::Refresh => {
+            crate::logger::acknowledge_updates();
+            state.logs.entries = crate::logger::entries();
+            iced::Task::none()
+        }
+
+        message::LogsMessage::SetTab(tab) => {
+            state.logs.tab = tab;
+            iced::Task::none()
+        }
+
+        message::LogsMessage::SetLevelFilter(level) => {
+            state.logs.level_filter = level;
+            iced::Task::none()
+        }
+
+        message::LogsMessage::UpdateTargetFilter(input) => {
+            state.logs.target_filter = input;
+            iced::Task::none()
+        }
+
+        message::LogsMessage::UpdateTextFilter(input) => {
+            state.logs.text_filter = input;
+            iced::Task::none()
+        }
+
+        message::LogsMessage::CopyEntry(id) => match state.logs.entries.iter().find(|entry| entry.id == id) {
+            Some(entry) => iced::clipboard::write(entry.line()),
+            None => iced::Task::none(),
+        },
+
+        message::LogsMessage::CopyShown => {
+            let lines = state.logs.shown_entries().map(|entry| entry.line()).collect::<Vec<_>>();
+            iced::clipboard::write(lines.join("\n"))
+        }
+
+        message::LogsMessage::ClearErrors => {
+            state.errors.clear_history();
+            iced::Task::none()
+        }
+
+        message::LogsMessage::ExportDiagnostics(path) => {
+            services::session::SessionService::new(session, state).export_diagnostics(path)
+        }
+
+        message::LogsMessage::DiagnosticsExported(path, result) => {
+            services::session::SessionService::new(session, state).finish_diagnostics_export(&path, result)
+        }
+    }
+}
+
+pub fn handle_history_message(
+    session: &mut session::ApplicationSession,
+    state: &mut state::ApplicationState,
+    message: message::HistoryMessage,
+) -> iced::Task<message::Message> {
+    let mut service = services::history::HistoryService::new(session, state);
+
+    match message {
+        message::HistoryMessage::Undo => service.undo(),
+
+        message::HistoryMessage::Redo => service.redo(),
+
+        message::HistoryMessage::Clear => {
+            service.clear();
+            iced::Task::none()
+        }
+    }
+}
@@ -3,6 +3,12 @@
 use std::path::PathBuf;
 
 use crate::app::session;
+use crate::app::state;
+use crate::core::compare;
+use crate::core::constants::DefaultProfile;
+use crate::core::diff;
+use crate::core::profile;
+use crate::core::storage;
 use crate::gui::options;
 
 use super::window::ApplicationWindow;
@@ -16,6 +22,10 @@ pub enum Message {
     Window(WindowMessage),
     System(SystemMessage),
     Error(ErrorMessage),
+    Conflicts(ConflictsMessage),
+    ModList(ModListMessage),
+    History(HistoryMessage),
+    Logs(LogsMessage),
 }
 
 #[derive(Debug, Clone)]
@@ -32,8 +42,33 @@ pub enum ProfileMessage {
     SetGameDir(Option<PathBuf>),
     AddInstance,
     RemoveActiveInstance,
+    DuplicateActiveInstance,
+    RenameActiveInstance,
+    ToggleExportModFiles(bool),
+    ExportActiveInstance,
+    ImportInstance(Option<PathBuf>),
     UpdateInstanceNameField(String),
     UpdateProfileDirField(String),
+    UpdateProfileNameField(String),
+    SetNewProfileGameType(DefaultProfile),
+    CreateProfile,
+    RenameActiveProfile,
+    DuplicateActiveProfile,
+    DeleteActiveProfile,
+    SetGameType(DefaultProfile),
+    SetExecutable(String),
+    SetLayoutDir(profile::LayoutDir, String),
+    ValidateGameDir,
+    DiscoverInstallations,
+    UseInstallation(usize),
+    UpdateSnapshotNameField(String),
+    UpdateSnapshotNoteField(String),
+    CreateSnapshot,
+    RestoreSnapshot(PathBuf),
+    DeleteSnapshot(PathBuf),
+    RescanGameDir,
+    DeployActiveInstance,
+    PurgeDeployment,
 }
 
 #[derive(Debug, Clone)]
@@ -44,6 +79,7 @@ pub enum ModMessage {
     ToggleSelection(usize),
     Uninstall(usize),
     UpdateModsDirField(String),
+    StorageTransfer(storage::TransferEvent),
     Reload,
 }
 
@@ -65,6 +101,53 @@ pub enum SettingsMessage {
     ToggleParserSetting(session::ParserCommand, bool),
     ToggleZSpyState(bool),
     ToggleErrorNotifications(bool),
+    SetLogLevel(session::LogLevel),
+    UpdateLogDirField(String),
+    SetLogDir(Option<PathBuf>),
+}
+
+#[derive(Debug, Clone)]
+pub enum ConflictsMessage {
+    OpenDiff(PathBuf),
+    SetDiffLeft(state::DiffProvider),
+    SetDiffRight(state::DiffProvider),
+    SetDiffEncoding(diff::TextEncoding),
+    SwapDiffSides,
+    AnalyzeOverlaps,
+    OpenComparison,
+    SetCompareLeft(compare::InstanceRef),
+    SetCompareRight(compare::InstanceRef),
+}
+
+#[derive(Debug, Clone)]
+pub enum ModListMessage {
+    Export,
+    Load(Option<PathBuf>),
+    SetArchiveDir(Option<PathBuf>),
+    BuildInstance,
+    ApplyModpack(Option<PathBuf>),
+}
+
+#[derive(Debug, Clone)]
+pub enum HistoryMessage {
+    Undo,
+    Redo,
+    Clear,
+}
+
+#[derive(Debug, Clone)]
+pub enum LogsMessage {
+    Refresh,
+    SetTab(state::LogsTab),
+    SetLevelFilter(tracing::level_filters::LevelFilter),
+    UpdateTargetFilter(String),
+    UpdateTextFilter(String),
+    CopyEntry(u64),
+    CopyShown,
+    ExportDiagnostics(Option<std::path::PathBuf>),
+    /// The bundle written to the path, with the number of files in it.
+    DiagnosticsExported(std::path::PathBuf, Result<usize, crate::error::ErrorContext>),
+    ClearErrors,
 }
 
 #[derive(Debug, Clone)]
@@ -122,3 +205,27 @@ impl From<ErrorMessage> for Message {
         Message::Error(msg)
     }
 }
+
+impl From<ConflictsMessage> for Message {
+    fn from(msg: ConflictsMessage) -> Self {
+        Message::Conflicts(msg)
+    }
+}
+
+impl From<ModListMessage> for Message {
+    fn from(msg: ModListMessage) -> Self {
+        Message::ModList(msg)
+    }
+}
+
+impl From<HistoryMessage> for Message {
+    fn from(msg: HistoryMessage) -> Self {
+        Message::History(msg)
+    }
+}
+
+impl From<LogsMessage> for Message {
+    fn from(msg: LogsMessage) -> Self {
+        Message::Logs(msg)
+    }
+}
@@ -19,6 +19,7 @@ pub struct ApplicationSession {
     pub files: Lookup<PathBuf, profile::FileMetadata>,
     pub launch_options: Option<GameLaunchConfiguration>,
     pub error_notifications_enabled: bool,
+    pub log_settings: LogSettings,
 }
 
 impl std::default::Default for ApplicationSession {
@@ -35,6 +36,55 @@ impl std::default::Default for ApplicationSession {
             files: Default::default(),
             launch_options: None,
             error_notifications_enabled: true,
+            log_settings: LogSettings::default(),
+        }
+    }
+}
+
+/// Level and location of the log file. The file is rotated daily or once it reaches
+/// `max_file_size_mb`, keeping `retained_files` rotated files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogSettings {
+    pub level: LogLevel,
+    /// Directory of the log file, the `logs` directory of the user data when not set.
+    pub directory: Option<PathBuf>,
+    pub max_file_size_mb: u64,
+    pub retained_files: usize,
+}
+
+impl std::default::Default for LogSettings {
+    fn default() -> Self {
+        Self { level: LogLevel::Info, directory: None, max_file_size_mb: 5, retained_files: 5 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, Display)]
+#[display("{}", _variant)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn into_iter() -> std::slice::Iter<'static, Self> {
+        static LEVELS: [LogLevel; 5] =
+            [LogLevel::Error, LogLevel::Warn, LogLevel::Info, LogLevel::Debug, LogLevel::Trace];
+        LEVELS.iter()
+    }
+}
+
+impl From<LogLevel> for tracing::level_filters::LevelFilter {
+    fn from(value: LogLevel) -> Self {
+        match value {
+            LogLevel::Error => tracing::level_filters::LevelFilter::ERROR,
+            LogLevel::Warn => tracing::level_filters::LevelFilter::WARN,
+            LogLevel::Info => tracing::level_filters::LevelFilter::INFO,
+            LogLevel::Debug => tracing::level_filters::LevelFilter::DEBUG,
+            LogLevel::Trace => tracing::level_filters::LevelFilter::TRACE,
         }
     }
 }
@@ -143,7 +193,6 @@ impl ParserCommand {
         COMMANDS.iter()
     }
 
-    #[allow(dead_code)]
     pub fn into_argument(self) -> String {
         match self {
             ParserCommand::Game => "zReparse_Game".to_owned(),
@@ -1,17 +1,29 @@
 #![allow(dead_code)]
 
+use std::path::Path;
 use std::path::PathBuf;
 
+use derive_more::Display;
 use iced::widget::combo_box;
 use iced::window::Id;
 
 use crate::app::session;
+use crate::core::basefiles;
+use crate::core::compare;
 use crate::core::constants;
+use crate::core::diff;
+use crate::core::discovery;
+use crate::core::helpers;
+use crate::core::history;
+use crate::core::modlist;
+use crate::core::overlaps;
 use crate::core::profile;
 use crate::core::profile::Lookup;
+use crate::core::snapshot;
+use crate::core::storage;
 use crate::error;
 use crate::gui::options;
-use crate::load_profile;
+use crate::logger;
 
 use super::window::WindowInfo;
 
@@ -22,16 +34,32 @@ pub struct ApplicationState {
     pub mod_management: ModState,
     pub settings: SettingsState,
     pub errors: ErrorState,
+    pub diff: DiffState,
+    pub overlaps: OverlapState,
+    pub modlist: ModListState,
+    pub compare: CompareState,
+    pub history: history::History,
+    pub logs: LogState,
 }
 
 impl ApplicationState {
     pub fn new(user_data_dir: PathBuf) -> Self {
+        Self::with_profile_state(ProfileState::new(user_data_dir))
+    }
+
+    pub fn with_profile_state(profile: ProfileState) -> Self {
         Self {
             ui: UiState::default(),
-            profile: ProfileState::new(user_data_dir),
+            profile,
             mod_management: ModState::default(),
             settings: SettingsState::default(),
             errors: ErrorState::default(),
+            diff: DiffState::default(),
+            overlaps: OverlapState::default(),
+            modlist: ModListState::default(),
+            compare: CompareState::default(),
+            history: history::History::default(),
+            logs: LogState::default(),
         }
     }
 }
@@ -45,71 +73,207 @@ pub struct UiState {
     pub windows: Lookup<Id, WindowInfo>,
 }
 
+impl UiState {
+    pub fn is_window_open(&self, window: super::window::ApplicationWindow) -> bool {
+        self.windows.values().any(|info| info.window_type == window && !info.is_closed)
+    }
+}
+
 #[derive(Debug)]
 pub struct ProfileState {
     pub profiles: Lookup<String, profile::Profile>,
     pub instance_name_field: String,
     pub profile_dir_field: String,
+    pub profile_name_field: String,
+    pub new_profile_game_type: constants::DefaultProfile,
     pub profile_choices: combo_box::State<String>,
     pub instance_choices: combo_box::State<String>,
+    pub game_type_choices: combo_box::State<constants::DefaultProfile>,
+    pub new_profile_game_type_choices: combo_box::State<constants::DefaultProfile>,
+    pub discovered_installations: Vec<discovery::DiscoveredInstallation>,
+    pub export_include_mod_files: bool,
+    pub snapshot_name_field: String,
+    pub snapshot_note_field: String,
+    /// Snapshots of the active instance, newest first.
+    pub snapshots: Vec<snapshot::SnapshotInfo>,
+    /// Result of the last game directory rescan of the active profile.
+    pub base_file_changes: Option<basefiles::BaseFileChanges>,
+    /// Profiles that could not be loaded on startup, reported once the application is running.
+    pub load_errors: Vec<error::ErrorContext>,
 }
 
 impl ProfileState {
     pub fn new(user_data_dir: PathBuf) -> Self {
-        Self {
-            profiles: constants::DefaultProfile::into_iter()
-                .map(|profile_name| {
-                    let name_str = (*profile_name).to_string();
-                    let profile = load_profile!(&name_str, Some(&user_data_dir))
-                        .unwrap_or_else(|| profile::Profile::default().with_name(&name_str));
-                    (name_str, profile)
-                })
-                .collect(),
-
-            profile_choices: iced::widget::combo_box::State::new(
-                constants::DefaultProfile::into_iter().map(|p| p.to_string()).collect(),
-            ),
+        Self::load(Some(&user_data_dir))
+    }
+
+    fn load(user_data_dir: Option<&Path>) -> Self {
+        let (profiles, load_errors) = helpers::load_profiles(user_data_dir, helpers::Recovery::Ask);
+        Self::with_profiles(profiles, load_errors.into_iter().map(|(_, err)| err).collect())
+    }
+
+    pub fn with_profiles(
+        profiles: Lookup<String, profile::Profile>,
+        load_errors: Vec<error::ErrorContext>,
+    ) -> Self {
+        let mut state = Self {
+            profiles,
             instance_choices: Default::default(),
+            profile_choices: Default::default(),
+            game_type_choices: combo_box::State::new(
+                constants::DefaultProfile::into_iter().copied().collect(),
+            ),
+            new_profile_game_type_choices: combo_box::State::new(
+                constants::DefaultProfile::into_iter().copied().collect(),
+            ),
+            discovered_installations: Vec::new(),
+            export_include_mod_files: true,
+            snapshot_name_field: String::new(),
+            snapshot_note_field: String::new(),
+            snapshots: Vec::new(),
+            base_file_changes: None,
+            load_errors,
             instance_name_field: String::new(),
             profile_dir_field: String::new(),
-        }
+            profile_name_field: String::new(),
+            new_profile_game_type: constants::DefaultProfile::default(),
+        };
+        state.refresh_profile_choices();
+        state
+    }
+
+    /// Rebuilds the profile selector, listing the default profiles first.
+    pub fn refresh_profile_choices(&mut self) {
+        let mut custom_profile_names =
+            self.profiles.values().filter(|p| p.is_custom).map(|p| p.name.clone()).collect::<Vec<_>>();
+        custom_profile_names.sort_unstable();
+
+        let profile_names = constants::DefaultProfile::into_iter()
+            .map(|p| p.to_string())
+            .chain(custom_profile_names)
+            .collect::<Vec<_>>();
+
+        self.profile_choices = combo_box::State::new(profile_names);
+    }
+
+    /// Reloads the snapshot list of the active instance.
+    pub fn refresh_snapshots(&mut self, session: &session::ApplicationSession) {
+        self.snapshots = match (&session.active_profile, &session.active_instance) {
+            (Some(profile_name), Some(instance_name)) => {
+                snapshot::list_snapshots(&helpers::snapshot_dir(session, profile_name, instance_name))
+            }
+            _ => Vec::new(),
+        };
     }
 }
 
 impl std::default::Default for ProfileState {
     fn default() -> Self {
-        Self {
-            profiles: constants::DefaultProfile::into_iter()
-                .map(|profile_name| {
-                    let name_str = (*profile_name).to_string();
-                    let profile = load_profile!(&name_str)
-                        .unwrap_or_else(|| profile::Profile::default().with_name(&name_str));
-                    (name_str, profile)
-                })
-                .collect(),
-
-            profile_choices: iced::widget::combo_box::State::new(
-                constants::DefaultProfile::into_iter().map(|p| p.to_string()).collect(),
-            ),
-            instance_choices: Default::default(),
-            instance_name_field: String::new(),
-            profile_dir_field: String::new(),
-        }
+        Self::load(None)
     }
 }
 
 #[derive(Debug, Default)]
 pub struct ModState {
     pub mods_dir_field: String,
+    /// Mods directory change that is waiting for its files to be moved or copied.
+    pub storage_transfer: Option<storage::StorageTransfer>,
+    pub storage_transfer_progress: storage::TransferProgress,
 }
 
 #[derive(Debug, Default)]
 pub struct SettingsState {
     pub zspy_level_field: u8,
+    pub log_dir_field: String,
     pub theme_choices: combo_box::State<String>,
     pub renderer_choices: combo_box::State<session::RendererBackend>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Display)]
+#[display("{name} (priority {priority})")]
+pub struct DiffProvider {
+    pub priority: usize,
+    pub name: String,
+    pub source_path: PathBuf,
+}
+
+#[derive(Debug, Default)]
+pub struct DiffState {
+    pub target_path: Option<PathBuf>,
+    pub providers: Vec<DiffProvider>,
+    pub left: Option<DiffProvider>,
+    pub right: Option<DiffProvider>,
+    pub encoding: diff::TextEncoding,
+    pub result: Option<diff::FileDiff>,
+    pub provider_choices: combo_box::State<DiffProvider>,
+    pub encoding_choices: combo_box::State<diff::TextEncoding>,
+}
+
+#[derive(Debug, Default)]
+pub struct OverlapState {
+    pub report: Option<overlaps::OverlapReport>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Display)]
+pub enum LogsTab {
+    #[default]
+    Errors,
+    #[display("Application log")]
+    Log,
+}
+
+#[derive(Debug)]
+pub struct LogState {
+    pub tab: LogsTab,
+    /// Copy of the log buffer, refreshed while the logs window is open.
+    pub entries: Vec<logger::LogEntry>,
+    /// Least severe level shown.
+    pub level_filter: tracing::level_filters::LevelFilter,
+    pub target_filter: String,
+    pub text_filter: String,
+}
+
+impl Default for LogState {
+    fn default() -> Self {
+        Self {
+            tab: LogsTab::default(),
+            entries: Vec::new(),
+            level_filter: tracing::level_filters::LevelFilter::TRACE,
+            target_filter: String::new(),
+            text_filter: String::new(),
+        }
+    }
+}
+
+impl LogState {
+    /// Entries matching the filters, oldest first. Target and text are matched ignoring case.
+    pub fn shown_entries(&self) -> impl Iterator<Item = &logger::LogEntry> {
+        let target_filter = self.target_filter.to_lowercase();
+        let text_filter = self.text_filter.to_lowercase();
+        self.entries.iter().filter(move |entry| {
+            self.level_filter >= entry.level
+                && entry.target.to_lowercase().contains(&target_filter)
+                && entry.message.to_lowercase().contains(&text_filter)
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CompareState {
+    pub left: Option<compare::InstanceRef>,
+    pub right: Option<compare::InstanceRef>,
+    pub choices: combo_box::State<compare::InstanceRef>,
+    pub result: Option<compare::InstanceComparison>,
+}
+
+#[derive(Debug, Default)]
+pub struct ModListState {
+    pub modlist: Option<modlist::ModList>,
+    pub archive_dir: Option<PathBuf>,
+    /// Local match of every entry of the loaded mod list, in the same order.
+    pub matches: Vec<modlist::ModMatch>,
+}
+
 #[derive(Debug)]
 pub struct ErrorState {
     pub active_errors: Lookup<uuid::Uuid, error::ErrorContext>,
@@ -143,6 +307,17 @@ impl ErrorState {
         id
     }
 
+    /// Replaces the history with the errors of previous sessions, keeping the newest ones.
+    pub fn restore_history(&mut self, errors: Vec<error::ErrorContext>) {
+        let excess = errors.len().saturating_sub(self.max_history_size);
+        self.error_history = errors.into_iter().skip(excess).collect();
+    }
+
+    pub fn clear_history(&mut self) {
+        self.error_history.clear();
+        self.active_errors.clear();
+    }
+
     pub fn dismiss_error(&mut self, id: uuid::Uuid) -> Option<error::ErrorContext> {
         self.active_errors.remove(&id)
     }
@@ -25,6 +25,18 @@ pub enum ApplicationWindow {
 
     #[display("logs")]
     Logs,
+
+    #[display("diff")]
+    Diff,
+
+    #[display("overlaps")]
+    Overlaps,
+
+    #[display("modlist")]
+    ModList,
+
+    #[display("compare")]
+    Compare,
 }
 
 impl From<&str> for ApplicationWindow {
@@ -34,6 +46,10 @@ impl From<&str> for ApplicationWindow {
             "options" => ApplicationWindow::Options,
             "conflicts" => ApplicationWindow::Conflicts,
             "logs" => ApplicationWindow::Logs,
+            "diff" => ApplicationWindow::Diff,
+            "overlaps" => ApplicationWindow::Overlaps,
+            "modlist" => ApplicationWindow::ModList,
+            "compare" => ApplicationWindow::Compare,
             _ => unreachable!(),
         }
     }
@@ -41,8 +57,15 @@ impl From<&str> for ApplicationWindow {
 
 impl ApplicationWindow {
     pub fn into_iter() -> std::slice::Iter<'static, ApplicationWindow> {
-        static WINDOWS: [ApplicationWindow; 3] =
-            [ApplicationWindow::Editor, ApplicationWindow::Options, ApplicationWindow::Conflicts];
+        static WINDOWS: [ApplicationWindow; 7] = [
+            ApplicationWindow::Editor,
+            ApplicationWindow::Options,
+            ApplicationWindow::Conflicts,
+            ApplicationWindow::Diff,
+            ApplicationWindow::Overlaps,
+            ApplicationWindow::ModList,
+            ApplicationWindow::Compare,
+        ];
         WINDOWS.iter()
     }
 
@@ -55,7 +78,11 @@ impl ApplicationWindow {
             ApplicationWindow::Editor => iced::Size { width: 768.0, height: 768.0 },
             ApplicationWindow::Options => iced::Size { width: 768.0, height: 460.0 },
             ApplicationWindow::Conflicts => iced::Size { width: 768.0, height: 460.0 },
-            ApplicationWindow::Logs => iced::Size { width: 512.0, height: 512.0 },
+            ApplicationWindow::Logs => iced::Size { width: 960.0, height: 640.0 },
+            ApplicationWindow::Diff => iced::Size { width: 1024.0, height: 640.0 },
+            ApplicationWindow::Overlaps => iced::Size { width: 768.0, height: 640.0 },
+            ApplicationWindow::ModList => iced::Size { width: 768.0, height: 640.0 },
+            ApplicationWindow::Compare => iced::Size { width: 1024.0, height: 640.0 },
         }
     }
 
@@ -65,6 +92,10 @@ impl ApplicationWindow {
             ApplicationWindow::Options => iced::window::Position::Centered,
             ApplicationWindow::Conflicts => iced::window::Position::Centered,
             ApplicationWindow::Logs => iced::window::Position::Centered,
+            ApplicationWindow::Diff => iced::window::Position::Centered,
+            ApplicationWindow::Overlaps => iced::window::Position::Centered,
+            ApplicationWindow::ModList => iced::window::Position::Centered,
+            ApplicationWindow::Compare => iced::window::Position::Centered,
         }
     }
 
@@ -74,6 +105,10 @@ impl ApplicationWindow {
             ApplicationWindow::Options => crate::gui::options::options_view(app),
             ApplicationWindow::Conflicts => crate::gui::conflicts::conflicts_view(app),
             ApplicationWindow::Logs => crate::gui::logs::logs_view(app),
+            ApplicationWindow::Diff => crate::gui::diff::diff_view(app),
+            ApplicationWindow::Overlaps => crate::gui::overlaps::overlaps_view(app),
+            ApplicationWindow::ModList => crate::gui::modlist::modlist_view(app),
+            ApplicationWindow::Compare => crate::gui::compare::compare_view(app),
         }
     }
 }
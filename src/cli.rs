@@ -0,0 +1,391 @@
+//! Command line interface working on the same user data as the application, without opening a
+//! window.
+
+pub mod deploy;
+pub mod diagnostics;
+pub mod list;
+pub mod modpack;
+pub mod mods;
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use serde::Serialize;
+
+use crate::app::session;
+use crate::app::state;
+use crate::core::helpers;
+use crate::core::profile;
+use crate::core::profile::Lookup;
+use crate::error;
+use crate::error::ErrorContext;
+
+#[derive(Debug, clap::Subcommand)]
+pub enum Command {
+    /// Inspect profiles
+    Profiles {
+        #[clap(subcommand)]
+        command: ProfilesCommand,
+    },
+    /// Inspect the instances of a profile
+    Instances {
+        #[clap(subcommand)]
+        command: InstancesCommand,
+    },
+    /// Inspect and manage the mods of an instance
+    Mods {
+        #[clap(subcommand)]
+        command: ModsCommand,
+    },
+    /// Make an instance match a modpack file
+    Apply { profile: String, instance: String, modpack: PathBuf },
+    /// Show what deploying an instance would change in the game directory
+    Plan { profile: String, instance: String },
+    /// Copy the enabled mods of an instance into the game directory
+    Deploy { profile: String, instance: String },
+    /// Remove every deployed mod file from the game directory of a profile
+    Purge { profile: String },
+    /// Start the game of a profile with a deployed instance
+    Launch {
+        profile: String,
+        instance: String,
+        /// zSpy verbosity from 0 to 10, overriding the launch options
+        #[clap(long, value_parser = clap::value_parser!(u8).range(0..=10))]
+        zspy: Option<u8>,
+        /// Wait for the game to exit and fail if it does not exit successfully
+        #[clap(long)]
+        wait: bool,
+        /// Additional arguments passed to the game
+        #[clap(last = true)]
+        args: Vec<String>,
+    },
+    /// Write a zip with logs, settings and system information to attach to a bug report
+    Diagnostics {
+        /// Profile to include, defaults to the active one
+        #[clap(long)]
+        profile: Option<String>,
+        /// Instance to include, defaults to the active one
+        #[clap(long)]
+        instance: Option<String>,
+        /// File to write, defaults to a timestamped file in the current directory
+        #[clap(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum ProfilesCommand {
+    /// List all profiles
+    List,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum InstancesCommand {
+    /// List the instances of a profile
+    List { profile: String },
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum ModsCommand {
+    /// List the mods of an instance in load order
+    List { profile: String, instance: String },
+    /// Install a mod from a directory or zip archive
+    Install { profile: String, instance: String, path: PathBuf },
+    /// Uninstall a mod, deleting its files
+    Uninstall {
+        profile: String,
+        instance: String,
+        /// Name or position of the mod
+        r#mod: String,
+    },
+    /// Enable a mod
+    Enable {
+        profile: String,
+        instance: String,
+        /// Name or position of the mod
+        r#mod: String,
+    },
+    /// Disable a mod
+    Disable {
+        profile: String,
+        instance: String,
+        /// Name or position of the mod
+        r#mod: String,
+    },
+    /// Move a mod to another position of the load order
+    Reorder {
+        profile: String,
+        instance: String,
+        /// Name or position of the mod
+        r#mod: String,
+        /// New position, starting at 1
+        #[clap(value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        position: usize,
+    },
+}
+
+/// Failure of a command. Every kind has its own exit code so that scripts can tell them apart.
+#[derive(Debug)]
+pub enum CliError {
+    /// The command could not be carried out.
+    Failed(ErrorContext),
+    /// A profile, instance or mod given on the command line does not exist.
+    NotFound(String),
+    /// An argument is outside of what the command accepts. Exits like the usage errors of clap.
+    InvalidArgument(String),
+    /// The instance to launch is not deployed or its deployment is out of date.
+    NotDeployed(String),
+    /// The game could not be waited for or did not exit successfully.
+    GameFailed(String),
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CliError::Failed(_) => 1,
+            CliError::InvalidArgument(_) => 2,
+            CliError::NotFound(_) => 3,
+            CliError::NotDeployed(_) => 4,
+            CliError::GameFailed(_) => 5,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            CliError::Failed(err) => err.error.to_string(),
+            CliError::NotFound(msg)
+            | CliError::InvalidArgument(msg)
+            | CliError::NotDeployed(msg)
+            | CliError::GameFailed(msg) => msg.clone(),
+        }
+    }
+
+    /// Stable code of the error kind, only known for failures of the command itself.
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            CliError::Failed(err) => Some(err.error.code()),
+            CliError::NotFound(_)
+            | CliError::InvalidArgument(_)
+            | CliError::NotDeployed(_)
+            | CliError::GameFailed(_) => None,
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            CliError::Failed(err) => err.error.path(),
+            CliError::NotFound(_)
+            | CliError::InvalidArgument(_)
+            | CliError::NotDeployed(_)
+            | CliError::GameFailed(_) => None,
+        }
+    }
+
+    pub fn suggested_action(&self) -> Option<&str> {
+        match self {
+            CliError::Failed(err) => Some(err.suggested_action.as_str()),
+            CliError::NotDeployed(_) => Some("Deploy the instance first"),
+            CliError::NotFound(_) | CliError::InvalidArgument(_) | CliError::GameFailed(_) => None,
+        }
+    }
+}
+
+impl From<ErrorContext> for CliError {
+    fn from(value: ErrorContext) -> Self {
+        CliError::Failed(value)
+    }
+}
+
+/// Result of a command, printed as JSON with `--json` and as text otherwise.
+pub struct Output {
+    json: serde_json::Value,
+    text: String,
+}
+
+impl Output {
+    pub fn new<T: Serialize>(value: &T, text: String) -> Self {
+        Self { json: serde_json::to_value(value).unwrap_or_default(), text }
+    }
+}
+
+/// Session and profiles as the application would load them, with paths resolved.
+pub struct UserData {
+    pub session: session::ApplicationSession,
+    pub state: state::ApplicationState,
+    /// Profiles that could not be loaded, by name. Commands touching them fail with the error.
+    pub broken_profiles: Lookup<String, ErrorContext>,
+}
+
+impl UserData {
+    /// Loads the user data. Unlike the application, unreadable files are reported instead of
+    /// replaced by defaults, so that a command never works on partial data.
+    pub fn load(user_data_dir: Option<&Path>) -> Result<Self, CliError> {
+        let mut session =
+            helpers::load_app_session(user_data_dir, helpers::Recovery::Never)?.unwrap_or_default();
+        session.custom_user_data_path = user_data_dir.map(Path::to_path_buf);
+
+        let (mut profiles, load_errors) = helpers::load_profiles(user_data_dir, helpers::Recovery::Never);
+        // Default profiles that failed to load are replaced by empty ones, which must not be
+        // worked on or saved over the broken file.
+        let broken_profiles = load_errors
+            .into_iter()
+            .inspect(|(name, _)| {
+                profiles.remove(name);
+            })
+            .collect::<Lookup<_, _>>();
+
+        profiles.values_mut().for_each(|profile| {
+            profile.resolve_portable(&helpers::mod_storage_root(&session, &profile.name));
+        });
+
+        let state = state::ApplicationState::with_profile_state(state::ProfileState::with_profiles(
+            profiles,
+            Vec::new(),
+        ));
+        Ok(Self { session, state, broken_profiles })
+    }
+
+    pub fn profiles(&self) -> &Lookup<String, profile::Profile> {
+        &self.state.profile.profiles
+    }
+
+    pub fn profile(&self, profile_name: &str) -> Result<&profile::Profile, CliError> {
+        if let Some(err) = self.broken_profiles.get(profile_name) {
+            return Err(CliError::Failed(err.clone()));
+        }
+
+        self.profiles()
+            .get(profile_name)
+            .ok_or_else(|| CliError::NotFound(format!("Profile not found: {profile_name}")))
+    }
+
+    pub fn instance(&self, profile_name: &str, instance_name: &str) -> Result<&profile::Instance, CliError> {
+        self.profile(profile_name)?
+            .instances
+            .as_ref()
+            .and_then(|instances| instances.get(instance_name))
+            .ok_or_else(|| {
+                CliError::NotFound(format!("Instance not found: {profile_name} / {instance_name}"))
+            })
+    }
+
+    /// Makes an instance the target of the services, the same way selecting it in the
+    /// application does.
+    pub fn select_instance(&mut self, profile_name: &str, instance_name: &str) -> Result<(), CliError> {
+        self.instance(profile_name, instance_name)?;
+        self.session.active_profile = Some(profile_name.to_owned());
+        self.session.active_instance = Some(instance_name.to_owned());
+        self.session.files.clear();
+        Ok(())
+    }
+
+    /// Writes the launch settings to the saved session. Everything else is kept as saved, for the
+    /// same reason as in [`UserData::save_profile`].
+    pub fn save_launch_options(&self) -> Result<(), CliError> {
+        let custom_path = self.session.custom_user_data_path.as_deref();
+        let mut session =
+            helpers::load_app_session(custom_path, helpers::Recovery::Never)?.unwrap_or_default();
+        session.launch_options = self.session.launch_options.clone();
+        session.active_zspy_config = self.session.active_zspy_config.clone();
+        session.active_renderer_backend = self.session.active_renderer_backend.clone();
+        helpers::save_app_session(&session, custom_path).map_err(|err| {
+            CliError::from(
+                ErrorContext::builder()
+                    .error(
+                        error::Error::file_system("Could not save session", "Save Session").with_source(&err),
+                    )
+                    .build(),
+            )
+        })
+    }
+
+    /// Writes a profile back to the user data directory. The session is left untouched, so that
+    /// commands do not change what the application shows on its next start.
+    pub fn save_profile(&self, profile_name: &str) -> Result<(), CliError> {
+        let profile = self.profile(profile_name)?;
+        let mod_storage_root = helpers::mod_storage_root(&self.session, profile_name);
+        helpers::save_profile(
+            &profile.to_portable(&mod_storage_root),
+            self.session.custom_user_data_path.as_deref(),
+        )
+        .map_err(|err| {
+            CliError::from(
+                ErrorContext::builder()
+                    .error(
+                        error::Error::file_system("Could not save profile", "Save Profile").with_source(&err),
+                    )
+                    .build(),
+            )
+        })
+    }
+}
+
+pub fn run(command: Command, user_data_dir: Option<&Path>, json: bool) -> ExitCode {
+    let result = UserData::load(user_data_dir).and_then(|mut data| match command {
+        Command::Profiles { command: ProfilesCommand::List } => list::profiles(&data),
+        Command::Instances { command: InstancesCommand::List { profile } } => {
+            list::instances(&data, &profile)
+        }
+        Command::Mods { command } => match command {
+            ModsCommand::List { profile, instance } => list::mods(&data, &profile, &instance),
+            ModsCommand::Install { profile, instance, path } => {
+                mods::install(&mut data, &profile, &instance, &path)
+            }
+            ModsCommand::Uninstall { profile, instance, r#mod } => {
+                mods::uninstall(&mut data, &profile, &instance, &r#mod)
+            }
+            ModsCommand::Enable { profile, instance, r#mod } => {
+                mods::toggle(&mut data, &profile, &instance, &r#mod, true)
+            }
+            ModsCommand::Disable { profile, instance, r#mod } => {
+                mods::toggle(&mut data, &profile, &instance, &r#mod, false)
+            }
+            ModsCommand::Reorder { profile, instance, r#mod, position } => {
+                mods::reorder(&mut data, &profile, &instance, &r#mod, position)
+            }
+        },
+        Command::Apply { profile, instance, modpack } => {
+            modpack::apply(&mut data, &profile, &instance, &modpack)
+        }
+        Command::Plan { profile, instance } => deploy::plan(&data, &profile, &instance),
+        Command::Deploy { profile, instance } => deploy::deploy(&data, &profile, &instance),
+        Command::Purge { profile } => deploy::purge(&data, &profile),
+        Command::Launch { profile, instance, zspy, wait, args } => {
+            deploy::launch(&data, &profile, &instance, zspy, wait, args)
+        }
+        Command::Diagnostics { profile, instance, output } => {
+            diagnostics::export(&data, profile, instance, output)
+        }
+    });
+
+    match result {
+        Ok(output) if json => {
+            println!("{}", serde_json::to_string_pretty(&output.json).unwrap_or_default());
+            ExitCode::SUCCESS
+        }
+        Ok(output) => {
+            println!("{}", output.text.trim_end());
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            if json {
+                let error = serde_json::json!({
+                    "error": err.message(),
+                    "code": err.code(),
+                    "path": err.path(),
+                    "suggested_action": err.suggested_action(),
+                    "exit_code": err.exit_code(),
+                });
+                eprintln!("{}", serde_json::to_string_pretty(&error).unwrap_or_default());
+            } else {
+                eprintln!("error: {}", err.message());
+                if let Some(suggested_action) = err.suggested_action() {
+                    eprintln!("hint: {suggested_action}");
+                }
+            }
+
+            ExitCode::from(err.exit_code())
+        }
+    }
+}
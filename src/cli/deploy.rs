@@ -0,0 +1,196 @@
+use std::fmt::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use serde::Serialize;
+
+use crate::app::session;
+use crate::cli::CliError;
+use crate::cli::Output;
+use crate::cli::UserData;
+use crate::core::deploy;
+use crate::core::helpers;
+use crate::core::launch;
+
+/// Directory inside the profile directory keeping the zSpy logs collected with `launch --wait`.
+pub const ZSPY_LOGS_DIR_NAME: &str = "zspy";
+
+/// Outcome of `plan`, `deploy` and `purge`.
+#[derive(Debug, Serialize)]
+pub struct DeployReport {
+    pub action: &'static str,
+    pub profile: String,
+    pub game_dir: PathBuf,
+    /// Instance deployed once the action is done, `None` after a purge.
+    pub instance: Option<String>,
+    /// Whether the action changes, or for `plan` would change, the game directory.
+    pub changed: bool,
+    pub summary: String,
+    pub files: Vec<deploy::PlannedFile>,
+}
+
+impl DeployReport {
+    fn new(
+        action: &'static str,
+        deployment: &deploy::Deployment,
+        profile: &str,
+        plan: deploy::DeployPlan,
+    ) -> Self {
+        Self {
+            action,
+            profile: profile.to_owned(),
+            game_dir: deployment.game_dir.clone(),
+            instance: plan.instance.clone(),
+            changed: !plan.is_up_to_date(),
+            summary: plan.summary(),
+            files: plan.files,
+        }
+    }
+
+    fn output(self) -> Output {
+        let mut text = String::new();
+        let changes = self.files.iter().filter(|file| file.action != deploy::DeployAction::Keep);
+        for file in changes {
+            let _ = write!(text, "  {:<8} {}", file.action, file.path.display());
+            match &file.mod_name {
+                Some(mod_name) => {
+                    let _ = writeln!(text, "  ({mod_name})");
+                }
+                None => text.push('\n'),
+            }
+        }
+
+        let _ = writeln!(text, "{}: {}", self.action, self.summary);
+        Output::new(&self, text)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LaunchReport {
+    pub profile: String,
+    pub instance: String,
+    pub command: launch::LaunchCommand,
+    pub pid: u32,
+    /// Exit code of the game, only known with `--wait`.
+    pub exit_code: Option<i32>,
+    /// Copy of the zSpy log written during the run, only collected with `--wait`.
+    pub collected_zspy_log: Option<PathBuf>,
+}
+
+pub fn plan(data: &UserData, profile: &str, instance: &str) -> Result<Output, CliError> {
+    let deployment = deployment(data, profile)?;
+    let manifest = deployment.load_manifest()?;
+    let plan = deployment.plan(Some(data.instance(profile, instance)?), &manifest);
+    Ok(DeployReport::new("plan", &deployment, profile, plan).output())
+}
+
+pub fn deploy(data: &UserData, profile: &str, instance: &str) -> Result<Output, CliError> {
+    let deployment = deployment(data, profile)?;
+    let plan = deployment.deploy(data.instance(profile, instance)?)?;
+    Ok(DeployReport::new("deploy", &deployment, profile, plan).output())
+}
+
+pub fn purge(data: &UserData, profile: &str) -> Result<Output, CliError> {
+    let deployment = deployment(data, profile)?;
+    let plan = deployment.purge()?;
+    Ok(DeployReport::new("purge", &deployment, profile, plan).output())
+}
+
+/// Starts the game of a profile. The instance has to be deployed and up to date, so that a
+/// script never tests something other than what it asked for.
+pub fn launch(
+    data: &UserData,
+    profile: &str,
+    instance: &str,
+    zspy: Option<u8>,
+    wait: bool,
+    args: Vec<String>,
+) -> Result<Output, CliError> {
+    let deployment = deployment(data, profile)?;
+    let manifest = deployment.load_manifest()?;
+    let plan = deployment.plan(Some(data.instance(profile, instance)?), &manifest);
+    let deployed = manifest.instance.as_deref() == Some(instance) || manifest.files.is_empty();
+    if !deployed || !plan.is_up_to_date() {
+        return Err(CliError::NotDeployed(format!(
+            "{profile} / {instance} is not deployed: {}",
+            plan.summary()
+        )));
+    }
+
+    let mut options = data.session.launch_options.clone().unwrap_or_default();
+    if let Some(level) = zspy {
+        options.game_settings.zspy =
+            session::ZspyConfig { is_enabled: level > 0, verbosity: session::ZSpyVerbosity::from(level) };
+    }
+
+    let command = launch::LaunchCommand::new(data.profile(profile)?, &options)?.with_args(args);
+    let previous_zspy_log = command.zspy_log.as_deref().and_then(modified_time);
+    let mut child = command.spawn()?;
+    let pid = child.id();
+    let (exit_code, collected_zspy_log) = match wait {
+        true => {
+            let status = child.wait().map_err(|err| CliError::GameFailed(err.to_string()))?;
+            let collected_zspy_log = command
+                .zspy_log
+                .as_deref()
+                .filter(|log| modified_time(log).is_some_and(|modified| Some(modified) != previous_zspy_log))
+                .and_then(|log| collect_zspy_log(data, profile, instance, log));
+            if !status.success() {
+                let log_hint = collected_zspy_log
+                    .as_ref()
+                    .map(|log| format!(", zSpy log kept as {}", log.display()))
+                    .unwrap_or_default();
+                return Err(CliError::GameFailed(format!("The game exited with {status}{log_hint}")));
+            }
+            (status.code(), collected_zspy_log)
+        }
+        false => (None, None),
+    };
+
+    let mut text = format!("launch: {} {} (pid {pid})", command.program.display(), command.args.join(" "));
+    if let Some(log) = &command.zspy_log {
+        let _ = write!(text, "\nzSpy log: {}", log.display());
+    }
+    if let Some(log) = &collected_zspy_log {
+        let _ = write!(text, "\nzSpy log kept as {}", log.display());
+    }
+
+    Ok(Output::new(
+        &LaunchReport {
+            profile: profile.to_owned(),
+            instance: instance.to_owned(),
+            command,
+            pid,
+            exit_code,
+            collected_zspy_log,
+        },
+        text,
+    ))
+}
+
+/// Copies the zSpy log of a finished run into the profile directory, where the next launch does
+/// not overwrite it.
+fn collect_zspy_log(data: &UserData, profile: &str, instance: &str, log: &Path) -> Option<PathBuf> {
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let target = helpers::profile_dir(profile, data.session.custom_user_data_path.as_deref())
+        .join(ZSPY_LOGS_DIR_NAME)
+        .join(format!("{instance}-{timestamp}.log"));
+    let copied =
+        target.parent().map_or(Ok(()), std::fs::create_dir_all).and_then(|()| std::fs::copy(log, &target));
+    match copied {
+        Ok(_) => Some(target),
+        Err(err) => {
+            tracing::warn!("Could not keep {} as {}: {err}", log.display(), target.display());
+            None
+        }
+    }
+}
+
+fn deployment(data: &UserData, profile: &str) -> Result<deploy::Deployment, CliError> {
+    Ok(deploy::Deployment::new(data.profile(profile)?, data.session.custom_user_data_path.as_deref()))
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
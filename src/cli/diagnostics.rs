@@ -0,0 +1,48 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::cli::CliError;
+use crate::cli::Output;
+use crate::cli::UserData;
+use crate::core::diagnostics;
+
+#[derive(Debug, Serialize)]
+pub struct DiagnosticsOutput {
+    pub path: PathBuf,
+    pub report: diagnostics::DiagnosticsReport,
+}
+
+/// Writes a diagnostics bundle for a profile and instance, by default the ones active in the
+/// session, to `output` or a timestamped file in the current directory.
+pub fn export(
+    data: &UserData,
+    profile: Option<String>,
+    instance: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<Output, CliError> {
+    let profile_name = profile.or_else(|| data.session.active_profile.clone());
+    let instance_name = instance.or_else(|| data.session.active_instance.clone());
+
+    let profile = profile_name.as_deref().map(|name| data.profile(name)).transpose()?;
+    let instance = match (profile_name.as_deref(), instance_name.as_deref()) {
+        (Some(profile_name), Some(instance_name)) => Some(data.instance(profile_name, instance_name)?),
+        _ => None,
+    };
+
+    let path = output.unwrap_or_else(|| PathBuf::from(diagnostics::default_file_name()));
+    let source = diagnostics::DiagnosticsSource { session: &data.session, profile, instance };
+    let report = diagnostics::write_diagnostics(&path, &source)?;
+
+    let mut text = String::new();
+    for file in &report.files {
+        let _ = writeln!(text, "  {file}");
+    }
+    for missing in &report.missing {
+        let _ = writeln!(text, "  skipped {missing}");
+    }
+    let _ = writeln!(text, "Diagnostics written to {}", path.display());
+
+    Ok(Output::new(&DiagnosticsOutput { path, report }, text))
+}
@@ -0,0 +1,177 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::cli::CliError;
+use crate::cli::Output;
+use crate::cli::UserData;
+use crate::core::constants;
+
+#[derive(Debug, Serialize)]
+pub struct ProfileSummary {
+    pub name: String,
+    pub game_type: String,
+    pub custom: bool,
+    pub active: bool,
+    pub game_dir: Option<PathBuf>,
+    pub instances: Vec<String>,
+    /// Why the profile could not be loaded.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InstanceSummary {
+    pub name: String,
+    pub active: bool,
+    pub mods: usize,
+    pub enabled_mods: usize,
+    pub disabled_files: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModSummary {
+    /// Position in the load order, later mods override earlier ones.
+    pub position: usize,
+    pub name: String,
+    pub enabled: bool,
+    pub version: Option<String>,
+    pub files: usize,
+    pub path: PathBuf,
+}
+
+/// Lists the default profiles first, followed by the custom ones by name. Profiles that could not
+/// be loaded are listed with their error.
+pub fn profiles(data: &UserData) -> Result<Output, CliError> {
+    let default_names = constants::DefaultProfile::into_iter().map(|game_type| game_type.to_string());
+    let mut custom_names = data
+        .profiles()
+        .values()
+        .filter(|profile| profile.is_custom)
+        .map(|profile| profile.name.clone())
+        .chain(data.broken_profiles.keys().cloned())
+        .filter(|name| constants::DefaultProfile::into_iter().all(|game_type| game_type.to_string() != *name))
+        .collect::<Vec<_>>();
+    custom_names.sort_unstable();
+
+    let summaries = default_names
+        .chain(custom_names)
+        .filter_map(|name| {
+            if let Some(err) = data.broken_profiles.get(&name) {
+                let default_type = constants::DefaultProfile::into_iter().find(|t| t.to_string() == name);
+                return Some(ProfileSummary {
+                    game_type: default_type.map(|t| t.to_string()).unwrap_or("Unknown".to_owned()),
+                    custom: default_type.is_none(),
+                    active: data.session.active_profile.as_ref() == Some(&name),
+                    game_dir: None,
+                    instances: Vec::new(),
+                    error: Some(err.error.to_string()),
+                    name,
+                });
+            }
+
+            let profile = data.profiles().get(&name)?;
+            let mut instances = profile
+                .instances
+                .as_ref()
+                .map(|instances| instances.keys().cloned().collect::<Vec<_>>())
+                .unwrap_or_default();
+            instances.sort_unstable();
+
+            Some(ProfileSummary {
+                name: profile.name.clone(),
+                game_type: profile.game_type.to_string(),
+                custom: profile.is_custom,
+                active: data.session.active_profile.as_ref() == Some(&profile.name),
+                game_dir: (!profile.path.as_os_str().is_empty()).then(|| profile.path.clone()),
+                instances,
+                error: None,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut text = String::new();
+    for summary in &summaries {
+        let marker = if summary.active { "*" } else { " " };
+        if let Some(error) = &summary.error {
+            let _ =
+                writeln!(text, "{marker} {:<24} {:<24} unreadable: {error}", summary.name, summary.game_type);
+            continue;
+        }
+
+        let game_dir = summary.game_dir.as_ref().map(|dir| dir.display().to_string());
+        let _ = writeln!(
+            text,
+            "{marker} {:<24} {:<24} {:>3} instances  {}",
+            summary.name,
+            summary.game_type,
+            summary.instances.len(),
+            game_dir.as_deref().unwrap_or("<no game directory>")
+        );
+    }
+
+    Ok(Output::new(&summaries, text))
+}
+
+pub fn instances(data: &UserData, profile_name: &str) -> Result<Output, CliError> {
+    let profile = data.profile(profile_name)?;
+    let is_active_profile = data.session.active_profile.as_deref() == Some(profile_name);
+
+    let mut summaries = profile
+        .instances
+        .iter()
+        .flat_map(|instances| instances.values())
+        .map(|instance| InstanceSummary {
+            name: instance.name.clone(),
+            active: is_active_profile && data.session.active_instance.as_ref() == Some(&instance.name),
+            mods: instance.mods.len(),
+            enabled_mods: instance.mods.iter().filter(|mod_info| mod_info.enabled).count(),
+            disabled_files: instance.files.values().filter(|metadata| !metadata.enabled).count(),
+        })
+        .collect::<Vec<_>>();
+    summaries.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+    let mut text = String::new();
+    for summary in &summaries {
+        let marker = if summary.active { "*" } else { " " };
+        let _ = writeln!(
+            text,
+            "{marker} {:<24} {}/{} mods enabled, {} files disabled",
+            summary.name, summary.enabled_mods, summary.mods, summary.disabled_files
+        );
+    }
+
+    Ok(Output::new(&summaries, text))
+}
+
+pub fn mods(data: &UserData, profile_name: &str, instance_name: &str) -> Result<Output, CliError> {
+    let instance = data.instance(profile_name, instance_name)?;
+    let summaries = instance
+        .mods
+        .iter()
+        .enumerate()
+        .map(|(position, mod_info)| ModSummary {
+            position: position + 1,
+            name: mod_info.name.clone(),
+            enabled: mod_info.enabled,
+            version: mod_info.version.clone(),
+            files: mod_info.files.len(),
+            path: mod_info.path.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    let mut text = String::new();
+    for summary in &summaries {
+        let state = if summary.enabled { "enabled" } else { "disabled" };
+        let _ = writeln!(
+            text,
+            "{:>3}. [{state:<8}] {:<32} {:<12} {} files",
+            summary.position,
+            summary.name,
+            summary.version.as_deref().unwrap_or("-"),
+            summary.files
+        );
+    }
+
+    Ok(Output::new(&summaries, text))
+}
@@ -0,0 +1,41 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::cli::CliError;
+use crate::cli::Output;
+use crate::cli::UserData;
+use crate::core::modpack;
+use crate::core::services;
+
+pub fn apply(data: &mut UserData, profile: &str, instance: &str, path: &Path) -> Result<Output, CliError> {
+    data.select_instance(profile, instance)?;
+    let modpack = modpack::Modpack::load(path)?;
+    let report = services::modlist::ModListService::new(&mut data.session, &mut data.state)
+        .try_apply_modpack(&modpack)?;
+
+    if !report.is_empty() {
+        data.save_profile(profile)?;
+    }
+
+    if report.launch_settings {
+        data.save_launch_options()?;
+    }
+
+    let mut text = String::new();
+    let changes = [
+        ("install", &report.installed),
+        ("reinstall", &report.reinstalled),
+        ("remove", &report.removed),
+        ("move", &report.moved),
+        ("enable", &report.enabled),
+        ("disable", &report.disabled),
+    ];
+    for (action, names) in changes {
+        names.iter().for_each(|name| {
+            let _ = writeln!(text, "  {action:<9} {name}");
+        });
+    }
+
+    let _ = writeln!(text, "apply: {}", report.summary());
+    Ok(Output::new(&report, text))
+}
@@ -0,0 +1,130 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::cli::CliError;
+use crate::cli::Output;
+use crate::cli::UserData;
+use crate::core::services;
+
+/// Outcome of a command that changed a mod.
+#[derive(Debug, Serialize)]
+pub struct ModChange {
+    pub action: &'static str,
+    pub name: String,
+    /// Position in the load order after the change, `None` once the mod is uninstalled.
+    pub position: Option<usize>,
+    /// Whether the command actually changed anything.
+    pub changed: bool,
+}
+
+impl ModChange {
+    fn output(self) -> Output {
+        let text = match (self.changed, self.position) {
+            (false, _) => format!("{}: nothing to do for \"{}\"", self.action, self.name),
+            (true, Some(position)) => format!("{}: \"{}\" at position {position}", self.action, self.name),
+            (true, None) => format!("{}: \"{}\"", self.action, self.name),
+        };
+
+        Output::new(&self, text)
+    }
+}
+
+pub fn install(data: &mut UserData, profile: &str, instance: &str, path: &Path) -> Result<Output, CliError> {
+    data.select_instance(profile, instance)?;
+    let installed = data.instance(profile, instance)?.mods.len();
+
+    services::mods::ModService::new(&mut data.session, &mut data.state).try_add_mod(path)?;
+    let mods = &data.instance(profile, instance)?.mods;
+    let changed = mods.len() > installed;
+    let name = mods.last().filter(|_| changed).map(|mod_info| mod_info.name.clone());
+    let position = changed.then_some(mods.len());
+    if changed {
+        data.save_profile(profile)?;
+    }
+
+    Ok(ModChange {
+        action: "install",
+        name: name.unwrap_or_else(|| path.display().to_string()),
+        position,
+        changed,
+    }
+    .output())
+}
+
+pub fn uninstall(
+    data: &mut UserData,
+    profile: &str,
+    instance: &str,
+    mod_ref: &str,
+) -> Result<Output, CliError> {
+    data.select_instance(profile, instance)?;
+    let (index, name) = find_mod(data, profile, instance, mod_ref)?;
+
+    services::mods::ModService::new(&mut data.session, &mut data.state).try_remove_mod(index, None)?;
+    data.save_profile(profile)?;
+    Ok(ModChange { action: "uninstall", name, position: None, changed: true }.output())
+}
+
+pub fn toggle(
+    data: &mut UserData,
+    profile: &str,
+    instance: &str,
+    mod_ref: &str,
+    enabled: bool,
+) -> Result<Output, CliError> {
+    data.select_instance(profile, instance)?;
+    let (index, name) = find_mod(data, profile, instance, mod_ref)?;
+    let changed = data.instance(profile, instance)?.mods[index].enabled != enabled;
+
+    if changed {
+        services::mods::ModService::new(&mut data.session, &mut data.state).try_toggle_mod(index, enabled)?;
+        data.save_profile(profile)?;
+    }
+
+    let action = if enabled { "enable" } else { "disable" };
+    Ok(ModChange { action, name, position: Some(index + 1), changed }.output())
+}
+
+pub fn reorder(
+    data: &mut UserData,
+    profile: &str,
+    instance: &str,
+    mod_ref: &str,
+    position: usize,
+) -> Result<Output, CliError> {
+    data.select_instance(profile, instance)?;
+    let (index, name) = find_mod(data, profile, instance, mod_ref)?;
+    let mod_count = data.instance(profile, instance)?.mods.len();
+    if !(1..=mod_count).contains(&position) {
+        return Err(CliError::InvalidArgument(format!("Position {position} is out of range 1-{mod_count}")));
+    }
+
+    let changed = index + 1 != position;
+    if changed {
+        services::mods::ModService::new(&mut data.session, &mut data.state)
+            .try_move_mod(index, position - 1)?;
+        data.save_profile(profile)?;
+    }
+
+    Ok(ModChange { action: "reorder", name, position: Some(position), changed }.output())
+}
+
+/// Finds a mod by name or by its position in the load order, starting at 1.
+fn find_mod(
+    data: &UserData,
+    profile: &str,
+    instance: &str,
+    mod_ref: &str,
+) -> Result<(usize, String), CliError> {
+    let mods = &data.instance(profile, instance)?.mods;
+    let by_position = || {
+        mod_ref.parse::<usize>().ok().filter(|position| (1..=mods.len()).contains(position)).map(|p| p - 1)
+    };
+
+    mods.iter()
+        .position(|mod_info| mod_info.name == mod_ref)
+        .or_else(by_position)
+        .map(|index| (index, mods[index].name.clone()))
+        .ok_or_else(|| CliError::NotFound(format!("Mod not found: {mod_ref}")))
+}
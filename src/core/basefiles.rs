@@ -0,0 +1,237 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::core::profile::Instance;
+use crate::core::profile::Lookup;
+use crate::core::schema;
+use crate::error::Error;
+use crate::error::ErrorContext;
+
+/// File next to `profile.json` recording the base files found by the last scan of the game
+/// directory. It has an entry per file of the game, so it is kept out of the profile itself.
+pub const BASE_FILES_FILE_NAME: &str = "base_files.json";
+
+/// Size and modification time of a base game file, used to notice files replaced by a patch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl FileStamp {
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some(Self { is_dir: metadata.is_dir(), size: metadata.len(), modified: metadata.modified().ok() })
+    }
+}
+
+/// Contents of [`BASE_FILES_FILE_NAME`], with paths relative to the game directory.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct BaseFileIndex {
+    pub files: Lookup<PathBuf, FileStamp>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BaseFileChanges {
+    pub added: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+    /// Only filled when the previous scan recorded stamps.
+    pub changed: Vec<PathBuf>,
+}
+
+impl BaseFileChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        match self.is_empty() {
+            true => "Base files are up to date".to_owned(),
+            false => format!(
+                "{} added, {} removed, {} changed base files",
+                self.added.len(),
+                self.removed.len(),
+                self.changed.len()
+            ),
+        }
+    }
+}
+
+/// Walks the game directory the same way creating an instance does.
+pub fn scan_base_files(game_dir: &Path) -> Lookup<PathBuf, FileStamp> {
+    ignore::WalkBuilder::new(game_dir)
+        .ignore(false)
+        .build()
+        .flatten()
+        .filter(|entry| entry.path() != game_dir)
+        .filter_map(|entry| Some((entry.path().to_path_buf(), FileStamp::of(entry.path())?)))
+        .collect()
+}
+
+/// Loads the base files recorded in `profile_dir`, resolved against `game_dir`. Profiles that were
+/// never scanned have none.
+pub fn load_base_files(
+    profile_dir: &Path,
+    game_dir: &Path,
+) -> Result<Lookup<PathBuf, FileStamp>, ErrorContext> {
+    let index: BaseFileIndex = schema::BASE_FILES_SCHEMA
+        .read(&profile_dir.join(BASE_FILES_FILE_NAME), |_| false)?
+        .unwrap_or_default();
+    Ok(index.files.into_iter().map(|(path, stamp)| (game_dir.join(path), stamp)).collect())
+}
+
+/// Records the base files of a scan of `game_dir` in `profile_dir`.
+pub fn save_base_files(
+    profile_dir: &Path,
+    game_dir: &Path,
+    base_files: &Lookup<PathBuf, FileStamp>,
+) -> Result<(), ErrorContext> {
+    let index = BaseFileIndex {
+        files: base_files
+            .iter()
+            .filter_map(|(path, stamp)| Some((path.strip_prefix(game_dir).ok()?.to_path_buf(), *stamp)))
+            .collect(),
+    };
+
+    let path = profile_dir.join(BASE_FILES_FILE_NAME);
+    std::fs::create_dir_all(profile_dir).and_then(|_| schema::BASE_FILES_SCHEMA.write(&path, &index)).map_err(
+        |err| {
+            ErrorContext::builder()
+                .error(
+                    Error::file_system("Could not write the base file index", "Save Base Files")
+                        .with_path(&path)
+                        .with_source(&err),
+                )
+                .build()
+        },
+    )
+}
+
+/// Paths an instance treats as base game files, including the ones currently overridden by a mod.
+/// Used for profiles that were set up before base file stamps were recorded.
+pub fn instance_base_paths(instance: &Instance) -> impl Iterator<Item = &PathBuf> {
+    let active = instance.files.iter().filter(|(_, metadata)| metadata.parent_name == instance.name);
+    let overridden = instance
+        .conflicts
+        .iter()
+        .filter(|(_, providers)| providers.values().any(|metadata| metadata.parent_name == instance.name));
+
+    active.map(|(path, _)| path).chain(overridden.map(|(path, _)| path))
+}
+
+/// Compares two scans. Stamps are only compared when `compare_stamps` is set, i.e. when the
+/// previous scan actually recorded them.
+pub fn detect_changes(
+    previous: &Lookup<PathBuf, FileStamp>,
+    current: &Lookup<PathBuf, FileStamp>,
+    compare_stamps: bool,
+) -> BaseFileChanges {
+    let mut changes = BaseFileChanges {
+        added: current.keys().filter(|path| !previous.contains_key(*path)).cloned().collect(),
+        removed: previous.keys().filter(|path| !current.contains_key(*path)).cloned().collect(),
+        changed: Vec::new(),
+    };
+
+    if compare_stamps {
+        changes.changed = current
+            .iter()
+            .filter(|(_, stamp)| !stamp.is_dir)
+            .filter(|(path, stamp)| previous.get(*path).is_some_and(|previous| previous != *stamp))
+            .map(|(path, _)| path.clone())
+            .collect();
+    }
+
+    changes.added.sort_unstable();
+    changes.removed.sort_unstable();
+    changes.changed.sort_unstable();
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::profile::Conflicts;
+    use crate::core::profile::FileMetadata;
+    use crate::core::testing::TempDir;
+
+    fn stamp(size: u64) -> FileStamp {
+        FileStamp { is_dir: false, size, modified: None }
+    }
+
+    #[test]
+    fn changes_are_detected_by_path_and_stamp() {
+        let previous = Lookup::from_iter([
+            (PathBuf::from("/game/Data"), FileStamp { is_dir: true, ..Default::default() }),
+            (PathBuf::from("/game/Data/Kept.vdf"), stamp(1)),
+            (PathBuf::from("/game/Data/Patched.vdf"), stamp(1)),
+            (PathBuf::from("/game/Data/Removed.vdf"), stamp(1)),
+        ]);
+        let current = Lookup::from_iter([
+            (PathBuf::from("/game/Data"), FileStamp { is_dir: true, size: 4096, modified: None }),
+            (PathBuf::from("/game/Data/Kept.vdf"), stamp(1)),
+            (PathBuf::from("/game/Data/Patched.vdf"), stamp(2)),
+            (PathBuf::from("/game/Data/Added.vdf"), stamp(1)),
+        ]);
+
+        let changes = detect_changes(&previous, &current, true);
+        assert_eq!(changes.added, [PathBuf::from("/game/Data/Added.vdf")]);
+        assert_eq!(changes.removed, [PathBuf::from("/game/Data/Removed.vdf")]);
+        assert_eq!(changes.changed, [PathBuf::from("/game/Data/Patched.vdf")]);
+        assert_eq!(changes.summary(), "1 added, 1 removed, 1 changed base files");
+
+        let without_stamps = detect_changes(&previous, &current, false);
+        assert!(without_stamps.changed.is_empty());
+        assert!(detect_changes(&current, &current, true).is_empty());
+    }
+
+    #[test]
+    fn base_paths_include_files_overridden_by_mods() {
+        let base = |path: &str| FileMetadata::new(true, Path::new(path), Path::new(path), "Default");
+        let patch = FileMetadata::new(
+            true,
+            Path::new("/mods/Patch/Data/A.vdf"),
+            Path::new("/game/Data/A.vdf"),
+            "Patch",
+        );
+
+        let mut instance = Instance::new(
+            "Default",
+            Lookup::from_iter([
+                (PathBuf::from("/game/Data/B.vdf"), base("/game/Data/B.vdf")),
+                (PathBuf::from("/game/Data/A.vdf"), patch.clone()),
+            ]),
+            Vec::new(),
+        );
+        instance.conflicts = Conflicts::new(Lookup::from_iter([(
+            PathBuf::from("/game/Data/A.vdf"),
+            Lookup::from_iter([(0, base("/game/Data/A.vdf")), (1, patch)]),
+        )]));
+
+        let mut paths = instance_base_paths(&instance).collect::<Vec<_>>();
+        paths.sort_unstable();
+        assert_eq!(paths, [Path::new("/game/Data/A.vdf"), Path::new("/game/Data/B.vdf")]);
+    }
+
+    #[test]
+    fn recorded_base_files_are_stored_relative_to_the_game_dir() {
+        let root = TempDir::new();
+        let game_dir = root.dir("Game");
+        root.file("Game/Data/Worlds.vdf", "worlds");
+        let scan = scan_base_files(&game_dir);
+
+        save_base_files(&root.path().join("profile"), &game_dir, &scan).unwrap();
+        let contents =
+            std::fs::read_to_string(root.path().join("profile").join(BASE_FILES_FILE_NAME)).unwrap();
+        assert!(!contents.contains(&*game_dir.to_string_lossy()));
+
+        let moved_dir = root.path().join("Moved");
+        let loaded = load_base_files(&root.path().join("profile"), &moved_dir).unwrap();
+        assert_eq!(loaded[&moved_dir.join("Data/Worlds.vdf")], scan[&game_dir.join("Data/Worlds.vdf")]);
+        assert!(load_base_files(&root.path().join("missing"), &game_dir).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,222 @@
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use chrono::Local;
+use serde::Deserialize;
+use serde::Serialize;
+use zip::write::SimpleFileOptions;
+
+use crate::app::session::GameLaunchConfiguration;
+use crate::core::constants;
+use crate::core::constants::DefaultProfile;
+use crate::core::fingerprint::GameVersion;
+use crate::core::profile::Instance;
+use crate::error::Error;
+use crate::error::ErrorContext;
+
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+pub const BUNDLE_EXTENSION: &str = "zip";
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+pub const MODS_DIR_NAME: &str = "mods";
+
+/// Placeholders replacing the machine specific game and mod storage directories in a manifest.
+pub const GAME_DIR_PLACEHOLDER: &str = "$GAME";
+pub const MOD_STORAGE_PLACEHOLDER: &str = "$MODS";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created: DateTime<Local>,
+    pub game_type: DefaultProfile,
+    pub game_version: Option<GameVersion>,
+    pub includes_mod_files: bool,
+    pub launch_options: Option<GameLaunchConfiguration>,
+    /// The exported instance with every path expressed relative to [`GAME_DIR_PLACEHOLDER`] or
+    /// [`MOD_STORAGE_PLACEHOLDER`].
+    pub instance: Instance,
+}
+
+impl BundleManifest {
+    pub fn new(
+        instance: &Instance,
+        game_dir: &Path,
+        mod_storage_dir: &Path,
+        game_type: DefaultProfile,
+        game_version: Option<GameVersion>,
+        launch_options: Option<GameLaunchConfiguration>,
+        includes_mod_files: bool,
+    ) -> Self {
+        let mut instance = instance.clone();
+        instance.map_paths(&|path| to_portable_path(path, game_dir, mod_storage_dir));
+
+        Self {
+            format_version: BUNDLE_FORMAT_VERSION,
+            app_version: constants::APP_VERSION.to_owned(),
+            created: Local::now(),
+            game_type,
+            game_version,
+            includes_mod_files,
+            launch_options,
+            instance,
+        }
+    }
+
+    /// Returns the bundled instance with its paths resolved against the local directories.
+    pub fn local_instance(&self, instance_name: &str, game_dir: &Path, mod_storage_dir: &Path) -> Instance {
+        let mut instance = self.instance.clone();
+        instance.map_paths(&|path| from_portable_path(path, game_dir, mod_storage_dir));
+        instance.rename(instance_name);
+        instance
+    }
+}
+
+/// Writes the manifest and, if requested, the contents of `mod_storage_dir` into a zip archive.
+pub fn write_bundle(
+    bundle_path: &Path,
+    manifest: &BundleManifest,
+    mod_storage_dir: &Path,
+) -> Result<(), ErrorContext> {
+    let handle =
+        std::fs::File::create(bundle_path).map_err(|e| bundle_error(e, bundle_path, "Export Instance"))?;
+    let mut writer = zip::ZipWriter::new(handle);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    let manifest_json = serde_json::to_string_pretty(manifest)
+        .map_err(|e| bundle_error(e, bundle_path, "Export Instance"))?;
+    writer
+        .start_file(MANIFEST_FILE_NAME, options)
+        .map_err(|e| bundle_error(e, bundle_path, "Export Instance"))?;
+    writer
+        .write_all(manifest_json.as_bytes())
+        .map_err(|e| bundle_error(e, bundle_path, "Export Instance"))?;
+
+    if manifest.includes_mod_files && mod_storage_dir.exists() {
+        let entries = ignore::WalkBuilder::new(mod_storage_dir).ignore(false).hidden(false).build().flatten();
+        for entry in entries.filter(|e| e.path().is_file()) {
+            let relative_path = entry.path().strip_prefix(mod_storage_dir).unwrap();
+            let entry_name = std::iter::once(MODS_DIR_NAME.into())
+                .chain(relative_path.components().map(|c| c.as_os_str().to_string_lossy()))
+                .collect::<Vec<_>>()
+                .join("/");
+
+            tracing::trace!("Adding {entry_name}");
+            let mut file = std::fs::File::open(entry.path())
+                .map_err(|e| bundle_error(e, entry.path(), "Export Instance"))?;
+            writer
+                .start_file(entry_name, options)
+                .map_err(|e| bundle_error(e, bundle_path, "Export Instance"))?;
+            std::io::copy(&mut file, &mut writer)
+                .map_err(|e| bundle_error(e, bundle_path, "Export Instance"))?;
+        }
+    }
+
+    writer.finish().map_err(|e| bundle_error(e, bundle_path, "Export Instance"))?;
+    Ok(())
+}
+
+pub fn read_manifest(bundle_path: &Path) -> Result<BundleManifest, ErrorContext> {
+    let mut archive = open_bundle(bundle_path)?;
+    let mut manifest_json = String::new();
+    archive
+        .by_name(MANIFEST_FILE_NAME)
+        .map_err(|e| bundle_error(e, bundle_path, "Import Instance"))?
+        .read_to_string(&mut manifest_json)
+        .map_err(|e| bundle_error(e, bundle_path, "Import Instance"))?;
+
+    let manifest: BundleManifest =
+        serde_json::from_str(&manifest_json).map_err(|e| bundle_error(e, bundle_path, "Import Instance"))?;
+
+    if manifest.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(ErrorContext::builder()
+            .error(Error::profile_service(
+                format!("Unsupported bundle format version {}", manifest.format_version),
+                "Import Instance",
+            ))
+            .suggested_action("Update the application to import this bundle")
+            .build());
+    }
+
+    Ok(manifest)
+}
+
+/// Extracts the bundled mod files into `mod_storage_dir`.
+pub fn extract_mod_files(bundle_path: &Path, mod_storage_dir: &Path) -> Result<(), ErrorContext> {
+    let mut archive = open_bundle(bundle_path)?;
+    for index in 0..archive.len() {
+        let mut file =
+            archive.by_index(index).map_err(|e| bundle_error(e, bundle_path, "Import Instance"))?;
+        let Some(relative_path) =
+            file.enclosed_name().and_then(|p| p.strip_prefix(MODS_DIR_NAME).ok().map(Path::to_path_buf))
+        else {
+            continue;
+        };
+
+        if file.is_dir() {
+            continue;
+        }
+
+        let output_path = mod_storage_dir.join(relative_path);
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| bundle_error(e, parent, "Import Instance"))?;
+        }
+
+        let mut output_file = std::fs::File::create(&output_path)
+            .map_err(|e| bundle_error(e, &output_path, "Import Instance"))?;
+        std::io::copy(&mut file, &mut output_file)
+            .map_err(|e| bundle_error(e, &output_path, "Import Instance"))?;
+    }
+
+    Ok(())
+}
+
+/// Replaces the game or mod storage directory prefix with its placeholder and uses `/` as the
+/// separator so that the manifest can be imported on any platform. Empty directories never match.
+pub fn to_portable_path(path: &Path, game_dir: &Path, mod_storage_dir: &Path) -> PathBuf {
+    let strip_prefix = |dir: &Path| match dir.as_os_str().is_empty() {
+        true => None,
+        false => path.strip_prefix(dir).ok(),
+    };
+
+    let (placeholder, relative_path) = if let Some(relative_path) = strip_prefix(mod_storage_dir) {
+        (MOD_STORAGE_PLACEHOLDER, relative_path)
+    } else if let Some(relative_path) = strip_prefix(game_dir) {
+        (GAME_DIR_PLACEHOLDER, relative_path)
+    } else {
+        return path.to_path_buf();
+    };
+
+    let components = relative_path.components().map(|c| c.as_os_str().to_string_lossy());
+    PathBuf::from(std::iter::once(placeholder.into()).chain(components).collect::<Vec<_>>().join("/"))
+}
+
+pub fn from_portable_path(path: &Path, game_dir: &Path, mod_storage_dir: &Path) -> PathBuf {
+    let portable_path = path.to_string_lossy();
+    let mut components = portable_path.split(['/', '\\']).filter(|c| !c.is_empty());
+    let base_dir = match components.next() {
+        Some(GAME_DIR_PLACEHOLDER) => game_dir,
+        Some(MOD_STORAGE_PLACEHOLDER) => mod_storage_dir,
+        _ => return path.to_path_buf(),
+    };
+
+    components.fold(base_dir.to_path_buf(), |path, component| path.join(component))
+}
+
+fn open_bundle(bundle_path: &Path) -> Result<zip::ZipArchive<std::fs::File>, ErrorContext> {
+    let handle =
+        std::fs::File::open(bundle_path).map_err(|e| bundle_error(e, bundle_path, "Import Instance"))?;
+    zip::ZipArchive::new(handle).map_err(|e| bundle_error(e, bundle_path, "Import Instance"))
+}
+
+fn bundle_error(err: impl std::error::Error + 'static, path: &Path, operation: &str) -> ErrorContext {
+    ErrorContext::builder()
+        .error(
+            Error::file_system("Could not access the instance bundle", operation)
+                .with_path(path)
+                .with_source(&err),
+        )
+        .build()
+}
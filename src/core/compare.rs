@@ -0,0 +1,297 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use derive_more::Display;
+
+use crate::core::profile::Instance;
+use crate::core::profile::Lookup;
+use crate::core::profile::Profile;
+use crate::core::profile::parent_dirs;
+
+/// Winner label of files supplied by the game itself.
+pub const BASE_GAME_LABEL: &str = "<base game>";
+
+/// Identifies an instance across profiles.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Display)]
+#[display("{profile} / {instance}")]
+pub struct InstanceRef {
+    pub profile: String,
+    pub instance: String,
+}
+
+impl InstanceRef {
+    pub fn all(profiles: &Lookup<String, Profile>) -> Vec<InstanceRef> {
+        let mut refs = profiles
+            .iter()
+            .flat_map(|(profile_name, profile)| {
+                profile.instances.iter().flat_map(|instances| instances.keys()).map(|instance_name| {
+                    InstanceRef { profile: profile_name.clone(), instance: instance_name.clone() }
+                })
+            })
+            .collect::<Vec<_>>();
+        refs.sort_unstable_by(|a, b| (&a.profile, &a.instance).cmp(&(&b.profile, &b.instance)));
+        refs
+    }
+
+    pub fn resolve<'a>(&self, profiles: &'a Lookup<String, Profile>) -> Option<(&'a Profile, &'a Instance)> {
+        let profile = profiles.get(&self.profile)?;
+        let instance = profile.instances.as_ref()?.get(&self.instance)?;
+        Some((profile, instance))
+    }
+}
+
+/// A value that differs between the left and the right instance. `None` means the key is absent
+/// on that side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference<K, V> {
+    pub key: K,
+    pub left: Option<V>,
+    pub right: Option<V>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct InstanceComparison {
+    pub only_left: Vec<String>,
+    pub only_right: Vec<String>,
+    pub enabled: Vec<Difference<String, bool>>,
+    /// One-based positions of the shared mods in the load order of each instance.
+    pub load_order: Vec<Difference<String, usize>>,
+    /// Target paths relative to the game directory of each profile.
+    pub file_toggles: Vec<Difference<PathBuf, bool>>,
+    pub winners: Vec<Difference<PathBuf, String>>,
+}
+
+impl InstanceComparison {
+    pub fn new(left: (&Profile, &Instance), right: (&Profile, &Instance)) -> Self {
+        let (left_profile, left_instance) = left;
+        let (right_profile, right_instance) = right;
+
+        let left_mods = left_instance.mods.iter().map(|m| (m.name.clone(), m)).collect::<Lookup<_, _>>();
+        let right_mods = right_instance.mods.iter().map(|m| (m.name.clone(), m)).collect::<Lookup<_, _>>();
+
+        let mut only_left: Vec<String> =
+            left_mods.keys().filter(|name| !right_mods.contains_key(*name)).cloned().collect();
+        let mut only_right: Vec<String> =
+            right_mods.keys().filter(|name| !left_mods.contains_key(*name)).cloned().collect();
+        only_left.sort_unstable();
+        only_right.sort_unstable();
+
+        let shared_mods = left_mods
+            .iter()
+            .filter_map(|(name, m)| right_mods.get(name).map(|other| (name, *m, *other)))
+            .collect::<Vec<_>>();
+
+        // Priorities are only compared by their order among the shared mods, mods present on one
+        // side only would otherwise shift every priority above them.
+        let shared_names = shared_mods.iter().map(|(name, ..)| *name).collect::<Vec<_>>();
+        let left_positions = relative_positions(left_instance, &shared_names);
+        let right_positions = relative_positions(right_instance, &shared_names);
+
+        let mut enabled = Vec::new();
+        let mut load_order = Vec::new();
+        for (name, left_mod, right_mod) in shared_mods {
+            if left_mod.enabled != right_mod.enabled {
+                enabled.push(Difference {
+                    key: name.clone(),
+                    left: Some(left_mod.enabled),
+                    right: Some(right_mod.enabled),
+                });
+            }
+
+            let left_position = left_positions.get(name).copied();
+            let right_position = right_positions.get(name).copied();
+            if left_position != right_position {
+                load_order.push(Difference { key: name.clone(), left: left_position, right: right_position });
+            }
+        }
+        enabled.sort_unstable_by(|a, b| a.key.cmp(&b.key));
+        load_order.sort_unstable_by(|a, b| a.key.cmp(&b.key));
+
+        let left_files = relative_files(left_instance, &left_profile.path);
+        let right_files = relative_files(right_instance, &right_profile.path);
+
+        let mut file_toggles = Vec::new();
+        let mut winners = Vec::new();
+        for path in left_files.keys().chain(right_files.keys().filter(|p| !left_files.contains_key(*p))) {
+            let left_file = left_files.get(path);
+            let right_file = right_files.get(path);
+
+            let left_enabled = left_file.map(|(enabled, _)| *enabled);
+            let right_enabled = right_file.map(|(enabled, _)| *enabled);
+            if left_enabled != right_enabled {
+                file_toggles.push(Difference { key: path.clone(), left: left_enabled, right: right_enabled });
+            }
+
+            let left_winner = left_file.map(|(_, winner)| winner.clone());
+            let right_winner = right_file.map(|(_, winner)| winner.clone());
+            if left_winner != right_winner {
+                winners.push(Difference { key: path.clone(), left: left_winner, right: right_winner });
+            }
+        }
+        file_toggles.sort_unstable_by(|a, b| a.key.cmp(&b.key));
+        winners.sort_unstable_by(|a, b| a.key.cmp(&b.key));
+
+        Self { only_left, only_right, enabled, load_order, file_toggles, winners }
+    }
+
+    pub fn is_identical(&self) -> bool {
+        self.only_left.is_empty()
+            && self.only_right.is_empty()
+            && self.enabled.is_empty()
+            && self.load_order.is_empty()
+            && self.file_toggles.is_empty()
+            && self.winners.is_empty()
+    }
+}
+
+/// Ranks `names` by their priority in the load order of `instance`, starting at one. Mods without
+/// a priority are left out.
+fn relative_positions<'a>(instance: &Instance, names: &[&'a String]) -> Lookup<&'a String, usize> {
+    let mut ranked = names
+        .iter()
+        .filter_map(|name| instance.load_order.get(*name).map(|priority| (*priority, *name)))
+        .collect::<Vec<_>>();
+    ranked.sort_unstable();
+    ranked.into_iter().enumerate().map(|(position, (_, name))| (name, position + 1)).collect()
+}
+
+/// Maps every file target to its enabled state and the name of the mod (or base game) that
+/// supplies it. Base game entries are named after the instance, so they are labelled uniformly
+/// to keep instances with different names comparable.
+fn relative_files(instance: &Instance, game_dir: &Path) -> Lookup<PathBuf, (bool, String)> {
+    let dirs = parent_dirs(instance.files.keys());
+    instance
+        .files
+        .iter()
+        .filter(|(path, _)| !dirs.contains(path.as_path()))
+        .filter_map(|(path, metadata)| {
+            let relative_path = path.strip_prefix(game_dir).ok()?.to_path_buf();
+            let winner = match metadata.parent_name == instance.name {
+                true => BASE_GAME_LABEL.to_owned(),
+                false => metadata.parent_name.clone(),
+            };
+            Some((relative_path, (metadata.enabled, winner)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::profile::FileMetadata;
+    use crate::core::profile::ModInfo;
+    use crate::core::services::mods::ModService;
+
+    fn mod_info(name: &str, files: &[&str]) -> ModInfo {
+        let path = PathBuf::from("/mods").join(name);
+        let files = files
+            .iter()
+            .map(|file| {
+                let source = path.join(file);
+                (source.clone(), FileMetadata::new(true, &source, Path::new(""), name))
+            })
+            .collect();
+        ModInfo::new(true, name, &path, files)
+    }
+
+    /// Profile with one instance holding the given mods, installed at the given priorities.
+    fn profile(instance_name: &str, base_files: &[&str], mods: Vec<(usize, ModInfo)>) -> Profile {
+        let game_dir = Path::new("/game");
+        let files = base_files
+            .iter()
+            .map(|file| {
+                let path = game_dir.join(file);
+                (path.clone(), FileMetadata::new(true, &path, &path, instance_name))
+            })
+            .collect();
+
+        let mut instance = Instance::new(instance_name, files, Vec::new());
+        instance.load_order.insert(instance_name.to_owned(), 0);
+        for (priority, mod_info) in mods {
+            instance.load_order.insert(mod_info.name.clone(), priority);
+            let (mut files, mut conflicts) = (instance.files, instance.conflicts);
+            ModService::apply_mod_files(
+                &mut files,
+                &mut conflicts,
+                &mut instance.load_order,
+                game_dir,
+                &mod_info,
+            );
+            (instance.files, instance.conflicts) = (files, conflicts);
+            instance.mods.push(mod_info);
+        }
+
+        let mut profile = Profile::new("Gothic", game_dir);
+        profile.instances = Some(Lookup::from_iter([(instance_name.to_owned(), instance)]));
+        profile
+    }
+
+    fn compare(left: &Profile, right: &Profile) -> InstanceComparison {
+        let resolve = |profile: &Profile| {
+            let instance =
+                profile.instances.as_ref().and_then(|instances| instances.values().next()).unwrap();
+            (profile.clone(), instance.clone())
+        };
+        let (left_profile, left_instance) = resolve(left);
+        let (right_profile, right_instance) = resolve(right);
+        InstanceComparison::new((&left_profile, &left_instance), (&right_profile, &right_instance))
+    }
+
+    #[test]
+    fn compares_the_relative_load_order_of_shared_mods() {
+        let left = profile(
+            "Left",
+            &[],
+            vec![
+                (1, mod_info("Extra", &["Data/Extra.vdf"])),
+                (2, mod_info("A", &["Data/A.vdf"])),
+                (3, mod_info("B", &["Data/B.vdf"])),
+            ],
+        );
+        let right = profile(
+            "Right",
+            &[],
+            vec![(1, mod_info("A", &["Data/A.vdf"])), (2, mod_info("B", &["Data/B.vdf"]))],
+        );
+
+        let comparison = compare(&left, &right);
+        assert_eq!(comparison.only_left, ["Extra"]);
+        assert!(comparison.load_order.is_empty());
+
+        let swapped = profile(
+            "Right",
+            &[],
+            vec![(1, mod_info("B", &["Data/B.vdf"])), (2, mod_info("A", &["Data/A.vdf"]))],
+        );
+        let comparison = compare(&left, &swapped);
+        assert_eq!(
+            comparison.load_order,
+            vec![
+                Difference { key: "A".to_owned(), left: Some(1), right: Some(2) },
+                Difference { key: "B".to_owned(), left: Some(2), right: Some(1) },
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_directories_and_labels_base_files_uniformly() {
+        let left =
+            profile("Left", &["Data", "Data/Base.vdf"], vec![(1, mod_info("A", &["Data", "Data/A.vdf"]))]);
+        let right =
+            profile("Right", &["Data", "Data/Base.vdf"], vec![(1, mod_info("A", &["Data", "Data/A.vdf"]))]);
+
+        let comparison = compare(&left, &right);
+        assert!(comparison.is_identical(), "{comparison:?}");
+
+        let overriding = profile(
+            "Right",
+            &["Data", "Data/Base.vdf"],
+            vec![(1, mod_info("A", &["Data", "Data/Base.vdf"]))],
+        );
+        let comparison = compare(&left, &overriding);
+        let winners = comparison.winners.iter().map(|d| d.key.as_path()).collect::<Vec<_>>();
+        assert_eq!(winners, [Path::new("Data/A.vdf"), Path::new("Data/Base.vdf")]);
+        assert_eq!(comparison.winners[1].left.as_deref(), Some(BASE_GAME_LABEL));
+        assert_eq!(comparison.winners[1].right.as_deref(), Some("A"));
+    }
+}
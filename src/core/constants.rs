@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
 use derive_more::Display;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 pub const APP_NAME: &str = env!("CARGO_PKG_NAME");
@@ -35,12 +36,13 @@ pub fn local_app_data_path() -> PathBuf {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, Default, Serialize, Deserialize)]
 pub enum DefaultProfile {
     #[display("Gothic")]
     Gothic,
     #[display("Gothic 2 Classic")]
     Gothic2Classic,
+    #[default]
     #[display("Gothic 2 Night of Raven")]
     Gothic2NightOfRaven,
     #[display("Gothic 2 Sequel")]
@@ -57,4 +59,18 @@ impl DefaultProfile {
         ];
         PROFILES.iter()
     }
+
+    pub fn from_name(name: &str) -> Option<DefaultProfile> {
+        Self::into_iter().find(|profile| profile.to_string() == name).copied()
+    }
+
+    /// Path of the game executable relative to the game directory.
+    pub fn default_executable(&self) -> PathBuf {
+        match self {
+            DefaultProfile::Gothic => PathBuf::from("System").join("GothicMod.exe"),
+            DefaultProfile::Gothic2Classic
+            | DefaultProfile::Gothic2NightOfRaven
+            | DefaultProfile::GothicSequel => PathBuf::from("System").join("Gothic2.exe"),
+        }
+    }
 }
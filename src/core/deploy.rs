@@ -0,0 +1,440 @@
+//! Copying the mod files of an instance into the game directory and removing them again.
+//!
+//! Every deployed file is recorded in a manifest next to `profile.json`, so that a later
+//! deployment only touches what changed and a purge restores the game directory exactly. Base
+//! game files replaced by a mod are moved into a backup directory and put back once the mod file
+//! is removed.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use chrono::Local;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::core::basefiles::FileStamp;
+use crate::core::helpers;
+use crate::core::profile::Instance;
+use crate::core::profile::Lookup;
+use crate::core::profile::Profile;
+use crate::core::schema;
+use crate::error::Error;
+use crate::error::ErrorContext;
+
+pub const MANIFEST_FILE_NAME: &str = "deploy.json";
+pub const BACKUP_DIR_NAME: &str = "deploy-backup";
+
+/// Files currently deployed into the game directory of a profile.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeployManifest {
+    pub instance: Option<String>,
+    pub deployed_at: Option<DateTime<Local>>,
+    /// Deployed files by their path relative to the game directory.
+    pub files: Lookup<PathBuf, DeployedFile>,
+    /// Directories created for deployed files, relative to the game directory.
+    pub created_dirs: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeployedFile {
+    pub mod_name: String,
+    pub source: PathBuf,
+    pub size: u64,
+    /// Whether a file that was in the game directory before was moved to the backup directory.
+    pub backed_up: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeployAction {
+    /// Copies a mod file to a path that is free.
+    Add,
+    /// Backs up the file at the path, then copies the mod file over it.
+    Replace,
+    /// Copies a mod file over a file deployed before.
+    Update,
+    /// Leaves a deployed file that is still up to date.
+    Keep,
+    /// Deletes a deployed file and restores the file it replaced.
+    Remove,
+}
+
+impl std::fmt::Display for DeployAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            DeployAction::Add => "add",
+            DeployAction::Replace => "replace",
+            DeployAction::Update => "update",
+            DeployAction::Keep => "keep",
+            DeployAction::Remove => "remove",
+        };
+
+        f.pad(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlannedFile {
+    /// Path relative to the game directory.
+    pub path: PathBuf,
+    pub action: DeployAction,
+    pub mod_name: Option<String>,
+    pub source: Option<PathBuf>,
+}
+
+/// What deploying an instance, or purging with no instance, does to the game directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DeployPlan {
+    pub instance: Option<String>,
+    pub files: Vec<PlannedFile>,
+}
+
+impl DeployPlan {
+    pub fn count(&self, action: DeployAction) -> usize {
+        self.files.iter().filter(|file| file.action == action).count()
+    }
+
+    /// Whether applying the plan would leave the game directory as it is.
+    pub fn is_up_to_date(&self) -> bool {
+        self.files.iter().all(|file| file.action == DeployAction::Keep)
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} to add, {} to replace, {} to update, {} to remove, {} up to date",
+            self.count(DeployAction::Add),
+            self.count(DeployAction::Replace),
+            self.count(DeployAction::Update),
+            self.count(DeployAction::Remove),
+            self.count(DeployAction::Keep)
+        )
+    }
+}
+
+/// Deployment of a profile's instances into its game directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deployment {
+    pub game_dir: PathBuf,
+    pub manifest_path: PathBuf,
+    pub backup_dir: PathBuf,
+}
+
+impl Deployment {
+    pub fn new(profile: &Profile, custom_user_data_path: Option<&Path>) -> Self {
+        let profile_dir = helpers::profile_dir(&profile.name, custom_user_data_path);
+        Self {
+            game_dir: profile.path.clone(),
+            manifest_path: profile_dir.join(MANIFEST_FILE_NAME),
+            backup_dir: profile_dir.join(BACKUP_DIR_NAME),
+        }
+    }
+
+    /// Reads the manifest, which is empty while nothing is deployed.
+    pub fn load_manifest(&self) -> Result<DeployManifest, ErrorContext> {
+        Ok(schema::DEPLOY_SCHEMA.read(&self.manifest_path, |_| false)?.unwrap_or_default())
+    }
+
+    /// Compares the files an instance provides with the deployed ones. Without an instance every
+    /// deployed file is removed.
+    pub fn plan(&self, instance: Option<&Instance>, manifest: &DeployManifest) -> DeployPlan {
+        let wanted = instance.map(|instance| self.wanted_files(instance)).unwrap_or_default();
+        let size = |path: &Path| std::fs::metadata(path).ok().filter(|m| m.is_file()).map(|m| m.len());
+
+        let mut files = wanted
+            .iter()
+            .map(|(path, (mod_name, source))| {
+                let target = self.game_dir.join(path);
+                let action = match manifest.files.get(path) {
+                    Some(deployed)
+                        if deployed.source == *source
+                            && size(source) == Some(deployed.size)
+                            && size(&target) == Some(deployed.size) =>
+                    {
+                        DeployAction::Keep
+                    }
+                    Some(_) => DeployAction::Update,
+                    None if target.exists() => DeployAction::Replace,
+                    None => DeployAction::Add,
+                };
+
+                PlannedFile {
+                    path: path.clone(),
+                    action,
+                    mod_name: Some(mod_name.clone()),
+                    source: Some(source.clone()),
+                }
+            })
+            .chain(manifest.files.iter().filter(|(path, _)| !wanted.contains_key(*path)).map(
+                |(path, deployed)| PlannedFile {
+                    path: path.clone(),
+                    action: DeployAction::Remove,
+                    mod_name: Some(deployed.mod_name.clone()),
+                    source: None,
+                },
+            ))
+            .collect::<Vec<_>>();
+
+        files.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+        DeployPlan { instance: instance.map(|instance| instance.name.clone()), files }
+    }
+
+    /// Brings the game directory in line with an instance.
+    pub fn deploy(&self, instance: &Instance) -> Result<DeployPlan, ErrorContext> {
+        self.validate_game_dir("Deploy")?;
+        let mut manifest = self.load_manifest()?;
+        let plan = self.plan(Some(instance), &manifest);
+        self.apply(&plan, &mut manifest)?;
+        Ok(plan)
+    }
+
+    /// Removes every deployed file and restores the files they replaced.
+    pub fn purge(&self) -> Result<DeployPlan, ErrorContext> {
+        self.validate_game_dir("Purge")?;
+        let mut manifest = self.load_manifest()?;
+        let plan = self.plan(None, &manifest);
+        self.apply(&plan, &mut manifest)?;
+        Ok(plan)
+    }
+
+    /// Applies a plan, recording every step in the manifest. The manifest is saved even if a step
+    /// fails, so that the files deployed up to that point can still be purged.
+    pub fn apply(&self, plan: &DeployPlan, manifest: &mut DeployManifest) -> Result<(), ErrorContext> {
+        tracing::info!("Deploying into {}: {}", self.game_dir.display(), plan.summary());
+        let result = self.apply_files(plan, manifest);
+        self.remove_empty_dirs(manifest);
+
+        match result.is_ok() && manifest.files.is_empty() {
+            true => {
+                manifest.instance = None;
+                manifest.deployed_at = None;
+            }
+            false => {
+                manifest.instance = plan.instance.clone();
+                manifest.deployed_at = Some(Local::now());
+            }
+        }
+
+        schema::DEPLOY_SCHEMA.write(&self.manifest_path, manifest).map_err(|err| {
+            ErrorContext::builder()
+                .error(
+                    Error::file_system("Could not write the deploy manifest", "Save Deploy Manifest")
+                        .with_path(&self.manifest_path)
+                        .with_source(&err),
+                )
+                .build()
+        })?;
+
+        result
+    }
+
+    /// Adjusts a scan of the game directory for the deployed files: files added by a deployment
+    /// are left out, and files replaced by one are reported with the stamp of their backup.
+    pub fn restore_base_stamps(&self, manifest: &DeployManifest, scan: &mut Lookup<PathBuf, FileStamp>) {
+        manifest.files.iter().for_each(|(path, deployed)| {
+            let target = self.game_dir.join(path);
+            match deployed.backed_up.then(|| FileStamp::of(&self.backup_dir.join(path))).flatten() {
+                Some(stamp) => scan.insert(target, stamp),
+                None => scan.remove(&target),
+            };
+        });
+
+        manifest.created_dirs.iter().for_each(|dir| {
+            scan.remove(&self.game_dir.join(dir));
+        });
+    }
+
+    /// Enabled files of the enabled mods that win their conflicts, by path relative to the game
+    /// directory, with the mod providing them and their source.
+    fn wanted_files(&self, instance: &Instance) -> Lookup<PathBuf, (String, PathBuf)> {
+        instance
+            .files
+            .iter()
+            .filter(|(_, metadata)| metadata.enabled && metadata.parent_name != instance.name)
+            .filter(|(_, metadata)| !metadata.source_path.is_dir())
+            .filter_map(|(target, metadata)| {
+                let path = target.strip_prefix(&self.game_dir).ok()?;
+                Some((path.to_path_buf(), (metadata.parent_name.clone(), metadata.source_path.clone())))
+            })
+            .collect()
+    }
+
+    fn apply_files(&self, plan: &DeployPlan, manifest: &mut DeployManifest) -> Result<(), ErrorContext> {
+        // Removing first frees the paths the other steps may back up again.
+        let (removals, others): (Vec<_>, Vec<_>) =
+            plan.files.iter().partition(|file| file.action == DeployAction::Remove);
+
+        for file in removals.into_iter().chain(others) {
+            let target = self.game_dir.join(&file.path);
+            let backup = self.backup_dir.join(&file.path);
+            match (file.action, file.source.as_deref()) {
+                (DeployAction::Keep, _) => {}
+                (DeployAction::Remove, _) => {
+                    if target.is_file() {
+                        std::fs::remove_file(&target).map_err(|err| file_error(&target, err, "Purge"))?;
+                    }
+
+                    let deployed = manifest.files.remove(&file.path);
+                    if deployed.is_some_and(|deployed| deployed.backed_up) && backup.exists() {
+                        move_file(&backup, &target).map_err(|err| file_error(&target, err, "Purge"))?;
+                    }
+                }
+                (action, Some(source)) => {
+                    let backed_up = match action {
+                        DeployAction::Replace => {
+                            move_file(&target, &backup).map_err(|err| file_error(&target, err, "Deploy"))?;
+                            true
+                        }
+                        _ => manifest.files.get(&file.path).is_some_and(|deployed| deployed.backed_up),
+                    };
+
+                    self.create_parent_dirs(&file.path, manifest)?;
+                    let size =
+                        std::fs::copy(source, &target).map_err(|err| file_error(source, err, "Deploy"))?;
+                    manifest.files.insert(
+                        file.path.clone(),
+                        DeployedFile {
+                            mod_name: file.mod_name.clone().unwrap_or_default(),
+                            source: source.to_path_buf(),
+                            size,
+                            backed_up,
+                        },
+                    );
+                }
+                (_, None) => tracing::warn!("No source for {}, skipping", file.path.display()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates the missing parents of a deployed file, recording the ones that did not exist.
+    fn create_parent_dirs(&self, path: &Path, manifest: &mut DeployManifest) -> Result<(), ErrorContext> {
+        let mut missing = path
+            .ancestors()
+            .skip(1)
+            .filter(|dir| !dir.as_os_str().is_empty() && !self.game_dir.join(dir).exists())
+            .map(Path::to_path_buf)
+            .collect::<Vec<_>>();
+
+        missing.reverse();
+        for dir in missing {
+            let target = self.game_dir.join(&dir);
+            std::fs::create_dir(&target).map_err(|err| file_error(&target, err, "Deploy"))?;
+            manifest.created_dirs.push(dir);
+        }
+
+        Ok(())
+    }
+
+    /// Removes the created directories that no deployed file needs anymore, deepest first.
+    fn remove_empty_dirs(&self, manifest: &mut DeployManifest) {
+        manifest.created_dirs.sort_unstable_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+        manifest.created_dirs.retain(|dir| {
+            let target = self.game_dir.join(dir);
+            let is_empty = std::fs::read_dir(&target).map(|mut entries| entries.next().is_none());
+            match is_empty {
+                Ok(true) => std::fs::remove_dir(&target).is_err(),
+                Ok(false) => true,
+                Err(_) => false,
+            }
+        });
+    }
+
+    fn validate_game_dir(&self, operation: &str) -> Result<(), ErrorContext> {
+        match !self.game_dir.as_os_str().is_empty() && self.game_dir.is_dir() {
+            true => Ok(()),
+            false => Err(deploy_error(
+                format!("Game directory not found: {}", self.game_dir.display()),
+                operation,
+                "Set the game directory of the profile first",
+            )),
+        }
+    }
+}
+
+/// Renames a file, falling back to copying it when the paths are on different devices.
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::rename(from, to).or_else(|_| {
+        std::fs::copy(from, to)?;
+        std::fs::remove_file(from)
+    })
+}
+
+fn file_error(path: &Path, err: std::io::Error, operation: &str) -> ErrorContext {
+    ErrorContext::builder()
+        .error(
+            Error::file_system("Could not update the game directory", operation)
+                .with_path(path)
+                .with_source(&err),
+        )
+        .build()
+}
+
+fn deploy_error(msg: impl Into<String>, operation: &str, suggested_action: &str) -> ErrorContext {
+    ErrorContext::builder()
+        .error(Error::file_system(msg, operation))
+        .suggested_action(suggested_action)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::profile::FileMetadata;
+    use crate::core::testing::TempDir;
+
+    fn deployment(root: &TempDir) -> Deployment {
+        Deployment {
+            game_dir: root.dir("game"),
+            manifest_path: root.path().join("profile").join(MANIFEST_FILE_NAME),
+            backup_dir: root.path().join("profile").join(BACKUP_DIR_NAME),
+        }
+    }
+
+    fn instance(root: &TempDir, game_dir: &Path) -> Instance {
+        let files = [("Data/Worlds.vdf", "modded"), ("Data/New/Patch.vdf", "new")].map(|(path, contents)| {
+            let source = root.file(&format!("mods/Patch/{path}"), contents);
+            let target = game_dir.join(path);
+            (target.clone(), FileMetadata::new(true, &source, &target, "Patch"))
+        });
+        let base_file = game_dir.join("System/Gothic.ini");
+        let base_metadata = FileMetadata::new(true, &base_file, &base_file, "Default");
+
+        Instance::new(
+            "Default",
+            Lookup::from_iter(files.into_iter().chain([(base_file, base_metadata)])),
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn purge_restores_the_files_a_deployment_replaced() {
+        let root = TempDir::new();
+        let deployment = deployment(&root);
+        let worlds = root.file("game/Data/Worlds.vdf", "original");
+        root.file("game/System/Gothic.ini", "ini");
+        let instance = instance(&root, &deployment.game_dir);
+
+        let plan = deployment.deploy(&instance).unwrap();
+        assert_eq!((plan.count(DeployAction::Replace), plan.count(DeployAction::Add)), (1, 1));
+        assert_eq!(std::fs::read_to_string(&worlds).unwrap(), "modded");
+        assert_eq!(std::fs::read_to_string(deployment.game_dir.join("Data/New/Patch.vdf")).unwrap(), "new");
+        let manifest = deployment.load_manifest().unwrap();
+        assert_eq!(manifest.instance.as_deref(), Some("Default"));
+        assert!(deployment.plan(Some(&instance), &manifest).is_up_to_date());
+
+        let plan = deployment.purge().unwrap();
+        assert_eq!(plan.count(DeployAction::Remove), 2);
+        assert_eq!(std::fs::read_to_string(&worlds).unwrap(), "original");
+        assert_eq!(std::fs::read_to_string(deployment.game_dir.join("System/Gothic.ini")).unwrap(), "ini");
+        assert!(!deployment.game_dir.join("Data/New").exists());
+        let manifest = deployment.load_manifest().unwrap();
+        assert!(manifest.files.is_empty() && manifest.created_dirs.is_empty());
+        assert_eq!(manifest.instance, None);
+    }
+}
@@ -0,0 +1,383 @@
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use chrono::Local;
+use serde::Serialize;
+use zip::write::SimpleFileOptions;
+
+use crate::app::session::ApplicationSession;
+use crate::core::constants;
+use crate::core::deploy;
+use crate::core::fingerprint;
+use crate::core::fingerprint::GameFingerprint;
+use crate::core::helpers;
+use crate::core::launch;
+use crate::core::overlaps::OverlapReport;
+use crate::core::profile::Instance;
+use crate::core::profile::Profile;
+use crate::core::schema;
+use crate::error::Error;
+use crate::error::ErrorContext;
+use crate::logger;
+
+pub const REPORT_FILE_NAME: &str = "report.json";
+pub const LOGS_DIR_NAME: &str = "logs";
+
+/// Placeholders replacing the home directory and the user name in the bundled files.
+pub const HOME_PLACEHOLDER: &str = "~";
+pub const USER_PLACEHOLDER: &str = "<user>";
+
+/// Everything the bundle is collected from. Missing parts are listed in the report instead.
+pub struct DiagnosticsSource<'a> {
+    pub session: &'a ApplicationSession,
+    pub profile: Option<&'a Profile>,
+    pub instance: Option<&'a Instance>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemInfo {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub os_version: Option<String>,
+    /// Output of `wine --version` when not running on Windows, the game is launched through it.
+    pub wine_version: Option<String>,
+    pub wine_prefix: Option<String>,
+}
+
+impl SystemInfo {
+    pub fn detect() -> Self {
+        let os_version = match cfg!(windows) {
+            true => command_output("cmd", &["/C", "ver"]),
+            false => command_output("uname", &["-sr"]),
+        };
+
+        let (wine_version, wine_prefix) = match cfg!(windows) {
+            true => (None, None),
+            false => {
+                let wine = std::env::var_os(launch::WINE_ENV_VAR).unwrap_or_else(|| "wine".into());
+                (command_output(wine, &["--version"]), std::env::var("WINEPREFIX").ok())
+            }
+        };
+
+        Self {
+            app_version: constants::APP_VERSION.to_owned(),
+            os: std::env::consts::OS.to_owned(),
+            arch: std::env::consts::ARCH.to_owned(),
+            os_version,
+            wine_version,
+            wine_prefix,
+        }
+    }
+}
+
+/// Files providing each conflicting path, lowest priority first, and the overlaps they add up to.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConflictReport {
+    pub files: Vec<ConflictingFile>,
+    pub overlaps: OverlapReport,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConflictingFile {
+    pub path: PathBuf,
+    pub providers: Vec<String>,
+}
+
+impl ConflictReport {
+    pub fn new(instance: &Instance, profile_path: &Path) -> Self {
+        let mut files = instance
+            .conflicts
+            .iter()
+            .filter_map(|(path, providers)| {
+                let mut providers = providers
+                    .iter()
+                    .filter(|(_, metadata)| !metadata.source_path.is_dir())
+                    .collect::<Vec<_>>();
+                providers.sort_unstable_by_key(|(priority, _)| **priority);
+                (providers.len() > 1).then(|| ConflictingFile {
+                    path: path.clone(),
+                    providers: providers
+                        .into_iter()
+                        .map(|(_, metadata)| metadata.parent_name.clone())
+                        .collect(),
+                })
+            })
+            .collect::<Vec<_>>();
+        files.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+
+        Self { files, overlaps: OverlapReport::new(instance, profile_path) }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsReport {
+    pub created: DateTime<Local>,
+    pub system: SystemInfo,
+    pub profile: Option<String>,
+    pub instance: Option<String>,
+    pub game: Option<GameFingerprint>,
+    /// Files contained in the bundle next to the report.
+    pub files: Vec<String>,
+    /// Parts that could not be collected and why.
+    pub missing: Vec<String>,
+}
+
+/// Replaces the home directory and the user name in text with placeholders, in the plain,
+/// Windows style and JSON escaped spelling of the paths.
+#[derive(Debug, Clone, Default)]
+pub struct Anonymizer {
+    replacements: Vec<(String, String)>,
+}
+
+impl Anonymizer {
+    pub fn from_env() -> Self {
+        let home = std::env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).map(PathBuf::from);
+        let user = std::env::var("USERNAME").or_else(|_| std::env::var("USER")).ok();
+        Self::new(home.as_deref(), user.as_deref())
+    }
+
+    pub fn new(home: Option<&Path>, user: Option<&str>) -> Self {
+        let mut replacements = Vec::new();
+
+        let home = home.map(|home| home.to_string_lossy().trim_end_matches(['/', '\\']).to_owned());
+        if let Some(home) = home.filter(|home| home.len() > 1) {
+            let unix_home = home.replace('\\', "/");
+            let windows_home = home.replace('/', "\\");
+            for pattern in [unix_home, windows_home.replace('\\', "\\\\"), windows_home] {
+                replacements.push((pattern, HOME_PLACEHOLDER.to_owned()));
+            }
+        }
+
+        if let Some(user) = user.filter(|user| !user.is_empty()) {
+            for separator in ["/", "\\\\", "\\"] {
+                for parent in ["home", "Users", "users"] {
+                    replacements.push((
+                        format!("{parent}{separator}{user}"),
+                        format!("{parent}{separator}{USER_PLACEHOLDER}"),
+                    ));
+                }
+            }
+        }
+
+        // Longest patterns first, so that a JSON escaped path is not replaced piecewise.
+        replacements.sort_by_key(|(pattern, _)| std::cmp::Reverse(pattern.len()));
+        replacements.dedup();
+        Self { replacements }
+    }
+
+    pub fn anonymize(&self, text: &str) -> String {
+        self.replacements.iter().fold(text.to_owned(), |text, (pattern, replacement)| {
+            replace_path_prefix(&text, pattern, replacement)
+        })
+    }
+}
+
+/// Replaces the occurrences of `pattern` that end at a path component boundary, i.e. are followed
+/// by a separator, a quote, whitespace or the end of the text. `/root` is left alone in `/rootfs`.
+fn replace_path_prefix(text: &str, pattern: &str, replacement: &str) -> String {
+    let mut anonymized = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find(pattern) {
+        let end = index + pattern.len();
+        let is_boundary = rest[end..]
+            .chars()
+            .next()
+            .is_none_or(|c| matches!(c, '/' | '\\' | '"' | '\'') || c.is_whitespace());
+
+        anonymized.push_str(&rest[..index]);
+        anonymized.push_str(if is_boundary { replacement } else { pattern });
+        rest = &rest[end..];
+    }
+
+    anonymized.push_str(rest);
+    anonymized
+}
+
+/// `gothic-organizer-rs-diagnostics-20250101-120000.zip`
+pub fn default_file_name() -> String {
+    format!("{}-diagnostics-{}.zip", constants::APP_NAME, Local::now().format("%Y%m%d-%H%M%S"))
+}
+
+/// Collects the recent logs, the session, the profile, the conflicts, the deployment manifest and
+/// system information into a zip archive at `bundle_path`, with user paths anonymized.
+pub fn write_diagnostics(
+    bundle_path: &Path,
+    source: &DiagnosticsSource,
+) -> Result<DiagnosticsReport, ErrorContext> {
+    let anonymizer = Anonymizer::from_env();
+    let mut entries: Vec<(String, String)> = Vec::new();
+    let mut missing = Vec::new();
+
+    match logger::log_file() {
+        Some(config) => {
+            for log_path in config.files() {
+                let name = log_path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                match std::fs::read(&log_path) {
+                    Ok(bytes) => entries
+                        .push((format!("{LOGS_DIR_NAME}/{name}"), String::from_utf8_lossy(&bytes).into())),
+                    Err(err) => missing.push(format!("{LOGS_DIR_NAME}/{name}: {err}")),
+                }
+            }
+        }
+        None => missing.push(format!("{LOGS_DIR_NAME}: Logging to a file is not set up")),
+    }
+
+    let mut push_json = |name: &str, json: Result<String, serde_json::Error>| match json {
+        Ok(json) => entries.push((name.to_owned(), json)),
+        Err(err) => missing.push(format!("{name}: {err}")),
+    };
+
+    push_json("session.json", schema::SESSION_SCHEMA.serialize(source.session));
+
+    let mut game = None;
+    match source.profile {
+        Some(profile) => {
+            let storage_root = helpers::mod_storage_root(source.session, &profile.name);
+            push_json("profile.json", schema::PROFILE_SCHEMA.serialize(&profile.to_portable(&storage_root)));
+
+            if let Some(instance) = source.instance {
+                push_json(
+                    "conflicts.json",
+                    serde_json::to_string_pretty(&ConflictReport::new(instance, &profile.path)),
+                );
+            } else {
+                missing.push("conflicts.json: No instance selected".to_owned());
+            }
+
+            let deployment =
+                deploy::Deployment::new(profile, source.session.custom_user_data_path.as_deref());
+            match std::fs::read_to_string(&deployment.manifest_path) {
+                Ok(manifest) => entries.push((deploy::MANIFEST_FILE_NAME.to_owned(), manifest)),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                    missing.push(format!("{}: Nothing is deployed", deploy::MANIFEST_FILE_NAME))
+                }
+                Err(err) => missing.push(format!("{}: {err}", deploy::MANIFEST_FILE_NAME)),
+            }
+
+            game = (!profile.path.as_os_str().is_empty())
+                .then(|| fingerprint::fingerprint(profile, &profile.path));
+        }
+        None => missing.push("profile.json: No profile selected".to_owned()),
+    }
+
+    let report = DiagnosticsReport {
+        created: Local::now(),
+        system: SystemInfo::detect(),
+        profile: source.profile.map(|profile| profile.name.clone()),
+        instance: source.instance.map(|instance| instance.name.clone()),
+        game,
+        files: entries.iter().map(|(name, _)| name.clone()).collect(),
+        missing,
+    };
+
+    let report_json = serde_json::to_string_pretty(&report)
+        .map_err(|e| diagnostics_error(e, bundle_path, "Export Diagnostics"))?;
+
+    if let Some(parent) = bundle_path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| diagnostics_error(e, parent, "Export Diagnostics"))?;
+    }
+
+    let handle = std::fs::File::create(bundle_path)
+        .map_err(|e| diagnostics_error(e, bundle_path, "Export Diagnostics"))?;
+    let mut writer = zip::ZipWriter::new(handle);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    for (name, contents) in std::iter::once((REPORT_FILE_NAME.to_owned(), report_json)).chain(entries) {
+        writer
+            .start_file(name, options)
+            .map_err(|e| diagnostics_error(e, bundle_path, "Export Diagnostics"))?;
+        writer
+            .write_all(anonymizer.anonymize(&contents).as_bytes())
+            .map_err(|e| diagnostics_error(e, bundle_path, "Export Diagnostics"))?;
+    }
+
+    writer.finish().map_err(|e| diagnostics_error(e, bundle_path, "Export Diagnostics"))?;
+    Ok(report)
+}
+
+fn command_output<S: AsRef<std::ffi::OsStr>>(program: S, args: &[&str]) -> Option<String> {
+    std::process::Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+        .filter(|output| !output.is_empty())
+}
+
+fn diagnostics_error(err: impl std::error::Error + 'static, path: &Path, operation: &str) -> ErrorContext {
+    ErrorContext::builder()
+        .error(
+            Error::file_system("Could not write the diagnostics bundle", operation)
+                .with_path(path)
+                .with_source(&err),
+        )
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::testing::TempDir;
+
+    #[test]
+    fn unix_paths_are_replaced_at_component_boundaries() {
+        let anonymizer = Anonymizer::new(Some(Path::new("/root/")), Some("al"));
+
+        assert_eq!(anonymizer.anonymize("/root/Gothic/System"), "~/Gothic/System");
+        assert_eq!(anonymizer.anonymize("dir=/root\nnext"), "dir=~\nnext");
+        assert_eq!(anonymizer.anonymize("/rootfs/Gothic and /root"), "/rootfs/Gothic and ~");
+        assert_eq!(anonymizer.anonymize("/home/al/Gothic"), "/home/<user>/Gothic");
+        assert_eq!(anonymizer.anonymize("/home/alice/Gothic"), "/home/alice/Gothic");
+    }
+
+    #[test]
+    fn windows_and_json_escaped_paths_are_replaced() {
+        let anonymizer = Anonymizer::new(Some(Path::new(r"C:\Users\al")), Some("al"));
+
+        assert_eq!(anonymizer.anonymize(r"C:\Users\al\Saves"), r"~\Saves");
+        assert_eq!(anonymizer.anonymize(r#"{"path": "C:\\Users\\al\\Saves"}"#), r#"{"path": "~\\Saves"}"#);
+        assert_eq!(anonymizer.anonymize(r#"{"path": "C:\\Users\\al"}"#), r#"{"path": "~"}"#);
+        assert_eq!(anonymizer.anonymize(r"D:\Users\al\Games"), r"D:\Users\<user>\Games");
+        assert_eq!(anonymizer.anonymize(r"C:\Users\alex\Saves"), r"C:\Users\alex\Saves");
+    }
+
+    #[test]
+    fn the_bundle_contains_the_listed_files() {
+        let root = TempDir::new();
+        let session = ApplicationSession {
+            active_profile: Some("Test".to_owned()),
+            active_instance: Some("Default".to_owned()),
+            mod_storage_dir: Some(root.path().join("mods")),
+            custom_user_data_path: Some(root.path().join("user")),
+            ..Default::default()
+        };
+        let instance = Instance::default().with_name("Default");
+        let profile = Profile::new("Test", &root.dir("game"));
+        let source =
+            DiagnosticsSource { session: &session, profile: Some(&profile), instance: Some(&instance) };
+
+        let bundle_path = root.path().join("export").join("diagnostics.zip");
+        let report = write_diagnostics(&bundle_path, &source).unwrap();
+        for name in ["session.json", "profile.json", "conflicts.json"] {
+            assert!(report.files.iter().any(|file| file == name), "{name} is not listed");
+        }
+        assert!(report.missing.iter().any(|missing| missing.starts_with(deploy::MANIFEST_FILE_NAME)));
+
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&bundle_path).unwrap()).unwrap();
+        let mut names: Vec<_> = archive.file_names().map(str::to_owned).collect();
+        names.sort();
+        let mut expected: Vec<_> = std::iter::once(REPORT_FILE_NAME.to_owned()).chain(report.files).collect();
+        expected.sort();
+        assert_eq!(names, expected);
+
+        let mut bundled_report = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name(REPORT_FILE_NAME).unwrap(), &mut bundled_report)
+            .unwrap();
+        let bundled_report: serde_json::Value = serde_json::from_str(&bundled_report).unwrap();
+        assert_eq!(bundled_report["instance"], "Default");
+    }
+}
@@ -0,0 +1,340 @@
+use std::path::Path;
+
+use derive_more::Display;
+use sha2::Digest;
+use similar::DiffOp;
+
+use crate::error::Error;
+use crate::error::ErrorContext;
+
+/// Extensions of files that are treated as text when comparing providers of a conflicting file.
+pub const TEXT_FILE_EXTENSIONS: [&str; 4] = ["d", "src", "ini", "txt"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Display)]
+pub enum TextEncoding {
+    #[default]
+    #[display("Auto")]
+    Auto,
+    #[display("UTF-8")]
+    Utf8,
+    #[display("Windows-1250 (Central European)")]
+    Windows1250,
+    #[display("Windows-1251 (Cyrillic)")]
+    Windows1251,
+    #[display("Windows-1252 (Western)")]
+    Windows1252,
+}
+
+impl TextEncoding {
+    pub fn into_iter() -> std::slice::Iter<'static, TextEncoding> {
+        static ENCODINGS: [TextEncoding; 5] = [
+            TextEncoding::Auto,
+            TextEncoding::Utf8,
+            TextEncoding::Windows1250,
+            TextEncoding::Windows1251,
+            TextEncoding::Windows1252,
+        ];
+        ENCODINGS.iter()
+    }
+
+    /// Guesses the encoding of raw file contents.
+    ///
+    /// Gothic scripts are usually stored in one of the Windows-125x code pages depending on the
+    /// localization, so anything that is not valid UTF-8 is decoded with each of them and the
+    /// decoding reading most like the languages written in that code page wins.
+    pub fn detect(bytes: &[u8]) -> TextEncoding {
+        if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) || std::str::from_utf8(bytes).is_ok() {
+            return TextEncoding::Utf8;
+        }
+
+        // `max_by_key` keeps the last of equal scores, so ties go to Western, the code page of
+        // most localizations.
+        [TextEncoding::Windows1251, TextEncoding::Windows1250, TextEncoding::Windows1252]
+            .into_iter()
+            .max_by_key(|encoding| encoding.score(bytes))
+            .unwrap_or(TextEncoding::Windows1252)
+    }
+
+    /// Plausibility of `bytes` decoded with this code page, judged by its non-ASCII characters.
+    fn score(self, bytes: &[u8]) -> i64 {
+        let chars = self.decode(bytes).chars().collect::<Vec<_>>();
+        chars
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| !c.is_ascii())
+            .map(|(i, c)| {
+                let previous = i.checked_sub(1).map(|i| chars[i]);
+                self.char_score(*c, previous, chars.get(i + 1).copied())
+            })
+            .sum()
+    }
+
+    fn char_score(self, c: char, previous: Option<char>, next: Option<char>) -> i64 {
+        const PUNCTUATION: &str = "\u{a0}‚„…‘’“”–—•«»°§©®·€£¥¡¿";
+        if c.is_control() || c == char::REPLACEMENT_CHARACTER {
+            return -10;
+        }
+        if !c.is_alphabetic() {
+            return if PUNCTUATION.contains(c) { 1 } else { -2 };
+        }
+
+        let mut score = if self.is_common_letter(c) { 3 } else { 1 };
+
+        // Words do not mix Cyrillic and Latin letters.
+        let is_cyrillic = |c: char| ('\u{400}'..='\u{4ff}').contains(&c);
+        score -= [previous, next]
+            .into_iter()
+            .flatten()
+            .filter(|neighbour| neighbour.is_alphabetic() && is_cyrillic(*neighbour) != is_cyrillic(c))
+            .count() as i64
+            * 5;
+
+        // Latin languages rarely put two accented letters in a row, while Cyrillic words consist
+        // of nothing else.
+        if self != TextEncoding::Windows1251 && previous.is_some_and(|p| !p.is_ascii() && p.is_alphabetic()) {
+            score -= 2;
+        }
+
+        // Letters glued to a number or switching to upper case within a word are symbols of
+        // another code page.
+        if previous.is_some_and(|p| p.is_ascii_digit())
+            || (c.is_lowercase() && next.is_some_and(char::is_uppercase))
+            || (c.is_uppercase() && previous.is_some_and(char::is_lowercase))
+        {
+            score -= 4;
+        }
+
+        score
+    }
+
+    /// Whether `c` is a frequent letter of the languages written in this code page: Russian for
+    /// Cyrillic, Polish and Czech for Central European and German, French, Spanish and Italian
+    /// for Western.
+    fn is_common_letter(self, c: char) -> bool {
+        match self {
+            TextEncoding::Windows1251 => matches!(c, 'а'..='я' | 'А'..='Я' | 'ё' | 'Ё'),
+            TextEncoding::Windows1250 => "ąćęłńóśźżĄĆĘŁŃÓŚŹŻáčďéěíňřšťúůýžÁČĎÉĚÍŇŘŠŤÚŮÝŽ".contains(c),
+            TextEncoding::Windows1252 => "àâäæçèéêëìîïòôöœùûüÿßáíñóúÀÂÄÆÇÈÉÊËÎÏÔÖŒÙÛÜÁÍÑÓÚ".contains(c),
+            TextEncoding::Auto | TextEncoding::Utf8 => false,
+        }
+    }
+
+    pub fn decode(self, bytes: &[u8]) -> String {
+        let encoding = match self {
+            TextEncoding::Auto => return Self::detect(bytes).decode(bytes),
+            TextEncoding::Utf8 => encoding_rs::UTF_8,
+            TextEncoding::Windows1250 => encoding_rs::WINDOWS_1250,
+            TextEncoding::Windows1251 => encoding_rs::WINDOWS_1251,
+            TextEncoding::Windows1252 => encoding_rs::WINDOWS_1252,
+        };
+
+        encoding.decode_with_bom_removal(bytes).0.into_owned()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLineKind {
+    Equal,
+    Removed,
+    Added,
+    Changed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub left_number: Option<usize>,
+    pub left: Option<String>,
+    pub right_number: Option<usize>,
+    pub right: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinarySummary {
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileDiff {
+    Text { left_encoding: TextEncoding, right_encoding: TextEncoding, lines: Vec<DiffLine> },
+    Binary { left: BinarySummary, right: BinarySummary },
+}
+
+impl FileDiff {
+    pub fn is_identical(&self) -> bool {
+        match self {
+            FileDiff::Text { lines, .. } => lines.iter().all(|l| l.kind == DiffLineKind::Equal),
+            FileDiff::Binary { left, right } => left == right,
+        }
+    }
+}
+
+pub fn is_text_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| TEXT_FILE_EXTENSIONS.iter().any(|ext| e.eq_ignore_ascii_case(ext)))
+}
+
+/// Compares two provider files of the same target path.
+///
+/// Text files are decoded with the given encoding (or a detected one for [`TextEncoding::Auto`])
+/// and compared line by line. Everything else, including text files containing NUL bytes, is
+/// compared by size and SHA-256 hash.
+pub fn diff_files(
+    left_path: &Path,
+    right_path: &Path,
+    encoding: TextEncoding,
+) -> Result<FileDiff, ErrorContext> {
+    let left_bytes = read_file(left_path)?;
+    let right_bytes = read_file(right_path)?;
+
+    let is_text = is_text_file(left_path)
+        && is_text_file(right_path)
+        && !left_bytes.contains(&0)
+        && !right_bytes.contains(&0);
+
+    if !is_text {
+        return Ok(FileDiff::Binary { left: summarize(&left_bytes), right: summarize(&right_bytes) });
+    }
+
+    let (left_encoding, right_encoding) = match encoding {
+        TextEncoding::Auto => (TextEncoding::detect(&left_bytes), TextEncoding::detect(&right_bytes)),
+        other => (other, other),
+    };
+
+    let left_text = left_encoding.decode(&left_bytes);
+    let right_text = right_encoding.decode(&right_bytes);
+
+    Ok(FileDiff::Text { left_encoding, right_encoding, lines: side_by_side(&left_text, &right_text) })
+}
+
+fn side_by_side(left_text: &str, right_text: &str) -> Vec<DiffLine> {
+    let text_diff = similar::TextDiff::from_lines(left_text, right_text);
+    let left_lines = text_diff.old_slices();
+    let right_lines = text_diff.new_slices();
+    let line =
+        |lines: &[&str], index: usize| lines.get(index).map(|l| l.trim_end_matches(['\r', '\n']).to_owned());
+
+    let mut rows = Vec::new();
+    for op in text_diff.ops() {
+        match *op {
+            DiffOp::Equal { old_index, new_index, len } => {
+                rows.extend((0..len).map(|i| DiffLine {
+                    kind: DiffLineKind::Equal,
+                    left_number: Some(old_index + i + 1),
+                    left: line(left_lines, old_index + i),
+                    right_number: Some(new_index + i + 1),
+                    right: line(right_lines, new_index + i),
+                }));
+            }
+
+            DiffOp::Delete { old_index, old_len, .. } => {
+                rows.extend((0..old_len).map(|i| DiffLine {
+                    kind: DiffLineKind::Removed,
+                    left_number: Some(old_index + i + 1),
+                    left: line(left_lines, old_index + i),
+                    right_number: None,
+                    right: None,
+                }));
+            }
+
+            DiffOp::Insert { new_index, new_len, .. } => {
+                rows.extend((0..new_len).map(|i| DiffLine {
+                    kind: DiffLineKind::Added,
+                    left_number: None,
+                    left: None,
+                    right_number: Some(new_index + i + 1),
+                    right: line(right_lines, new_index + i),
+                }));
+            }
+
+            DiffOp::Replace { old_index, old_len, new_index, new_len } => {
+                // Pair up replaced lines so that both sides stay aligned in the view.
+                rows.extend((0..old_len.max(new_len)).map(|i| DiffLine {
+                    kind: match (i < old_len, i < new_len) {
+                        (true, true) => DiffLineKind::Changed,
+                        (true, false) => DiffLineKind::Removed,
+                        _ => DiffLineKind::Added,
+                    },
+                    left_number: (i < old_len).then_some(old_index + i + 1),
+                    left: (i < old_len).then(|| line(left_lines, old_index + i)).flatten(),
+                    right_number: (i < new_len).then_some(new_index + i + 1),
+                    right: (i < new_len).then(|| line(right_lines, new_index + i)).flatten(),
+                }));
+            }
+        }
+    }
+
+    rows
+}
+
+fn summarize(bytes: &[u8]) -> BinarySummary {
+    BinarySummary { size: bytes.len() as u64, sha256: format!("{:x}", sha2::Sha256::digest(bytes)) }
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, ErrorContext> {
+    std::fs::read(path).map_err(|e| {
+        ErrorContext::builder()
+            .error(Error::file_system("Could not read file", "Compare Files").with_path(path).with_source(&e))
+            .build()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(text: &str, encoding: &'static encoding_rs::Encoding) -> Vec<u8> {
+        encoding.encode(text).0.into_owned()
+    }
+
+    #[test]
+    fn detects_russian_scripts_with_mostly_ascii_code() {
+        let script = "func void DIA_Xardas_Hello_Info()\n{\n\tAI_Output(self, other, \"DIA_Xardas_Hello_14_00\"); //Привет, чужестранец!\n\tAI_StopProcessInfos(self);\n};\n";
+        assert_eq!(
+            TextEncoding::detect(&encode(script, encoding_rs::WINDOWS_1251)),
+            TextEncoding::Windows1251
+        );
+    }
+
+    #[test]
+    fn detects_central_european_scripts() {
+        let script =
+            "AI_Output(self, other, \"DIA_Lester_Hello_13_00\"); //Zażółć gęślą jaźń, przyjacielu.\n";
+        assert_eq!(
+            TextEncoding::detect(&encode(script, encoding_rs::WINDOWS_1250)),
+            TextEncoding::Windows1250
+        );
+
+        let script = "AI_Output(self, other, \"DIA_Diego_Hello_11_00\"); //Příliš žluťoučký kůň.\n";
+        assert_eq!(
+            TextEncoding::detect(&encode(script, encoding_rs::WINDOWS_1250)),
+            TextEncoding::Windows1250
+        );
+    }
+
+    #[test]
+    fn keeps_western_scripts_with_symbols_of_other_code_pages() {
+        let scripts = [
+            "AI_Output(self, other, \"DIA_Diego_Hello_11_00\"); //Die Größe des Tals überrascht mich.\n",
+            "AI_Output(self, other, \"DIA_Diego_Hello_11_00\"); //¿Qué quieres?\n",
+            "AI_Output(self, other, \"DIA_Diego_Hello_11_00\"); //Das kostet 100¥.\n",
+            "AI_Output(self, other, \"DIA_Diego_Hello_11_00\"); //Je suis allé à la mine.\n",
+        ];
+
+        for script in scripts {
+            assert_eq!(
+                TextEncoding::detect(&encode(script, encoding_rs::WINDOWS_1252)),
+                TextEncoding::Windows1252,
+                "{script}"
+            );
+        }
+    }
+
+    #[test]
+    fn detects_utf8() {
+        assert_eq!(TextEncoding::detect("//Привет, Größe".as_bytes()), TextEncoding::Utf8);
+        assert_eq!(TextEncoding::detect(b"\xEF\xBB\xBFplain"), TextEncoding::Utf8);
+    }
+}
@@ -0,0 +1,375 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use derive_more::Display;
+
+use crate::core::constants::DefaultProfile;
+
+/// Steam app ids of the Gothic releases and the `installdir` they use when the manifest is missing.
+pub const STEAM_APPS: [(&str, &str); 2] = [("65540", "Gothic"), ("39510", "Gothic II")];
+
+/// Install directory names used by GOG and by the retail/disc installers.
+pub const KNOWN_INSTALL_DIRS: [&str; 8] = [
+    "Gothic",
+    "Gothic 1",
+    "Gothic II",
+    "Gothic 2",
+    "Gothic 2 Gold",
+    "Gothic II Gold",
+    "Gothic II Gold Edition",
+    "Gothic 2 - Die Nacht des Raben",
+];
+
+/// Directories relative to a drive root (`C:\` or a Wine prefix `drive_c`) that usually contain
+/// one of [`KNOWN_INSTALL_DIRS`].
+pub const KNOWN_PARENT_DIRS: [&str; 8] = [
+    "GOG Games",
+    "Games",
+    "Program Files (x86)/GOG Galaxy/Games",
+    "Program Files/GOG Galaxy/Games",
+    "Program Files (x86)/JoWooD",
+    "Program Files (x86)/Piranha Bytes",
+    "Program Files (x86)",
+    "Program Files",
+];
+
+/// Steam installation directories relative to a drive root.
+pub const STEAM_DRIVE_DIRS: [&str; 2] = ["Program Files (x86)/Steam", "Program Files/Steam"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Display)]
+pub enum InstallSource {
+    #[display("Steam")]
+    Steam,
+    #[display("GOG / Retail")]
+    Known,
+    #[display("Wine ({})", _0.display())]
+    Wine(PathBuf),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredInstallation {
+    pub path: PathBuf,
+    pub source: InstallSource,
+    pub suggested_profile: Option<DefaultProfile>,
+}
+
+/// Places the detector looks at. [`SearchLocations::system`] builds the list for the current
+/// machine, but any directory tree laid out the same way can be scanned.
+#[derive(Debug, Clone, Default)]
+pub struct SearchLocations {
+    /// Steam installation directories containing `steamapps/libraryfolders.vdf`.
+    pub steam_roots: Vec<(PathBuf, InstallSource)>,
+    /// Drive roots searched for [`KNOWN_PARENT_DIRS`] and [`STEAM_DRIVE_DIRS`].
+    pub drive_roots: Vec<(PathBuf, InstallSource)>,
+}
+
+impl SearchLocations {
+    pub fn system() -> Self {
+        let mut locations = Self::default();
+
+        #[cfg(target_os = "windows")]
+        {
+            for letter in 'C'..='H' {
+                let drive = PathBuf::from(format!("{letter}:\\"));
+                if drive.exists() {
+                    locations.drive_roots.push((drive, InstallSource::Known));
+                }
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let home = PathBuf::from(std::env::var("HOME").unwrap_or_default());
+            for steam_dir in
+                [".steam/steam", ".local/share/Steam", ".var/app/com.valvesoftware.Steam/.local/share/Steam"]
+            {
+                locations.steam_roots.push((home.join(steam_dir), InstallSource::Steam));
+            }
+
+            // Native copies are usually kept in `~/Games` or `~/GOG Games`.
+            locations.drive_roots.push((home.clone(), InstallSource::Known));
+
+            for prefix in wine_prefixes(&home) {
+                locations.drive_roots.push((prefix.join("drive_c"), InstallSource::Wine(prefix)));
+            }
+        }
+
+        locations
+    }
+}
+
+/// Scans the locations of the current machine for Gothic installations.
+pub fn discover_installations() -> Vec<DiscoveredInstallation> {
+    discover(&SearchLocations::system())
+}
+
+pub fn discover(locations: &SearchLocations) -> Vec<DiscoveredInstallation> {
+    let mut installations: Vec<DiscoveredInstallation> = Vec::new();
+    let mut push = |path: PathBuf, source: &InstallSource| {
+        let Some(suggested_profile) = detect_game_type(&path) else {
+            return;
+        };
+
+        let path = path.canonicalize().unwrap_or(path);
+        if installations.iter().all(|i| i.path != path) {
+            tracing::info!("Found {suggested_profile} installation: {}", path.display());
+            installations.push(DiscoveredInstallation {
+                path,
+                source: source.clone(),
+                suggested_profile: Some(suggested_profile),
+            });
+        }
+    };
+
+    let drive_steam_roots = locations.drive_roots.iter().flat_map(|(drive, source)| {
+        STEAM_DRIVE_DIRS.iter().map(move |dir| (drive.join(dir), source.clone()))
+    });
+
+    for (steam_root, source) in locations.steam_roots.iter().cloned().chain(drive_steam_roots) {
+        let drive = match &source {
+            InstallSource::Wine(prefix) => Some(prefix.join("drive_c")),
+            _ => None,
+        };
+
+        for library in steam_libraries(&steam_root, drive.as_deref()) {
+            for install_dir in steam_install_dirs(&library) {
+                push(library.join("steamapps").join("common").join(install_dir), &source);
+            }
+        }
+    }
+
+    for (drive, source) in &locations.drive_roots {
+        for parent in KNOWN_PARENT_DIRS {
+            for install_dir in KNOWN_INSTALL_DIRS {
+                push(drive.join(parent).join(install_dir), source);
+            }
+        }
+    }
+
+    installations
+}
+
+/// Suggests the game a directory contains based on the executables and archives it ships with.
+///
+/// Gothic 2 Sequel has no files distinguishing it from Night of the Raven and is never suggested.
+pub fn detect_game_type(path: &Path) -> Option<DefaultProfile> {
+    if !path.is_dir() {
+        return None;
+    }
+
+    if find_case_insensitive(path, "System/Gothic2.exe").is_some() {
+        if find_case_insensitive(path, "Data/Worlds_Addon.vdf").is_some() {
+            Some(DefaultProfile::Gothic2NightOfRaven)
+        } else {
+            Some(DefaultProfile::Gothic2Classic)
+        }
+    } else if find_case_insensitive(path, "System/Gothic.exe").is_some()
+        || find_case_insensitive(path, "System/GothicMod.exe").is_some()
+    {
+        Some(DefaultProfile::Gothic)
+    } else {
+        None
+    }
+}
+
+/// Resolves a `/` separated relative path ignoring the case of each component, which matters for
+/// installations copied from Windows onto case sensitive file systems.
+pub fn find_case_insensitive(base: &Path, relative: &str) -> Option<PathBuf> {
+    relative.split('/').filter(|c| !c.is_empty()).try_fold(base.to_path_buf(), |current, component| {
+        let exact = current.join(component);
+        if exact.exists() {
+            return Some(exact);
+        }
+
+        std::fs::read_dir(&current)
+            .ok()?
+            .flatten()
+            .find(|entry| entry.file_name().to_string_lossy().eq_ignore_ascii_case(component))
+            .map(|entry| entry.path())
+    })
+}
+
+/// Lists the library directories registered in `steamapps/libraryfolders.vdf`, including the
+/// Steam directory itself. Windows paths found inside a Wine prefix are mapped onto `drive_c`.
+pub fn steam_libraries(steam_root: &Path, wine_drive: Option<&Path>) -> Vec<PathBuf> {
+    let mut libraries = Vec::new();
+    if steam_root.join("steamapps").is_dir() {
+        libraries.push(steam_root.to_path_buf());
+    }
+
+    let Ok(content) = std::fs::read_to_string(steam_root.join("steamapps").join("libraryfolders.vdf")) else {
+        return libraries;
+    };
+
+    for value in vdf_values(&content, "path") {
+        let library = match wine_drive {
+            Some(drive) => windows_to_wine_path(&value, drive),
+            None => PathBuf::from(value),
+        };
+
+        if library.join("steamapps").is_dir() && !libraries.contains(&library) {
+            libraries.push(library);
+        }
+    }
+
+    libraries
+}
+
+/// Returns the install directories of the Gothic apps present in a Steam library, preferring the
+/// `installdir` recorded in the app manifest.
+pub fn steam_install_dirs(library: &Path) -> Vec<String> {
+    STEAM_APPS
+        .iter()
+        .filter_map(|(app_id, default_dir)| {
+            let manifest = library.join("steamapps").join(format!("appmanifest_{app_id}.acf"));
+            match std::fs::read_to_string(&manifest) {
+                Ok(content) => vdf_values(&content, "installdir").next().or(Some(default_dir.to_string())),
+                Err(_) => library
+                    .join("steamapps")
+                    .join("common")
+                    .join(default_dir)
+                    .is_dir()
+                    .then(|| default_dir.to_string()),
+            }
+        })
+        .collect()
+}
+
+/// Extracts the values of every `"key" "value"` pair with the given key from a Valve KeyValues
+/// file, ignoring the nesting.
+pub fn vdf_values<'a>(content: &'a str, key: &'a str) -> impl Iterator<Item = String> + 'a {
+    content.lines().filter_map(move |line| {
+        let mut tokens = line.trim().split('"').skip(1).step_by(2);
+        match (tokens.next(), tokens.next()) {
+            (Some(k), Some(value)) if k.eq_ignore_ascii_case(key) => Some(value.replace("\\\\", "\\")),
+            _ => None,
+        }
+    })
+}
+
+/// Maps `C:\Games\Steam` onto `<drive_c>/Games/Steam`. Other drive letters are looked up through
+/// the `dosdevices` links of the prefix.
+pub fn windows_to_wine_path(windows_path: &str, drive_c: &Path) -> PathBuf {
+    let normalized = windows_path.replace('\\', "/");
+    let (drive, rest) = normalized.split_once(":/").unwrap_or(("c", normalized.as_str()));
+    let drive_root = if drive.eq_ignore_ascii_case("c") {
+        drive_c.to_path_buf()
+    } else {
+        let prefix = drive_c.parent().unwrap_or(drive_c);
+        prefix.join("dosdevices").join(format!("{}:", drive.to_lowercase()))
+    };
+
+    rest.split('/').filter(|c| !c.is_empty()).fold(drive_root, |path, c| path.join(c))
+}
+
+/// Default Wine prefix plus the prefixes managed by Lutris and Bottles.
+#[cfg(target_os = "linux")]
+fn wine_prefixes(home: &Path) -> Vec<PathBuf> {
+    let mut prefixes = vec![home.join(".wine")];
+    if let Ok(prefix) = std::env::var("WINEPREFIX") {
+        prefixes.push(PathBuf::from(prefix));
+    }
+
+    for managed_dir in
+        ["Games", ".local/share/bottles/bottles", ".var/app/com.usebottles.bottles/data/bottles/bottles"]
+    {
+        if let Ok(entries) = std::fs::read_dir(home.join(managed_dir)) {
+            prefixes.extend(entries.flatten().map(|e| e.path()).filter(|p| p.join("drive_c").is_dir()));
+        }
+    }
+
+    prefixes.retain(|p| p.join("drive_c").is_dir());
+    prefixes.dedup();
+    prefixes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::testing::TempDir;
+
+    #[test]
+    fn steam_libraries_reads_every_library_folder() {
+        let root = TempDir::new();
+        let steam = root.dir("Steam");
+        root.dir("Steam/steamapps");
+        let second = root.dir("Library2");
+        root.dir("Library2/steamapps");
+        let missing = root.path().join("Unplugged");
+
+        let vdf = format!(
+            "\"libraryfolders\"\n{{\n\t\"0\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t}}\n\t\"1\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t\t\"label\"\t\t\"\"\n\t}}\n\t\"2\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t}}\n}}\n",
+            steam.display(),
+            second.display(),
+            missing.display()
+        );
+        root.file("Steam/steamapps/libraryfolders.vdf", vdf);
+
+        assert_eq!(steam_libraries(&steam, None), vec![steam, second]);
+    }
+
+    #[test]
+    fn steam_install_dirs_prefer_the_app_manifest() {
+        let root = TempDir::new();
+        root.file(
+            "steamapps/appmanifest_39510.acf",
+            "\"AppState\"\n{\n\t\"appid\"\t\t\"39510\"\n\t\"installdir\"\t\t\"Gothic II Gold\"\n}\n",
+        );
+        root.dir("steamapps/common/Gothic");
+
+        assert_eq!(steam_install_dirs(root.path()), vec!["Gothic".to_owned(), "Gothic II Gold".to_owned()]);
+    }
+
+    #[test]
+    fn game_files_are_found_ignoring_case() {
+        let root = TempDir::new();
+        let executable = root.file("SYSTEM/gothic2.EXE", "");
+
+        assert_eq!(find_case_insensitive(root.path(), "System/Gothic2.exe"), Some(executable));
+        assert_eq!(detect_game_type(root.path()), Some(DefaultProfile::Gothic2Classic));
+
+        root.file("data/WORLDS_ADDON.vdf", "");
+        assert_eq!(detect_game_type(root.path()), Some(DefaultProfile::Gothic2NightOfRaven));
+    }
+
+    #[test]
+    fn wine_drive_letters_map_through_dosdevices() {
+        let prefix = TempDir::new();
+        let drive_c = prefix.dir("drive_c");
+
+        assert_eq!(
+            windows_to_wine_path("C:\\Program Files\\Steam", &drive_c),
+            drive_c.join("Program Files/Steam")
+        );
+        assert_eq!(
+            windows_to_wine_path("D:\\Games\\SteamLibrary", &drive_c),
+            prefix.path().join("dosdevices/d:/Games/SteamLibrary")
+        );
+    }
+
+    #[test]
+    fn discover_finds_installations_in_wine_steam_libraries() {
+        let prefix = TempDir::new();
+        let drive_c = prefix.dir("drive_c");
+        prefix.dir("drive_c/Program Files (x86)/Steam/steamapps");
+        prefix.file(
+            "drive_c/Program Files (x86)/Steam/steamapps/libraryfolders.vdf",
+            "\"libraryfolders\"\n{\n\t\"1\"\n\t{\n\t\t\"path\"\t\t\"D:\\\\SteamLibrary\"\n\t}\n}\n",
+        );
+        prefix.file(
+            "dosdevices/d:/SteamLibrary/steamapps/appmanifest_39510.acf",
+            "\"AppState\"\n{\n\t\"installdir\"\t\t\"Gothic II\"\n}\n",
+        );
+        prefix.file("dosdevices/d:/SteamLibrary/steamapps/common/Gothic II/System/Gothic2.exe", "");
+
+        let source = InstallSource::Wine(prefix.path().to_path_buf());
+        let locations =
+            SearchLocations { steam_roots: Vec::new(), drive_roots: vec![(drive_c, source.clone())] };
+        let installations = discover(&locations);
+
+        assert_eq!(installations.len(), 1);
+        assert_eq!(installations[0].source, source);
+        assert_eq!(installations[0].suggested_profile, Some(DefaultProfile::Gothic2Classic));
+        assert!(installations[0].path.ends_with("SteamLibrary/steamapps/common/Gothic II"));
+    }
+}
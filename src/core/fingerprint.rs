@@ -0,0 +1,346 @@
+use std::path::Path;
+
+use derive_more::Display;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+
+use crate::core::diff::TextEncoding;
+use crate::core::discovery::find_case_insensitive;
+use crate::core::profile::LayoutDir;
+use crate::core::profile::Profile;
+
+/// An unmodified game executable: its SHA-256 hash, the release and the distribution it ships with.
+pub type KnownExecutable = (&'static str, GameVersion, Distribution);
+
+/// Executables the version and distribution of an installation are identified by. Entries have to
+/// be taken from verified copies of the original releases. Installations with an executable not
+/// listed here are identified by their files instead, see [`guess_version`].
+pub const KNOWN_EXECUTABLES: &[KnownExecutable] = &[];
+
+/// Smallest size of an original game executable. The releases are several megabytes large, smaller
+/// files are launchers or stubs and not identified by the files around them.
+pub const MIN_EXECUTABLE_SIZE: u64 = 1024 * 1024;
+
+/// Archive shipped with Night of the Raven, which is installed on top of Gothic 2 Classic.
+pub const ADDON_ARCHIVE: &str = "Worlds_Addon.vdf";
+
+/// Lowercase file name fragments of the archives shipped by the community patches.
+pub const COMMUNITY_PATCH_MARKERS: [&str; 3] = ["communitypatch", "community_patch", "_cp_"];
+
+/// Compiled scripts, which contain every dialogue line of the game and thus reveal its language.
+pub const COMPILED_SCRIPTS: &str = "_work/Data/Scripts/_compiled/Gothic.dat";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize, Deserialize)]
+pub enum GameVersion {
+    #[display("Gothic 1.08k")]
+    Gothic108k,
+    #[display("Gothic")]
+    Gothic,
+    #[display("Gothic 2 Classic")]
+    Gothic2Classic,
+    #[display("Gothic 2 Night of the Raven")]
+    Gothic2NightOfRaven,
+    #[display("Gothic 2 Night of the Raven + Community Patch")]
+    Gothic2NightOfRavenCommunityPatch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize, Deserialize)]
+pub enum Distribution {
+    Steam,
+    #[display("GOG")]
+    Gog,
+    Disc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize, Deserialize)]
+pub enum GameLanguage {
+    English,
+    German,
+    Polish,
+    Russian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize, Deserialize)]
+pub enum Loader {
+    SystemPack,
+    Union,
+}
+
+/// Result of validating a game directory against the layout configured on a profile.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct GameFingerprint {
+    pub version: Option<GameVersion>,
+    pub distribution: Option<Distribution>,
+    pub language: Option<GameLanguage>,
+    pub loaders: Vec<Loader>,
+    pub executable_sha256: Option<String>,
+    /// Problems that make the directory unusable as a game directory.
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl GameFingerprint {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// One line description for the profile header, e.g. `Gothic 2 Classic, Steam, German, Union`.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![
+            self.version.map(|v| v.to_string()).unwrap_or("Unknown version".into()),
+            self.distribution.map(|d| d.to_string()).unwrap_or("Unknown distribution".into()),
+        ];
+        parts.extend(self.language.map(|l| l.to_string()));
+        parts.extend(self.loaders.iter().map(|l| l.to_string()));
+        parts.join(", ")
+    }
+}
+
+/// Checks that `game_dir` contains the executable and directories configured on `profile` and
+/// identifies the installed game.
+pub fn fingerprint(profile: &Profile, game_dir: &Path) -> GameFingerprint {
+    fingerprint_with(profile, game_dir, KNOWN_EXECUTABLES)
+}
+
+/// [`fingerprint`] identifying the executable by the hashes in `known_executables`.
+pub fn fingerprint_with(
+    profile: &Profile,
+    game_dir: &Path,
+    known_executables: &[KnownExecutable],
+) -> GameFingerprint {
+    let mut fingerprint = GameFingerprint::default();
+
+    let executable = profile.executable();
+    let executable_path = find_case_insensitive(game_dir, &executable.to_string_lossy().replace('\\', "/"));
+    let mut executable_size = 0;
+    match &executable_path {
+        Some(executable_path) => match std::fs::read(executable_path) {
+            Ok(bytes) => {
+                executable_size = bytes.len() as u64;
+                fingerprint.executable_sha256 = Some(format!("{:x}", sha2::Sha256::digest(&bytes)));
+            }
+            Err(err) => {
+                fingerprint.errors.push(format!("Failed to read {}: {err}", executable_path.display()))
+            }
+        },
+        None => fingerprint.errors.push(format!("Missing executable: {}", executable.display())),
+    }
+
+    for (dir, required) in [(LayoutDir::System, true), (LayoutDir::Data, true), (LayoutDir::Work, false)] {
+        let relative_path = profile.layout.get(dir).to_string_lossy().replace('\\', "/");
+        if find_case_insensitive(game_dir, &relative_path).is_some_and(|p| p.is_dir()) {
+            continue;
+        }
+
+        let message = format!("Missing directory: {relative_path}");
+        if required { fingerprint.errors.push(message) } else { fingerprint.warnings.push(message) }
+    }
+
+    let known = fingerprint
+        .executable_sha256
+        .as_deref()
+        .and_then(|hash| known_executables.iter().find(|(known_hash, ..)| *known_hash == hash));
+
+    match known {
+        Some((_, version, distribution)) => {
+            fingerprint.version = Some(*version);
+            fingerprint.distribution = Some(*distribution);
+        }
+        None => {
+            if executable_size >= MIN_EXECUTABLE_SIZE {
+                fingerprint.version =
+                    executable_path.as_deref().and_then(|path| guess_version(profile, path, game_dir));
+            }
+
+            // Original releases not sold by a store leaving its files behind come from a disc.
+            fingerprint.distribution =
+                detect_store(game_dir).or(fingerprint.version.map(|_| Distribution::Disc));
+
+            let warning = match fingerprint.version {
+                Some(_) => "Unrecognized executable, the game version was guessed from the installed files",
+                None => "Unrecognized executable, the game version is unknown",
+            };
+            if fingerprint.executable_sha256.is_some() {
+                fingerprint.warnings.push(warning.to_owned());
+            }
+        }
+    }
+
+    // The community patch replaces the executable, so it has to be checked even for known hashes.
+    if fingerprint.version == Some(GameVersion::Gothic2NightOfRaven) && has_community_patch(profile, game_dir)
+    {
+        fingerprint.version = Some(GameVersion::Gothic2NightOfRavenCommunityPatch);
+    }
+
+    fingerprint.language = detect_language(game_dir);
+    fingerprint.loaders = detect_loaders(profile, game_dir);
+    fingerprint
+}
+
+/// Identifies the release from the name of the executable and the archives installed with it, for
+/// executables patched or otherwise missing from [`KNOWN_EXECUTABLES`]. The patch level of Gothic
+/// can not be told from its files.
+fn guess_version(profile: &Profile, executable_path: &Path, game_dir: &Path) -> Option<GameVersion> {
+    let executable_name = executable_path.file_name()?.to_string_lossy().to_lowercase();
+    match executable_name.as_str() {
+        "gothic.exe" => Some(GameVersion::Gothic),
+        "gothic2.exe" => {
+            let data_dir = profile.layout.get(LayoutDir::Data).to_string_lossy().replace('\\', "/");
+            match find_case_insensitive(game_dir, &format!("{data_dir}/{ADDON_ARCHIVE}")) {
+                Some(_) => Some(GameVersion::Gothic2NightOfRaven),
+                None => Some(GameVersion::Gothic2Classic),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Recognizes the stores leaving files of their own in the game directory. Disc releases leave
+/// nothing behind and are only identified by their executable.
+fn detect_store(game_dir: &Path) -> Option<Distribution> {
+    let is_steam_library = game_dir.components().any(|c| c.as_os_str().eq_ignore_ascii_case("steamapps"));
+    if is_steam_library || find_case_insensitive(game_dir, "System/steam_api.dll").is_some() {
+        return Some(Distribution::Steam);
+    }
+
+    let has_gog_info = std::fs::read_dir(game_dir).ok()?.flatten().any(|entry| {
+        let name = entry.file_name().to_string_lossy().to_lowercase();
+        name.starts_with("goggame-") && name.ends_with(".info")
+    });
+
+    has_gog_info.then_some(Distribution::Gog)
+}
+
+fn has_community_patch(profile: &Profile, game_dir: &Path) -> bool {
+    let data_dir = profile.layout.get(LayoutDir::Data).to_string_lossy().replace('\\', "/");
+    let Some(entries) =
+        find_case_insensitive(game_dir, &data_dir).and_then(|dir| std::fs::read_dir(dir).ok())
+    else {
+        return false;
+    };
+
+    entries.flatten().any(|entry| {
+        let name = entry.file_name().to_string_lossy().to_lowercase();
+        COMMUNITY_PATCH_MARKERS.iter().any(|marker| name.contains(marker))
+    })
+}
+
+/// Guesses the language from the code page of the compiled scripts. Western European
+/// localizations share a code page, so German is told apart from English by its umlauts.
+fn detect_language(game_dir: &Path) -> Option<GameLanguage> {
+    let bytes = std::fs::read(find_case_insensitive(game_dir, COMPILED_SCRIPTS)?).ok()?;
+
+    // Only look at the string constants, the bytecode around them would skew the statistics.
+    let text = bytes
+        .split(|b| *b < 0x20 || *b == 0x7F)
+        .filter(|run| run.len() >= 8)
+        .flat_map(|run| run.iter().copied().chain([b'\n']))
+        .collect::<Vec<_>>();
+
+    const UMLAUTS: [u8; 7] = [0xC4, 0xD6, 0xDC, 0xDF, 0xE4, 0xF6, 0xFC];
+    let language = match TextEncoding::detect(&text) {
+        TextEncoding::Windows1251 => GameLanguage::Russian,
+        TextEncoding::Windows1250 => GameLanguage::Polish,
+        _ if text.iter().filter(|b| UMLAUTS.contains(b)).count() >= 10 => GameLanguage::German,
+        _ => GameLanguage::English,
+    };
+
+    Some(language)
+}
+
+fn detect_loaders(profile: &Profile, game_dir: &Path) -> Vec<Loader> {
+    let system_dir = profile.layout.get(LayoutDir::System).to_string_lossy().replace('\\', "/");
+    let markers = [(Loader::SystemPack, "SystemPack.ini"), (Loader::Union, "Union.ini")];
+
+    markers
+        .into_iter()
+        .filter(|(_, marker)| find_case_insensitive(game_dir, &format!("{system_dir}/{marker}")).is_some())
+        .map(|(loader, _)| loader)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::constants::DefaultProfile;
+    use crate::core::testing::TempDir;
+
+    const FIXTURE_EXECUTABLE: &[u8] = b"MZ fixture Gothic2.exe";
+    const FIXTURE_SHA256: &str = "6de008e459d355e64ad9b418895fc5808c2e49cee5c1574fb446b400c0190fcf";
+
+    fn game_dir(executable: &[u8]) -> TempDir {
+        let root = TempDir::new();
+        root.file("System/Gothic2.exe", executable);
+        root.dir("Data");
+        root.dir("_work");
+        root
+    }
+
+    fn profile(root: &TempDir) -> Profile {
+        Profile::new("Test", root.path()).with_game_type(DefaultProfile::Gothic2NightOfRaven)
+    }
+
+    #[test]
+    fn known_executables_identify_version_and_distribution() {
+        let root = game_dir(FIXTURE_EXECUTABLE);
+        let known = [(FIXTURE_SHA256, GameVersion::Gothic2NightOfRaven, Distribution::Disc)];
+
+        let fingerprint = fingerprint_with(&profile(&root), root.path(), &known);
+
+        assert_eq!(fingerprint.executable_sha256.as_deref(), Some(FIXTURE_SHA256));
+        assert_eq!(fingerprint.version, Some(GameVersion::Gothic2NightOfRaven));
+        assert_eq!(fingerprint.distribution, Some(Distribution::Disc));
+        assert!(fingerprint.errors.is_empty() && fingerprint.warnings.is_empty(), "{fingerprint:?}");
+
+        root.file("Data/Gothic2_CommunityPatch.vdf", "");
+        let fingerprint = fingerprint_with(&profile(&root), root.path(), &known);
+        assert_eq!(fingerprint.version, Some(GameVersion::Gothic2NightOfRavenCommunityPatch));
+    }
+
+    #[test]
+    fn unknown_executables_are_reported_as_unknown() {
+        let root = game_dir(b"");
+
+        let fingerprint = fingerprint(&profile(&root), root.path());
+
+        assert_eq!(fingerprint.version, None);
+        assert_eq!(fingerprint.distribution, None);
+        assert_eq!(fingerprint.warnings, ["Unrecognized executable, the game version is unknown"]);
+        assert_eq!(fingerprint.summary(), "Unknown version, Unknown distribution");
+    }
+
+    #[test]
+    fn unknown_original_sized_executables_are_identified_by_the_installed_files() {
+        let root = game_dir(&vec![0; MIN_EXECUTABLE_SIZE as usize]);
+
+        let fingerprint = fingerprint(&profile(&root), root.path());
+        assert_eq!(fingerprint.version, Some(GameVersion::Gothic2Classic));
+        assert_eq!(fingerprint.distribution, Some(Distribution::Disc));
+        assert_eq!(
+            fingerprint.warnings,
+            ["Unrecognized executable, the game version was guessed from the installed files"]
+        );
+
+        root.file("Data/Worlds_Addon.vdf", "");
+        let fingerprint = super::fingerprint(&profile(&root), root.path());
+        assert_eq!(fingerprint.version, Some(GameVersion::Gothic2NightOfRaven));
+
+        root.file("Data/Gothic2_CommunityPatch.vdf", "");
+        root.file("System/steam_api.dll", "");
+        let fingerprint = super::fingerprint(&profile(&root), root.path());
+        assert_eq!(fingerprint.version, Some(GameVersion::Gothic2NightOfRavenCommunityPatch));
+        assert_eq!(fingerprint.distribution, Some(Distribution::Steam));
+    }
+
+    #[test]
+    fn store_files_reveal_the_distribution_of_unknown_executables() {
+        let root = game_dir(b"");
+        root.file("goggame-1207658787.info", "{}");
+
+        let fingerprint = fingerprint(&profile(&root), root.path());
+
+        assert_eq!(fingerprint.version, None);
+        assert_eq!(fingerprint.distribution, Some(Distribution::Gog));
+    }
+}
@@ -1,12 +1,13 @@
 use std::fs::create_dir_all;
-use std::fs::read_to_string;
-use std::fs::write;
 use std::path::Path;
 use std::path::PathBuf;
 
 use crate::app::session;
 use crate::core::constants;
 use crate::core::profile;
+use crate::core::profile::Lookup;
+use crate::core::schema;
+use crate::error::ErrorContext;
 
 fn default_path<P: AsRef<Path>>(custom_path: Option<P>) -> PathBuf {
     match custom_path {
@@ -19,58 +20,231 @@ pub fn save_app_session<P: AsRef<Path>>(
     session: &session::ApplicationSession,
     custom_path: Option<P>,
 ) -> Result<(), std::io::Error> {
-    let default_path = default_path(custom_path);
-    let session_string = serde_json::to_string_pretty(session)?;
-    tracing::info!("Writing to {}", default_path.join("session.json").display());
-    write(default_path.join("session.json"), session_string)?;
+    let session_path = default_path(custom_path).join("session.json");
+    tracing::info!("Writing to {}", session_path.display());
+    schema::SESSION_SCHEMA.write(&session_path, session)
+}
+
+/// Saves the error history, which is kept across sessions for the logs window.
+pub fn save_error_history<P: AsRef<Path>>(
+    errors: &[ErrorContext],
+    custom_path: Option<P>,
+) -> Result<(), std::io::Error> {
+    let history_path = default_path(custom_path).join("errors.json");
+    tracing::info!("Writing to {}", history_path.display());
+    schema::ERROR_HISTORY_SCHEMA.write(&history_path, &crate::error::ErrorHistory { errors: errors.to_vec() })
+}
 
-    Ok(())
+/// Loads the errors of previous sessions, oldest first. An unreadable history is set aside and
+/// replaced rather than recovered from a backup.
+pub fn load_error_history<P: AsRef<Path>>(custom_path: Option<P>) -> Result<Vec<ErrorContext>, ErrorContext> {
+    let history_path = default_path(custom_path).join("errors.json");
+    let history: Option<crate::error::ErrorHistory> =
+        schema::ERROR_HISTORY_SCHEMA.read(&history_path, |_| false)?;
+    Ok(history.map(|history| history.errors).unwrap_or_default())
 }
 
-pub fn load_app_session<P: AsRef<Path>>(custom_path: Option<P>) -> Option<session::ApplicationSession> {
-    let default_path = default_path(custom_path);
-    if !default_path.exists() {
-        return None;
-    }
+/// Reads the log settings of the saved session before the logger is set up. Unlike
+/// [`load_app_session`], an unreadable session is left alone and the defaults are used.
+pub fn load_log_settings<P: AsRef<Path>>(custom_path: Option<P>) -> session::LogSettings {
+    std::fs::read_to_string(default_path(custom_path).join("session.json"))
+        .ok()
+        .and_then(|contents| schema::SESSION_SCHEMA.parse::<session::ApplicationSession>(&contents).ok())
+        .map(|session| session.log_settings)
+        .unwrap_or_default()
+}
 
-    tracing::info!("Reading from {}", default_path.join("session.json").display());
-    let session_json = read_to_string(default_path.join("session.json")).ok()?;
+/// Log file described by the log settings.
+pub fn log_file_config<P: AsRef<Path>>(
+    settings: &session::LogSettings,
+    custom_path: Option<P>,
+) -> crate::logger::LogFileConfig {
+    let directory = settings.directory.clone().unwrap_or_else(|| default_path(custom_path).join("logs"));
+    crate::logger::LogFileConfig {
+        path: directory.join(format!("{}.log", constants::APP_NAME)),
+        max_size: settings.max_file_size_mb.max(1) * 1024 * 1024,
+        retained_files: settings.retained_files,
+    }
+}
 
-    let Ok(session): Result<session::ApplicationSession, _> = serde_json::from_str(&session_json) else {
-        return None;
-    };
+/// What to do with a file that cannot be loaded while one of its backups still can.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    /// Ask whether the backup should be used instead.
+    Ask,
+    /// Report the error without touching the backups, for use without a user interface.
+    Never,
+}
 
-    Some(session)
+/// Loads the session, migrating it from older schema versions. Returns `Ok(None)` if there is no
+/// saved session yet.
+pub fn load_app_session<P: AsRef<Path>>(
+    custom_path: Option<P>,
+    recovery: Recovery,
+) -> Result<Option<session::ApplicationSession>, ErrorContext> {
+    let session_path = default_path(custom_path).join("session.json");
+    tracing::info!("Reading from {}", session_path.display());
+    schema::SESSION_SCHEMA.read(&session_path, |backup| confirm_recovery(recovery, "The session", backup))
 }
 
 pub fn save_profile<P: AsRef<Path>>(
     profile: &profile::Profile,
     custom_path: Option<P>,
 ) -> Result<(), std::io::Error> {
-    let default_profile_path = default_path(custom_path);
-    let this_profile_path = default_profile_path.join(&profile.name);
-    let profile_json = serde_json::to_string_pretty(&profile).map_err(std::io::Error::other)?;
-
+    let this_profile_path = default_path(custom_path).join(&profile.name);
     create_dir_all(&this_profile_path).map_err(|e| std::io::Error::new(e.kind(), e))?;
 
     tracing::info!("Writing to {}", this_profile_path.join("profile.json").display());
-    write(default_profile_path.join(&profile.name).join("profile.json"), profile_json)?;
+    schema::PROFILE_SCHEMA.write(&this_profile_path.join("profile.json"), profile)
+}
 
-    Ok(())
+/// Loads a profile, migrating it from older schema versions. Returns `Ok(None)` if the profile
+/// was never saved.
+pub fn load_profile<P: AsRef<Path>>(
+    name: &str,
+    custom_path: Option<P>,
+    recovery: Recovery,
+) -> Result<Option<profile::Profile>, ErrorContext> {
+    let profile_path = default_path(custom_path).join(name).join("profile.json");
+    tracing::info!("Reading from {}", profile_path.display());
+    schema::PROFILE_SCHEMA
+        .read(&profile_path, |backup| confirm_recovery(recovery, &format!("The profile \"{name}\""), backup))
 }
 
-pub fn load_profile<P: AsRef<Path>>(name: &str, custom_path: Option<P>) -> Option<profile::Profile> {
-    let default_profile_path = default_path(custom_path);
-    let this_profile_path = default_profile_path.join(name);
+/// Loads the default profiles followed by every custom profile found in the user data directory.
+/// Profiles that cannot be loaded are left out, or replaced by defaults for the default profiles,
+/// and their errors are returned alongside by profile name.
+pub fn load_profiles(
+    custom_path: Option<&Path>,
+    recovery: Recovery,
+) -> (Lookup<String, profile::Profile>, Vec<(String, ErrorContext)>) {
+    let mut load_errors = Vec::new();
+    let mut load = |name: &str| {
+        load_profile(name, custom_path, recovery).unwrap_or_else(|err| {
+            load_errors.push((name.to_owned(), err));
+            None
+        })
+    };
+
+    let mut profiles: Lookup<String, profile::Profile> = constants::DefaultProfile::into_iter()
+        .map(|game_type| {
+            let name_str = (*game_type).to_string();
+            let profile = load(&name_str)
+                .unwrap_or_else(|| profile::Profile::default().with_name(&name_str))
+                .with_game_type(*game_type)
+                .with_custom(false);
+            (name_str, profile)
+        })
+        .collect();
+
+    let mut custom_profile_names = list_profile_names(custom_path)
+        .into_iter()
+        .filter(|name| !profiles.contains_key(name))
+        .collect::<Vec<_>>();
+    custom_profile_names.sort_unstable();
 
-    tracing::info!("Reading from {}", this_profile_path.join("profile.json").display());
-    let profile_json = read_to_string(this_profile_path.join("profile.json")).ok()?;
+    custom_profile_names.iter().for_each(|name| {
+        if let Some(profile) = load(name) {
+            profiles.insert(name.clone(), profile.with_name(name).with_custom(true));
+        }
+    });
 
-    let Ok(profile): Result<profile::Profile, _> = serde_json::from_str(&profile_json) else {
-        return None;
+    (profiles, load_errors)
+}
+
+/// Asks whether a file that could not be loaded should be replaced by one of its backups.
+fn confirm_recovery(recovery: Recovery, subject: &str, backup_path: &Path) -> bool {
+    if recovery == Recovery::Never {
+        tracing::warn!("{subject} could not be loaded, a valid backup is at {}", backup_path.display());
+        return false;
+    }
+
+    let saved_at = std::fs::metadata(backup_path)
+        .and_then(|metadata| metadata.modified())
+        .map(|modified| {
+            chrono::DateTime::<chrono::Local>::from(modified).format("%Y-%m-%d %H:%M:%S").to_string()
+        })
+        .unwrap_or_else(|_| "an unknown time".to_owned());
+
+    rfd::MessageDialog::new()
+        .set_title("Recover from backup")
+        .set_description(format!(
+            "{subject} could not be loaded. Restore it from the backup saved at {saved_at}?\n\n{}",
+            backup_path.display()
+        ))
+        .set_buttons(rfd::MessageButtons::YesNo)
+        .set_level(rfd::MessageLevel::Warning)
+        .show()
+        == rfd::MessageDialogResult::Yes
+}
+
+pub fn profile_dir<P: AsRef<Path>>(name: &str, custom_path: Option<P>) -> PathBuf {
+    default_path(custom_path).join(name)
+}
+
+/// Lists the names of all profiles that have a `profile.json` in the user data directory.
+pub fn list_profile_names<P: AsRef<Path>>(custom_path: Option<P>) -> Vec<String> {
+    let default_profile_path = default_path(custom_path);
+    let Ok(entries) = std::fs::read_dir(&default_profile_path) else {
+        return Vec::new();
     };
 
-    Some(profile)
+    entries
+        .flatten()
+        .filter(|entry| entry.path().join("profile.json").is_file())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect()
+}
+
+/// Removes the directory of a profile, with its profile file, backups and everything else stored
+/// for it.
+pub fn remove_profile<P: AsRef<Path>>(name: &str, custom_path: Option<P>) -> Result<(), std::io::Error> {
+    let profile_dir = profile_dir(name, custom_path);
+    if !profile_dir.exists() {
+        return Ok(());
+    }
+
+    tracing::info!("Removing {}", profile_dir.display());
+    std::fs::remove_dir_all(profile_dir)
+}
+
+/// Returns the directory holding the installed mods of every instance of the given profile.
+pub fn mod_storage_root(session: &session::ApplicationSession, profile_name: &str) -> PathBuf {
+    let base_path = session.mod_storage_dir.as_deref().or(session.custom_user_data_path.as_deref());
+    mod_storage_root_in(base_path, profile_name)
+}
+
+/// Returns the mod storage root of a profile for the given mods directory, `None` meaning the
+/// default location.
+pub fn mod_storage_root_in(mods_dir: Option<&Path>, profile_name: &str) -> PathBuf {
+    let storage_root = default_path(mods_dir).join(profile_name).join("mods");
+    std::path::absolute(&storage_root).unwrap_or(storage_root)
+}
+
+/// Returns the directory holding the installed mods of a single instance.
+pub fn mod_storage_dir(
+    session: &session::ApplicationSession,
+    profile_name: &str,
+    instance_name: &str,
+) -> PathBuf {
+    mod_storage_root(session, profile_name).join(instance_name)
+}
+
+/// Returns the directory keeping the mods removed by undoable operations of a profile. It sits next
+/// to the mod storage root rather than in it, where it could clash with the storage of an instance.
+pub fn trash_dir(session: &session::ApplicationSession, profile_name: &str) -> PathBuf {
+    mod_storage_root(session, profile_name).with_file_name(crate::core::history::TRASH_DIR_NAME)
+}
+
+/// Returns the directory holding the snapshots of a single instance, next to `profile.json`.
+pub fn snapshot_dir(
+    session: &session::ApplicationSession,
+    profile_name: &str,
+    instance_name: &str,
+) -> PathBuf {
+    profile_dir(profile_name, session.custom_user_data_path.as_deref())
+        .join(crate::core::snapshot::SNAPSHOTS_DIR_NAME)
+        .join(instance_name)
 }
 
 pub fn default_themes<'a>() -> [(&'a str, iced::Theme); 22] {
@@ -0,0 +1,100 @@
+use std::path::Path;
+
+use chrono::Local;
+
+use crate::core::profile::Instance;
+use crate::core::utils;
+use crate::error::ErrorContext;
+
+/// Number of steps kept on the undo stack before the oldest ones are dropped.
+pub const HISTORY_LIMIT: usize = 50;
+
+/// Directory next to the mod storage root of a profile holding the mods removed by history steps.
+pub const TRASH_DIR_NAME: &str = "trash";
+
+#[derive(Debug, Clone)]
+pub struct HistoryStep {
+    /// Unique across sessions, names the trash directory of the step.
+    pub id: String,
+    pub description: String,
+    pub profile_name: String,
+    pub instance_name: String,
+    /// State of the instance on the other side of the step: the state before it while the step
+    /// is on the undo stack and the state after it while it is on the redo stack.
+    pub instance: Instance,
+}
+
+#[derive(Debug, Default)]
+pub struct History {
+    pub undo: Vec<HistoryStep>,
+    pub redo: Vec<HistoryStep>,
+    recorded: usize,
+}
+
+impl History {
+    /// Pushes a step capturing `instance` as the state to return to. Returns the new step id
+    /// together with the steps that can no longer be reached: the redo stack and the undo steps
+    /// beyond [`HISTORY_LIMIT`].
+    pub fn record(
+        &mut self,
+        description: &str,
+        profile_name: &str,
+        instance: &Instance,
+    ) -> (String, Vec<HistoryStep>) {
+        self.recorded += 1;
+        let id = format!("{}-{}", Local::now().format("%Y%m%d%H%M%S%3f"), self.recorded);
+
+        let mut discarded = std::mem::take(&mut self.redo);
+        self.undo.push(HistoryStep {
+            id: id.clone(),
+            description: description.to_owned(),
+            profile_name: profile_name.to_owned(),
+            instance_name: instance.name.clone(),
+            instance: instance.clone(),
+        });
+
+        let overflow = self.undo.len().saturating_sub(HISTORY_LIMIT);
+        discarded.extend(self.undo.drain(..overflow));
+        (id, discarded)
+    }
+
+    pub fn clear(&mut self) -> Vec<HistoryStep> {
+        self.undo.drain(..).chain(self.redo.drain(..)).collect()
+    }
+
+    pub fn undo_description(&self) -> Option<&str> {
+        self.undo.last().map(|step| step.description.as_str())
+    }
+
+    pub fn redo_description(&self) -> Option<&str> {
+        self.redo.last().map(|step| step.description.as_str())
+    }
+}
+
+/// Moves the mod directories of `current` that `target` does not reference into `trash_dir` and
+/// brings back the ones `target` references from there.
+pub fn sync_mod_dirs(current: &Instance, target: &Instance, trash_dir: &Path) -> Result<(), ErrorContext> {
+    let is_referenced = |instance: &Instance, path: &Path| instance.mods.iter().any(|m| m.path == path);
+
+    for mod_info in current.mods.iter().filter(|m| !is_referenced(target, &m.path) && m.path.exists()) {
+        let Some(dir_name) = mod_info.path.file_name() else {
+            continue;
+        };
+
+        tracing::info!("Moving mod \"{}\" to trash", mod_info.name);
+        utils::move_dir(&mod_info.path, &trash_dir.join(dir_name))?;
+    }
+
+    for mod_info in target.mods.iter().filter(|m| !is_referenced(current, &m.path)) {
+        let Some(trashed_path) = mod_info.path.file_name().map(|name| trash_dir.join(name)) else {
+            continue;
+        };
+
+        if trashed_path.exists() && !mod_info.path.exists() {
+            tracing::info!("Restoring mod \"{}\" from trash", mod_info.name);
+            utils::move_dir(&trashed_path, &mod_info.path)?;
+        }
+    }
+
+    Ok(())
+}
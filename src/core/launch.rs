@@ -0,0 +1,98 @@
+//! Starting the game of a profile with the configured launch options.
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::app::session;
+use crate::core::profile::Profile;
+use crate::error::Error;
+use crate::error::ErrorContext;
+
+/// Program used to run the game outside of Windows, unless overridden by this variable.
+pub const WINE_ENV_VAR: &str = "WINE";
+
+/// Log the game writes next to its executable while zSpy logging is enabled.
+pub const ZSPY_LOG_FILE_NAME: &str = "zSpy.log";
+
+/// Command line that starts the game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LaunchCommand {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub working_dir: PathBuf,
+    /// Where the zSpy log is written, `None` while zSpy is disabled.
+    pub zspy_log: Option<PathBuf>,
+}
+
+impl LaunchCommand {
+    pub fn new(profile: &Profile, options: &session::GameLaunchConfiguration) -> Result<Self, ErrorContext> {
+        let executable = profile.path.join(profile.executable());
+        if profile.path.as_os_str().is_empty() || !executable.is_file() {
+            return Err(ErrorContext::builder()
+                .error(Error::system(
+                    format!("Game executable not found: {}", executable.display()),
+                    "Launch",
+                ))
+                .suggested_action("Set the game directory and executable of the profile")
+                .build());
+        }
+
+        let working_dir = executable.parent().map(PathBuf::from).unwrap_or_else(|| profile.path.clone());
+        let zspy_log = is_zspy_enabled(options).then(|| working_dir.join(ZSPY_LOG_FILE_NAME));
+        let args = game_arguments(options);
+        let (program, args) = match cfg!(windows) {
+            true => (executable, args),
+            false => {
+                let wine = std::env::var_os(WINE_ENV_VAR).map(PathBuf::from).unwrap_or_else(|| "wine".into());
+                (wine, std::iter::once(executable.display().to_string()).chain(args).collect())
+            }
+        };
+
+        Ok(Self { program, args, working_dir, zspy_log })
+    }
+
+    pub fn with_args(mut self, args: impl IntoIterator<Item = String>) -> Self {
+        self.args.extend(args);
+        self
+    }
+
+    pub fn spawn(&self) -> Result<std::process::Child, ErrorContext> {
+        tracing::info!("Launching {} {}", self.program.display(), self.args.join(" "));
+        std::process::Command::new(&self.program)
+            .args(&self.args)
+            .current_dir(&self.working_dir)
+            .spawn()
+            .map_err(|err| {
+                ErrorContext::builder()
+                    .error(
+                        Error::system("Could not start the game", "Launch")
+                            .with_path(&self.program)
+                            .with_source(&err),
+                    )
+                    .suggested_action(match cfg!(windows) {
+                        true => "Check if the game executable can be run",
+                        false => "Check if wine is installed or set WINE to the program running the game",
+                    })
+                    .build()
+            })
+    }
+}
+
+/// Game arguments for the zSpy, Marvin mode and script reparsing options. The renderer is chosen
+/// by the wrapper installed in the game directory and has no argument.
+pub fn game_arguments(options: &session::GameLaunchConfiguration) -> Vec<String> {
+    let settings = &options.game_settings;
+    let zspy = is_zspy_enabled(options).then(|| format!("-zlog:{},s", u8::from(settings.zspy.verbosity)));
+    let marvin = settings.is_marvin_mode_enabled.then(|| "-devmode".to_owned());
+    let reparse = session::ParserCommand::into_iter()
+        .filter(|command| options.parser_settings.commands.get(*command).copied().unwrap_or(false))
+        .map(|command| format!("-{}", command.clone().into_argument()));
+
+    zspy.into_iter().chain(marvin).chain(reparse).collect()
+}
+
+pub fn is_zspy_enabled(options: &session::GameLaunchConfiguration) -> bool {
+    let zspy = &options.game_settings.zspy;
+    zspy.is_enabled && zspy.verbosity != session::ZSpyVerbosity::Off
+}
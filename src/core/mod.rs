@@ -1,5 +1,23 @@
+pub mod basefiles;
+pub mod bundle;
+pub mod compare;
 pub mod constants;
+pub mod deploy;
+pub mod diagnostics;
+pub mod diff;
+pub mod discovery;
+pub mod fingerprint;
 pub mod helpers;
+pub mod history;
+pub mod launch;
+pub mod modlist;
+pub mod modpack;
+pub mod overlaps;
 pub mod profile;
+pub mod schema;
 pub mod services;
+pub mod snapshot;
+pub mod storage;
+#[cfg(test)]
+pub mod testing;
 pub mod utils;
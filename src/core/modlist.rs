@@ -0,0 +1,343 @@
+use std::io::Read;
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use chrono::Local;
+use derive_more::Display;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+
+use crate::core::bundle;
+use crate::core::constants;
+use crate::core::constants::DefaultProfile;
+use crate::core::profile::Instance;
+use crate::core::profile::ModInfo;
+use crate::error::Error;
+use crate::error::ErrorContext;
+
+pub const MODLIST_FORMAT_VERSION: u32 = 1;
+pub const MODLIST_EXTENSION: &str = "json";
+
+/// A shareable description of an instance that references mods by name, version and hash instead
+/// of shipping their files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModList {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created: DateTime<Local>,
+    pub game_type: DefaultProfile,
+    pub instance_name: String,
+    /// Mods sorted by priority, lowest first.
+    pub mods: Vec<ModListEntry>,
+    /// Disabled files in the portable form of [`bundle::to_portable_path`].
+    pub disabled_files: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModListEntry {
+    pub name: String,
+    pub version: Option<String>,
+    pub source_hash: Option<String>,
+    pub enabled: bool,
+}
+
+impl ModList {
+    pub fn new(
+        instance: &Instance,
+        game_dir: &Path,
+        mod_storage_dir: &Path,
+        game_type: DefaultProfile,
+    ) -> Self {
+        let mut mods = instance.mods.iter().collect::<Vec<_>>();
+        mods.sort_by_key(|m| instance.load_order.get(&m.name).copied().unwrap_or_default());
+
+        let mut disabled_files = instance
+            .files
+            .iter()
+            .filter(|(_, metadata)| !metadata.enabled)
+            .map(|(path, _)| bundle::to_portable_path(path, game_dir, mod_storage_dir))
+            .collect::<Vec<_>>();
+        disabled_files.sort();
+
+        Self {
+            format_version: MODLIST_FORMAT_VERSION,
+            app_version: constants::APP_VERSION.to_owned(),
+            created: Local::now(),
+            game_type,
+            instance_name: instance.name.clone(),
+            mods: mods
+                .into_iter()
+                .map(|m| ModListEntry {
+                    name: m.name.clone(),
+                    version: m.version.clone(),
+                    source_hash: m.source_hash.clone(),
+                    enabled: m.enabled,
+                })
+                .collect(),
+            disabled_files,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ErrorContext> {
+        let content = std::fs::read_to_string(path).map_err(|e| modlist_error(e, path, "Load Mod List"))?;
+        let modlist: ModList =
+            serde_json::from_str(&content).map_err(|e| modlist_error(e, path, "Load Mod List"))?;
+
+        if modlist.format_version > MODLIST_FORMAT_VERSION {
+            return Err(ErrorContext::builder()
+                .error(Error::mods_service(
+                    format!("Unsupported mod list format version {}", modlist.format_version),
+                    "Load Mod List",
+                ))
+                .suggested_action("Update the application to load this mod list")
+                .build());
+        }
+
+        Ok(modlist)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ErrorContext> {
+        let content =
+            serde_json::to_string_pretty(self).map_err(|e| modlist_error(e, path, "Save Mod List"))?;
+        std::fs::write(path, content).map_err(|e| modlist_error(e, path, "Save Mod List"))
+    }
+}
+
+/// A local copy of a mod, either an installed mod directory or an archive found on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalMod {
+    pub name: String,
+    pub version: Option<String>,
+    pub source_hash: Option<String>,
+    pub path: PathBuf,
+}
+
+impl From<&ModInfo> for LocalMod {
+    fn from(mod_info: &ModInfo) -> Self {
+        Self {
+            name: mod_info.name.clone(),
+            version: mod_info.version.clone(),
+            source_hash: mod_info.source_hash.clone(),
+            path: mod_info.path.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Display)]
+pub enum ModMatch {
+    #[display("Available")]
+    Found(LocalMod),
+    #[display("Different version{}", _0.version.as_ref().map(|v| format!(" ({v})")).unwrap_or_default())]
+    DifferentVersion(LocalMod),
+    #[display("Missing")]
+    Missing,
+}
+
+impl ModMatch {
+    pub fn local_mod(&self) -> Option<&LocalMod> {
+        match self {
+            ModMatch::Found(local_mod) | ModMatch::DifferentVersion(local_mod) => Some(local_mod),
+            ModMatch::Missing => None,
+        }
+    }
+}
+
+/// Matches every mod list entry against the local mods, by hash first and by name otherwise.
+pub fn match_mods(modlist: &ModList, local_mods: &[LocalMod]) -> Vec<ModMatch> {
+    modlist
+        .mods
+        .iter()
+        .map(|entry| {
+            let by_hash = entry
+                .source_hash
+                .as_ref()
+                .and_then(|hash| local_mods.iter().find(|m| m.source_hash.as_ref() == Some(hash)));
+
+            if let Some(local_mod) = by_hash {
+                return ModMatch::Found(local_mod.clone());
+            }
+
+            // Without hashes on both sides the version is all there is to compare.
+            match local_mods.iter().find(|m| m.name == entry.name) {
+                Some(local_mod)
+                    if (entry.source_hash.is_none() || local_mod.source_hash.is_none())
+                        && local_mod.version == entry.version =>
+                {
+                    ModMatch::Found(local_mod.clone())
+                }
+                Some(local_mod) => ModMatch::DifferentVersion(local_mod.clone()),
+                None => ModMatch::Missing,
+            }
+        })
+        .collect()
+}
+
+/// Collects the zip archives of a directory (not recursively) as local mods.
+pub fn scan_archives(dir: &Path) -> Vec<LocalMod> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e.eq_ignore_ascii_case("zip")))
+        .map(|path| {
+            let (name, version) = split_file_name(&path);
+            LocalMod { name, version, source_hash: source_hash(&path).ok(), path }
+        })
+        .collect()
+}
+
+/// Splits the file name of a mod archive or directory into the name the mod is installed under
+/// and a trailing version, e.g. `Mod-1.2.3.zip` into `Mod` and `1.2.3` or `Mod_v2` into `Mod`
+/// and `2`.
+pub fn split_file_name(path: &Path) -> (String, Option<String>) {
+    let is_archive = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("zip"));
+    let stem = match is_archive {
+        true => path.file_stem(),
+        false => path.file_name(),
+    };
+    let stem = stem.map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+
+    let Some((name, version)) = stem.rsplit_once(['-', '_', ' ']) else {
+        return (stem, None);
+    };
+
+    let version = version.strip_prefix(['v', 'V']).unwrap_or(version);
+    match !name.is_empty() && version.starts_with(|c: char| c.is_ascii_digit()) {
+        true => (name.to_owned(), Some(version.to_owned())),
+        false => (stem, None),
+    }
+}
+
+/// SHA-256 of a mod archive, or of the relative paths and contents of a mod directory.
+pub fn source_hash(path: &Path) -> Result<String, ErrorContext> {
+    let mut hasher = sha2::Sha256::new();
+
+    if path.is_dir() {
+        let mut files = ignore::WalkBuilder::new(path)
+            .ignore(false)
+            .hidden(false)
+            .build()
+            .flatten()
+            .map(|entry| entry.into_path())
+            .filter(|p| p.is_file())
+            .collect::<Vec<_>>();
+        files.sort();
+
+        for file in files {
+            let relative_path = file.strip_prefix(path).unwrap().to_string_lossy().replace('\\', "/");
+            hasher.update(relative_path.as_bytes());
+            hash_file(&file, &mut hasher)?;
+        }
+    } else {
+        hash_file(path, &mut hasher)?;
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn hash_file(path: &Path, hasher: &mut sha2::Sha256) -> Result<(), ErrorContext> {
+    let mut file = std::fs::File::open(path).map_err(|e| modlist_error(e, path, "Hash Mod"))?;
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).map_err(|e| modlist_error(e, path, "Hash Mod"))?;
+        if read == 0 {
+            return Ok(());
+        }
+        hasher.update(&buffer[..read]);
+    }
+}
+
+fn modlist_error(err: impl std::error::Error + 'static, path: &Path, operation: &str) -> ErrorContext {
+    ErrorContext::builder()
+        .error(Error::file_system("Could not access the file", operation).with_path(path).with_source(&err))
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::testing::TempDir;
+
+    fn entry(name: &str, version: Option<&str>, source_hash: Option<&str>) -> ModListEntry {
+        ModListEntry {
+            name: name.to_owned(),
+            version: version.map(str::to_owned),
+            source_hash: source_hash.map(str::to_owned),
+            enabled: true,
+        }
+    }
+
+    fn modlist(mods: Vec<ModListEntry>) -> ModList {
+        ModList {
+            format_version: MODLIST_FORMAT_VERSION,
+            app_version: constants::APP_VERSION.to_owned(),
+            created: Local::now(),
+            game_type: DefaultProfile::Gothic2NightOfRaven,
+            instance_name: "Default".to_owned(),
+            mods,
+            disabled_files: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn splits_names_and_versions_of_archives_and_directories() {
+        let split = |path: &str| split_file_name(Path::new(path));
+
+        assert_eq!(split("/mods/Mod-1.2.3.zip"), ("Mod".to_owned(), Some("1.2.3".to_owned())));
+        assert_eq!(split("/mods/Mod_v2.ZIP"), ("Mod".to_owned(), Some("2".to_owned())));
+        assert_eq!(split("/mods/Mod v2.1"), ("Mod".to_owned(), Some("2.1".to_owned())));
+        assert_eq!(split("/mods/My_Cool_Mod.zip"), ("My_Cool_Mod".to_owned(), None));
+        assert_eq!(split("/mods/Mod.Patch"), ("Mod.Patch".to_owned(), None));
+    }
+
+    #[test]
+    fn matches_versioned_archives_by_name() {
+        let root = TempDir::new();
+        root.file("archives/Patch-1.2.zip", "patch 1.2");
+        root.file("archives/Textures-2.0.zip", "textures 2.0");
+        root.file("archives/notes.txt", "");
+
+        let modlist = modlist(vec![
+            entry("Patch", Some("1.2"), None),
+            entry("Textures", Some("1.0"), None),
+            entry("Music", None, None),
+        ]);
+        let matches = match_mods(&modlist, &scan_archives(&root.path().join("archives")));
+
+        assert!(matches!(&matches[0], ModMatch::Found(m) if m.path.ends_with("Patch-1.2.zip")));
+        assert!(matches!(&matches[1], ModMatch::DifferentVersion(m) if m.version.as_deref() == Some("2.0")));
+        assert_eq!(matches[2], ModMatch::Missing);
+    }
+
+    #[test]
+    fn hashes_take_precedence_over_names() {
+        let local_mod = |name: &str, hash: &str| LocalMod {
+            name: name.to_owned(),
+            version: None,
+            source_hash: Some(hash.to_owned()),
+            path: PathBuf::from(name),
+        };
+        let local_mods = [local_mod("Renamed", "abc"), local_mod("Patch", "def")];
+
+        let modlist = modlist(vec![entry("Patch", None, Some("abc")), entry("Patch", None, Some("123"))]);
+        let matches = match_mods(&modlist, &local_mods);
+
+        assert!(matches!(&matches[0], ModMatch::Found(m) if m.name == "Renamed"));
+        assert!(matches!(&matches[1], ModMatch::DifferentVersion(m) if m.name == "Patch"));
+    }
+
+    #[test]
+    fn mod_lists_survive_saving_and_loading() {
+        let root = TempDir::new();
+        let path = root.path().join("Default.json");
+        let modlist = modlist(vec![entry("Patch", Some("1.2"), Some("abc"))]);
+
+        modlist.save(&path).unwrap();
+        assert_eq!(ModList::load(&path).unwrap(), modlist);
+    }
+}
@@ -0,0 +1,363 @@
+//! Declarative description of an instance, meant to be kept under version control.
+//!
+//! A modpack lists the archives or directories of its mods in load order, together with their
+//! hashes, enabled flags and excluded files, and optionally the launch settings. Applying it makes
+//! an instance match it. Modpacks are written in TOML or JSON:
+//!
+//! ```toml
+//! [launch]
+//! zspy = 5
+//! marvin_mode = true
+//!
+//! [[mods]]
+//! source = "archives/Union-1.0m.zip"
+//! sha256 = "..."
+//!
+//! [[mods]]
+//! source = "archives/Patch.zip"
+//! enabled = false
+//! exclude = ["System/Gothic.ini"]
+//! ```
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::app::session;
+use crate::core::modlist;
+use crate::error::Error;
+use crate::error::ErrorContext;
+
+pub const MODPACK_EXTENSIONS: [&str; 2] = ["toml", "json"];
+/// Directory in the trash of a profile keeping the mods a modpack replaces until it is fully
+/// applied.
+pub const STAGING_DIR_NAME: &str = "modpack";
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Modpack {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Mods in load order, later mods override earlier ones.
+    #[serde(default)]
+    pub mods: Vec<ModpackEntry>,
+    #[serde(default)]
+    pub launch: Option<ModpackLaunch>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModpackEntry {
+    /// Zip archive or directory of the mod, relative to the modpack file.
+    pub source: PathBuf,
+    /// SHA-256 of the source as computed by [`modlist::source_hash`].
+    #[serde(default)]
+    pub sha256: Option<String>,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    /// Files of the mod that stay disabled, relative to the mod directory.
+    #[serde(default)]
+    pub exclude: Vec<PathBuf>,
+}
+
+/// Launch settings of the modpack. Settings left out keep their current value.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ModpackLaunch {
+    #[serde(default)]
+    pub renderer: Option<session::RendererBackend>,
+    /// zSpy verbosity from 0 to 10, 0 disabling zSpy.
+    #[serde(default)]
+    pub zspy: Option<u8>,
+    #[serde(default)]
+    pub marvin_mode: Option<bool>,
+    /// Scripts reparsed on start, replacing the current selection.
+    #[serde(default)]
+    pub reparse: Option<Vec<session::ParserCommand>>,
+}
+
+/// What applying a modpack changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ModpackReport {
+    pub installed: Vec<String>,
+    pub reinstalled: Vec<String>,
+    pub removed: Vec<String>,
+    pub moved: Vec<String>,
+    pub enabled: Vec<String>,
+    pub disabled: Vec<String>,
+    /// Files enabled or disabled to match the exclusions.
+    pub toggled_files: usize,
+    pub launch_settings: bool,
+}
+
+impl ModpackReport {
+    pub fn is_empty(&self) -> bool {
+        self.installed.is_empty()
+            && self.reinstalled.is_empty()
+            && self.removed.is_empty()
+            && self.moved.is_empty()
+            && self.enabled.is_empty()
+            && self.disabled.is_empty()
+            && self.toggled_files == 0
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} installed, {} reinstalled, {} removed, {} moved, {} enabled, {} disabled, {} files toggled",
+            self.installed.len(),
+            self.reinstalled.len(),
+            self.removed.len(),
+            self.moved.len(),
+            self.enabled.len(),
+            self.disabled.len(),
+            self.toggled_files
+        )
+    }
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+impl ModpackEntry {
+    /// Name the mod is installed under.
+    pub fn name(&self) -> String {
+        modlist::split_file_name(&self.source).0
+    }
+}
+
+impl ModpackLaunch {
+    pub fn apply_to(&self, session: &mut session::ApplicationSession) {
+        let options = session.launch_options.get_or_insert_default();
+        if let Some(renderer) = &self.renderer {
+            options.game_settings.renderer = renderer.clone();
+            session.active_renderer_backend = Some(renderer.clone());
+        }
+
+        if let Some(level) = self.zspy {
+            let zspy = session::ZspyConfig { is_enabled: level > 0, verbosity: level.into() };
+            options.game_settings.zspy = zspy.clone();
+            session.active_zspy_config = Some(zspy);
+        }
+
+        if let Some(marvin_mode) = self.marvin_mode {
+            options.game_settings.is_marvin_mode_enabled = marvin_mode;
+        }
+
+        if let Some(reparse) = &self.reparse {
+            options.parser_settings.commands = session::ParserCommand::into_iter()
+                .map(|command| (command.clone(), reparse.contains(command)))
+                .collect();
+        }
+    }
+}
+
+impl Modpack {
+    /// Loads a modpack from a `.toml` or `.json` file. Mod sources are resolved against the
+    /// directory of the file.
+    pub fn load(path: &Path) -> Result<Self, ErrorContext> {
+        let content = std::fs::read_to_string(path).map_err(|e| modpack_error(e, path, "Load Modpack"))?;
+        let is_toml = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+        let mut modpack: Modpack = match is_toml {
+            true => toml::from_str(&content).map_err(|e| modpack_error(e, path, "Load Modpack"))?,
+            false => serde_json::from_str(&content).map_err(|e| modpack_error(e, path, "Load Modpack"))?,
+        };
+
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        modpack.mods.iter_mut().for_each(|entry| entry.source = base_dir.join(&entry.source));
+        modpack.validate()?;
+        Ok(modpack)
+    }
+
+    fn validate(&self) -> Result<(), ErrorContext> {
+        let mut names = hashbrown::HashSet::new();
+        if let Some(entry) = self.mods.iter().find(|entry| !names.insert(entry.name())) {
+            return Err(ErrorContext::builder()
+                .error(Error::mods_service(
+                    format!("Mod \"{}\" is listed more than once", entry.name()),
+                    "Load Modpack",
+                ))
+                .suggested_action("Remove the duplicate entry from the modpack")
+                .build());
+        }
+
+        match self.launch.as_ref().and_then(|launch| launch.zspy).filter(|level| *level > 10) {
+            Some(level) => Err(ErrorContext::builder()
+                .error(Error::mods_service(format!("Invalid zSpy verbosity {level}"), "Load Modpack"))
+                .suggested_action("Use a zSpy verbosity from 0 to 10")
+                .build()),
+            None => Ok(()),
+        }
+    }
+}
+
+fn modpack_error(err: impl std::error::Error + 'static, path: &Path, operation: &str) -> ErrorContext {
+    ErrorContext::builder()
+        .error(Error::file_system("Could not load the modpack", operation).with_path(path).with_source(&err))
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::state;
+    use crate::core::profile::Instance;
+    use crate::core::profile::Lookup;
+    use crate::core::profile::Profile;
+    use crate::core::services::modlist::ModListService;
+    use crate::core::services::mods::ModService;
+    use crate::core::testing::TempDir;
+
+    #[test]
+    fn toml_and_json_modpacks_load_with_sources_next_to_them() {
+        let root = TempDir::new();
+        let toml_path = root.file(
+            "packs/pack.toml",
+            r#"
+            [launch]
+            zspy = 5
+
+            [[mods]]
+            source = "archives/Union-1.0m.zip"
+            sha256 = "abc"
+
+            [[mods]]
+            source = "archives/Patch"
+            enabled = false
+            exclude = ["System/Gothic.ini"]
+            "#,
+        );
+        let json_path = root.file(
+            "packs/pack.json",
+            r#"{ "launch": { "zspy": 5 }, "mods": [
+                { "source": "archives/Union-1.0m.zip", "sha256": "abc" },
+                { "source": "archives/Patch", "enabled": false, "exclude": ["System/Gothic.ini"] }
+            ] }"#,
+        );
+
+        let modpack = Modpack::load(&toml_path).unwrap();
+        assert_eq!(modpack, Modpack::load(&json_path).unwrap());
+        assert_eq!(modpack.mods[0].source, root.path().join("packs/archives/Union-1.0m.zip"));
+        assert_eq!(modpack.mods[0].name(), "Union");
+        assert!(modpack.mods[0].enabled && !modpack.mods[1].enabled);
+        assert_eq!(modpack.mods[1].exclude, [PathBuf::from("System/Gothic.ini")]);
+        assert_eq!(modpack.launch.unwrap().zspy, Some(5));
+    }
+
+    #[test]
+    fn invalid_modpacks_are_rejected() {
+        let root = TempDir::new();
+        let duplicate =
+            root.file("duplicate.toml", "[[mods]]\nsource = \"A.zip\"\n[[mods]]\nsource = \"A-2.0\"\n");
+        let verbosity = root.file("verbosity.toml", "[launch]\nzspy = 11\n");
+        let malformed = root.file("malformed.toml", "[[mods]\nsource = \"A.zip\"\n");
+
+        assert!(Modpack::load(&duplicate).unwrap_err().error.to_string().contains("more than once"));
+        assert!(Modpack::load(&verbosity).unwrap_err().error.to_string().contains("zSpy verbosity"));
+        assert!(Modpack::load(&malformed).is_err());
+    }
+
+    struct Setup {
+        root: TempDir,
+        session: session::ApplicationSession,
+        state: state::ApplicationState,
+    }
+
+    impl Setup {
+        /// Instance with the mods of the given source directories installed in order.
+        fn new(sources: &[&str]) -> Self {
+            let root = TempDir::new();
+            root.file("sources/A/Data/A.vdf", "a");
+            root.file("sources/A/Data/Shared.vdf", "a");
+            root.file("sources/B/Data/B.vdf", "b");
+            root.file("sources/C/Data/C.vdf", "c");
+            root.file("sources/C/Data/Shared.vdf", "c");
+            root.file("sources/D/Data/D.vdf", "d");
+            root.file("sources/Broken.zip", "not an archive");
+
+            let mut instance = Instance::new("Default", Lookup::default(), Vec::new());
+            instance.load_order.insert("Default".to_owned(), 0);
+            let profile = Profile::new("Test", &root.dir("game"))
+                .with_instances(Some(Lookup::from_iter([("Default".to_owned(), instance)])));
+
+            let mut setup = Self {
+                session: session::ApplicationSession {
+                    active_profile: Some("Test".to_owned()),
+                    active_instance: Some("Default".to_owned()),
+                    mod_storage_dir: Some(root.path().join("mods")),
+                    ..Default::default()
+                },
+                state: state::ApplicationState::default(),
+                root,
+            };
+            setup.state.profile.profiles.insert("Test".to_owned(), profile);
+
+            for source in sources {
+                let source = setup.root.path().join("sources").join(source);
+                ModService::new(&mut setup.session, &mut setup.state).try_add_mod(&source).unwrap();
+            }
+
+            setup
+        }
+
+        fn entry(&self, source: &str) -> ModpackEntry {
+            ModpackEntry {
+                source: self.root.path().join("sources").join(source),
+                sha256: None,
+                enabled: true,
+                exclude: Vec::new(),
+            }
+        }
+
+        fn instance(&self) -> &Instance {
+            &self.state.profile.profiles["Test"].instances.as_ref().unwrap()["Default"]
+        }
+
+        fn apply(&mut self, mods: Vec<ModpackEntry>) -> Result<ModpackReport, ErrorContext> {
+            let modpack = Modpack { mods, ..Default::default() };
+            ModListService::new(&mut self.session, &mut self.state).try_apply_modpack(&modpack)
+        }
+    }
+
+    #[test]
+    fn applying_a_modpack_reconciles_the_instance() {
+        let mut setup = Setup::new(&["A", "B", "C"]);
+        let mut c = setup.entry("C");
+        c.exclude = vec![PathBuf::from("Data/Shared.vdf")];
+        let mut d = setup.entry("D");
+        d.enabled = false;
+
+        let report = setup.apply(vec![c, setup.entry("A"), d]).unwrap();
+        assert_eq!(report.installed, ["D"]);
+        assert_eq!(report.removed, ["B"]);
+        assert_eq!(report.moved, ["C"]);
+        assert_eq!(report.disabled, ["D"]);
+        assert_eq!(report.toggled_files, 1);
+
+        let instance = setup.instance();
+        let names = instance.mods.iter().map(|mod_info| mod_info.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["C", "A", "D"]);
+        assert!(!setup.root.path().join("mods/gothic-organizer-rs/Test/mods/Default/B").exists());
+
+        // C is overridden by A, its excluded file only shows up among the conflicts.
+        let shared = setup.root.path().join("game/Data/Shared.vdf");
+        assert_eq!(instance.files[&shared].parent_name, "A");
+        let c_shared = instance.conflicts.entries[&shared].values().find(|m| m.parent_name == "C").unwrap();
+        assert!(!c_shared.enabled);
+
+        let report = setup.apply(vec![setup.entry("C"), setup.entry("A"), setup.entry("D")]).unwrap();
+        assert_eq!((report.enabled.as_slice(), report.toggled_files), (["D".to_owned()].as_slice(), 1));
+    }
+
+    #[test]
+    fn a_failed_modpack_leaves_the_instance_as_it_was() {
+        let mut setup = Setup::new(&["A", "B"]);
+        let before = setup.instance().clone();
+
+        assert!(setup.apply(vec![setup.entry("A"), setup.entry("Broken.zip")]).is_err());
+
+        assert_eq!(setup.instance(), &before);
+        let storage = setup.root.path().join("mods/gothic-organizer-rs/Test/mods/Default");
+        assert!(storage.join("B/Data/B.vdf").exists());
+        assert!(!storage.join("Broken").exists());
+    }
+}
@@ -0,0 +1,260 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::core::profile::Instance;
+use crate::core::profile::Lookup;
+use crate::core::profile::parent_dirs;
+
+/// Mods replacing at least this many base game files are always reported as heavy overriders.
+pub const HEAVY_VANILLA_OVERRIDE_COUNT: usize = 100;
+
+/// Mods replacing at least [`HEAVY_VANILLA_OVERRIDE_MIN_FILES`] base game files are reported as
+/// heavy overriders once this share of their own files replaces base game files.
+pub const HEAVY_VANILLA_OVERRIDE_RATIO: f32 = 0.5;
+pub const HEAVY_VANILLA_OVERRIDE_MIN_FILES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModOverlap {
+    pub first: String,
+    pub second: String,
+    pub shared_files: usize,
+    pub first_wins: usize,
+    pub second_wins: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModOverlapSummary {
+    pub name: String,
+    pub priority: usize,
+    pub total_files: usize,
+    pub overridden_files: usize,
+    pub vanilla_overrides: usize,
+}
+
+impl ModOverlapSummary {
+    /// A mod is effectively dead when every one of its files is replaced by a higher priority mod.
+    pub fn is_fully_overridden(&self) -> bool {
+        self.total_files > 0 && self.overridden_files == self.total_files
+    }
+
+    pub fn is_heavy_vanilla_overrider(&self) -> bool {
+        if self.vanilla_overrides >= HEAVY_VANILLA_OVERRIDE_COUNT {
+            return true;
+        }
+
+        self.vanilla_overrides >= HEAVY_VANILLA_OVERRIDE_MIN_FILES
+            && self.vanilla_overrides as f32 >= self.total_files as f32 * HEAVY_VANILLA_OVERRIDE_RATIO
+    }
+}
+
+/// Pairwise overlap statistics of the enabled mods of an instance, built from its conflicts.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct OverlapReport {
+    pub instance_name: String,
+    pub pairs: Vec<ModOverlap>,
+    pub mods: Vec<ModOverlapSummary>,
+}
+
+impl OverlapReport {
+    pub fn new(instance: &Instance, profile_path: &Path) -> Self {
+        let is_mod = |name: &str| instance.mods.iter().any(|m| m.enabled && m.name == name);
+        let mut pairs: Lookup<(String, String), ModOverlap> = Lookup::default();
+        let mut vanilla_overrides: Lookup<String, usize> = Lookup::default();
+
+        // Directories shared by several mods are not overlaps, only the files in them are.
+        let target_dirs = parent_dirs(instance.files.keys());
+        for (_, providers) in
+            instance.conflicts.iter().filter(|(target, _)| !target_dirs.contains(target.as_path()))
+        {
+            let mut providers = providers
+                .iter()
+                .map(|(priority, metadata)| (*priority, metadata.parent_name.as_str()))
+                .collect::<Vec<_>>();
+
+            if providers.len() < 2 {
+                continue;
+            }
+
+            providers.sort_unstable_by_key(|(priority, _)| *priority);
+            let (_, winner) = providers[providers.len() - 1];
+
+            if is_mod(winner) && providers.iter().any(|(_, name)| !is_mod(name)) {
+                *vanilla_overrides.entry(winner.to_owned()).or_default() += 1;
+            }
+
+            let mod_providers = providers.iter().filter(|(_, name)| is_mod(name)).collect::<Vec<_>>();
+            for (index, (first_priority, first)) in mod_providers.iter().enumerate() {
+                for (second_priority, second) in mod_providers.iter().skip(index + 1) {
+                    let (first, second, first_wins) = match first.cmp(second) {
+                        std::cmp::Ordering::Greater => (*second, *first, second_priority > first_priority),
+                        _ => (*first, *second, first_priority > second_priority),
+                    };
+
+                    let overlap =
+                        pairs.entry((first.to_owned(), second.to_owned())).or_insert_with(|| ModOverlap {
+                            first: first.to_owned(),
+                            second: second.to_owned(),
+                            shared_files: 0,
+                            first_wins: 0,
+                            second_wins: 0,
+                        });
+
+                    overlap.shared_files += 1;
+                    if first_wins {
+                        overlap.first_wins += 1;
+                    } else {
+                        overlap.second_wins += 1;
+                    }
+                }
+            }
+        }
+
+        let mut mods = instance
+            .mods
+            .iter()
+            .filter(|m| m.enabled)
+            .map(|mod_info| {
+                let mod_dirs = parent_dirs(mod_info.files.keys());
+                let mod_files = mod_info
+                    .files
+                    .keys()
+                    .filter(|path| !mod_dirs.contains(path.as_path()))
+                    .filter_map(|path| path.strip_prefix(&mod_info.path).ok())
+                    .map(|relative_path| profile_path.join(relative_path))
+                    .collect::<Vec<_>>();
+
+                let overridden_files = mod_files
+                    .iter()
+                    .filter(|dst_path| {
+                        instance
+                            .files
+                            .get(*dst_path)
+                            .is_some_and(|active| active.parent_name != mod_info.name)
+                    })
+                    .count();
+
+                ModOverlapSummary {
+                    name: mod_info.name.clone(),
+                    priority: instance.load_order.get(&mod_info.name).copied().unwrap_or_default(),
+                    total_files: mod_files.len(),
+                    overridden_files,
+                    vanilla_overrides: vanilla_overrides.get(&mod_info.name).copied().unwrap_or_default(),
+                }
+            })
+            .collect::<Vec<_>>();
+        mods.sort_unstable_by_key(|m| m.priority);
+
+        let mut pairs = pairs.into_values().collect::<Vec<_>>();
+        pairs.sort_unstable_by(|a, b| {
+            b.shared_files
+                .cmp(&a.shared_files)
+                .then_with(|| (&a.first, &a.second).cmp(&(&b.first, &b.second)))
+        });
+
+        Self { instance_name: instance.name.clone(), pairs, mods }
+    }
+
+    pub fn fully_overridden_mods(&self) -> impl Iterator<Item = &ModOverlapSummary> {
+        self.mods.iter().filter(|m| m.is_fully_overridden())
+    }
+
+    pub fn heavy_vanilla_overriders(&self) -> impl Iterator<Item = &ModOverlapSummary> {
+        self.mods.iter().filter(|m| m.is_heavy_vanilla_overrider())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::core::profile::FileMetadata;
+    use crate::core::profile::ModInfo;
+    use crate::core::services::mods::ModService;
+
+    fn mod_info(name: &str, files: &[&str]) -> ModInfo {
+        let path = PathBuf::from("/mods").join(name);
+        let files = files
+            .iter()
+            .map(|file| {
+                let source = path.join(file);
+                (source.clone(), FileMetadata::new(true, &source, Path::new(""), name))
+            })
+            .collect();
+        ModInfo::new(true, name, &path, files)
+    }
+
+    /// Instance with the given mods installed on top of the base files, in order of priority.
+    fn instance(base_files: &[&str], mods: Vec<ModInfo>) -> Instance {
+        let game_dir = Path::new("/game");
+        let files = base_files
+            .iter()
+            .map(|file| {
+                let path = game_dir.join(file);
+                (path.clone(), FileMetadata::new(true, &path, &path, "Default"))
+            })
+            .collect();
+
+        let mut instance = Instance::new("Default", files, Vec::new());
+        instance.load_order.insert("Default".to_owned(), 0);
+        for (priority, mod_info) in mods.into_iter().enumerate() {
+            instance.load_order.insert(mod_info.name.clone(), priority + 1);
+            let (mut files, mut conflicts) = (instance.files, instance.conflicts);
+            ModService::apply_mod_files(
+                &mut files,
+                &mut conflicts,
+                &mut instance.load_order,
+                game_dir,
+                &mod_info,
+            );
+            (instance.files, instance.conflicts) = (files, conflicts);
+            instance.mods.push(mod_info);
+        }
+
+        instance
+    }
+
+    #[test]
+    fn counts_shared_files_but_not_shared_directories() {
+        let instance = instance(
+            &["Data", "Data/Base.vdf"],
+            vec![
+                mod_info("First", &["Data", "Data/Shared.vdf", "Data/Other.vdf", "Data/Textures"]),
+                mod_info("Second", &["Data", "Data/Shared.vdf", "Data/Other.vdf", "Data/Base.vdf"]),
+            ],
+        );
+        let report = OverlapReport::new(&instance, Path::new("/game"));
+
+        assert_eq!(
+            report.pairs,
+            vec![ModOverlap {
+                first: "First".to_owned(),
+                second: "Second".to_owned(),
+                shared_files: 2,
+                first_wins: 0,
+                second_wins: 2,
+            }]
+        );
+
+        let first = &report.mods[0];
+        assert_eq!((first.name.as_str(), first.total_files, first.overridden_files), ("First", 3, 2));
+        assert!(!first.is_fully_overridden());
+
+        let second = &report.mods[1];
+        assert_eq!((second.total_files, second.overridden_files, second.vanilla_overrides), (3, 0, 1));
+    }
+
+    #[test]
+    fn reports_mods_whose_files_are_all_overridden() {
+        let instance = instance(
+            &[],
+            vec![mod_info("Old", &["Data", "Data/Patch.vdf"]), mod_info("New", &["Data", "Data/Patch.vdf"])],
+        );
+        let report = OverlapReport::new(&instance, Path::new("/game"));
+
+        let fully_overridden = report.fully_overridden_mods().map(|m| m.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(fully_overridden, ["Old"]);
+        assert_eq!(report.heavy_vanilla_overriders().count(), 0);
+    }
+}
@@ -3,6 +3,10 @@
 use std::path::Path;
 use std::path::PathBuf;
 
+use crate::core::bundle;
+use crate::core::constants::DefaultProfile;
+use crate::core::fingerprint::GameFingerprint;
+
 pub type Lookup<K, V> = hashbrown::HashMap<K, V, ahash::RandomState>;
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -10,11 +14,67 @@ pub struct Profile {
     pub name: String,
     pub path: PathBuf,
     pub instances: Option<Lookup<String, Instance>>,
+    #[serde(default)]
+    pub game_type: DefaultProfile,
+    #[serde(default)]
+    pub is_custom: bool,
+    #[serde(default)]
+    pub executable: Option<PathBuf>,
+    #[serde(default)]
+    pub layout: DirectoryLayout,
+    #[serde(default)]
+    pub fingerprint: Option<GameFingerprint>,
 }
 
 impl Profile {
     pub fn new(name: &str, path: &Path) -> Self {
-        Self { name: name.to_owned(), path: path.to_owned(), instances: None }
+        Self { name: name.to_owned(), path: path.to_owned(), ..Default::default() }
+    }
+
+    pub fn with_game_type(mut self, game_type: DefaultProfile) -> Self {
+        self.game_type = game_type;
+        self
+    }
+
+    pub fn with_custom(mut self, is_custom: bool) -> Self {
+        self.is_custom = is_custom;
+        self
+    }
+
+    /// Path of the game executable relative to the game directory, falling back to the default
+    /// executable of the profile's game type.
+    pub fn executable(&self) -> PathBuf {
+        self.executable.clone().unwrap_or_else(|| self.game_type.default_executable())
+    }
+
+    /// Rewrites every path of this profile's instances that lies below `from` to lie below `to`.
+    pub fn rebase_paths(&mut self, from: &Path, to: &Path) {
+        if let Some(instances) = self.instances.as_mut() {
+            instances.values_mut().for_each(|instance| instance.rebase_paths(from, to));
+        }
+    }
+
+    /// Applies `f` to every path of the profile's instances. The game directory itself is left
+    /// as is.
+    pub fn map_paths(&mut self, f: &dyn Fn(&Path) -> PathBuf) {
+        if let Some(instances) = self.instances.as_mut() {
+            instances.values_mut().for_each(|instance| instance.map_paths(f));
+        }
+    }
+
+    /// Returns a copy of the profile as it is saved: paths below the game directory or the mod
+    /// storage root are stored relative to them, so that either can be moved.
+    pub fn to_portable(&self, mod_storage_root: &Path) -> Self {
+        let mut profile = self.clone();
+        profile.map_paths(&|path| bundle::to_portable_path(path, &self.path, mod_storage_root));
+        profile
+    }
+
+    /// Resolves the relative paths of a loaded profile. Absolute paths written by older versions
+    /// are kept as they are.
+    pub fn resolve_portable(&mut self, mod_storage_root: &Path) {
+        let game_dir = self.path.clone();
+        self.map_paths(&|path| bundle::from_portable_path(path, &game_dir, mod_storage_root));
     }
 
     pub fn with_name(mut self, name: &str) -> Self {
@@ -100,6 +160,103 @@ impl Instance {
         self.load_order = load_order;
         self
     }
+
+    /// Renames the instance together with the base game entries it owns in the load order, files
+    /// and conflicts.
+    pub fn rename(&mut self, new_name: &str) {
+        let old_name = std::mem::replace(&mut self.name, new_name.to_owned());
+        if let Some(priority) = self.load_order.remove(&old_name) {
+            self.load_order.insert(new_name.to_owned(), priority);
+        }
+
+        self.files
+            .values_mut()
+            .chain(self.conflicts.entries.values_mut().flat_map(|providers| providers.values_mut()))
+            .filter(|metadata| metadata.parent_name == old_name)
+            .for_each(|metadata| metadata.parent_name = new_name.to_owned());
+    }
+
+    /// Rewrites every mod, file and conflict path that lies below `from` to lie below `to`.
+    pub fn rebase_paths(&mut self, from: &Path, to: &Path) {
+        self.map_paths(&|path| rebase_path(path, from, to));
+    }
+
+    /// Applies `f` to every mod, file and conflict path of the instance.
+    pub fn map_paths(&mut self, f: &dyn Fn(&Path) -> PathBuf) {
+        self.mods.iter_mut().for_each(|mod_info| mod_info.map_paths(f));
+        self.files = std::mem::take(&mut self.files)
+            .into_iter()
+            .map(|(path, mut metadata)| {
+                metadata.map_paths(f);
+                (f(&path), metadata)
+            })
+            .collect();
+
+        self.conflicts.entries = std::mem::take(&mut self.conflicts.entries)
+            .into_iter()
+            .map(|(path, mut providers)| {
+                providers.values_mut().for_each(|metadata| metadata.map_paths(f));
+                (f(&path), providers)
+            })
+            .collect();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct DirectoryLayout {
+    pub system_dir: PathBuf,
+    pub data_dir: PathBuf,
+    pub work_dir: PathBuf,
+}
+
+impl Default for DirectoryLayout {
+    fn default() -> Self {
+        Self {
+            system_dir: PathBuf::from("System"),
+            data_dir: PathBuf::from("Data"),
+            work_dir: PathBuf::from("_work"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutDir {
+    System,
+    Data,
+    Work,
+}
+
+impl DirectoryLayout {
+    pub fn get(&self, dir: LayoutDir) -> &Path {
+        match dir {
+            LayoutDir::System => &self.system_dir,
+            LayoutDir::Data => &self.data_dir,
+            LayoutDir::Work => &self.work_dir,
+        }
+    }
+
+    pub fn set(&mut self, dir: LayoutDir, path: &Path) {
+        match dir {
+            LayoutDir::System => self.system_dir = path.to_owned(),
+            LayoutDir::Data => self.data_dir = path.to_owned(),
+            LayoutDir::Work => self.work_dir = path.to_owned(),
+        }
+    }
+}
+
+/// Entries of a walked directory tree that other entries lie in, which tells its directories apart
+/// from its files without touching the filesystem. Empty directories pass for files.
+pub fn parent_dirs<'a>(paths: impl IntoIterator<Item = &'a PathBuf>) -> hashbrown::HashSet<&'a Path> {
+    paths.into_iter().filter_map(|path| path.parent()).collect()
+}
+
+/// Replaces the `from` prefix of `path` with `to`, returning the path unchanged if it does not
+/// start with `from`.
+pub fn rebase_path(path: &Path, from: &Path, to: &Path) -> PathBuf {
+    match path.strip_prefix(from) {
+        Ok(relative_path) => to.join(relative_path),
+        Err(_) => path.to_owned(),
+    }
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -138,11 +295,16 @@ pub struct ModInfo {
     pub name: String,
     pub path: PathBuf,
     pub files: Lookup<PathBuf, FileMetadata>,
+    #[serde(default)]
+    pub version: Option<String>,
+    /// SHA-256 of the archive or directory the mod was installed from.
+    #[serde(default)]
+    pub source_hash: Option<String>,
 }
 
 impl ModInfo {
     pub fn new(enabled: bool, name: &str, path: &Path, files: Lookup<PathBuf, FileMetadata>) -> Self {
-        Self { enabled, name: name.to_owned(), path: path.to_owned(), files }
+        Self { enabled, name: name.to_owned(), path: path.to_owned(), files, ..Default::default() }
     }
 
     pub fn with_enabled(mut self, enabled: bool) -> Self {
@@ -164,6 +326,31 @@ impl ModInfo {
         self.files = files;
         self
     }
+
+    pub fn with_version(mut self, version: Option<String>) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn with_source_hash(mut self, source_hash: Option<String>) -> Self {
+        self.source_hash = source_hash;
+        self
+    }
+
+    pub fn rebase_paths(&mut self, from: &Path, to: &Path) {
+        self.map_paths(&|path| rebase_path(path, from, to));
+    }
+
+    pub fn map_paths(&mut self, f: &dyn Fn(&Path) -> PathBuf) {
+        self.path = f(&self.path);
+        self.files = std::mem::take(&mut self.files)
+            .into_iter()
+            .map(|(path, mut metadata)| {
+                metadata.map_paths(f);
+                (f(&path), metadata)
+            })
+            .collect();
+    }
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
@@ -203,4 +390,94 @@ impl FileMetadata {
         self.parent_name = parent_name.to_owned();
         self
     }
+
+    pub fn rebase_paths(&mut self, from: &Path, to: &Path) {
+        self.map_paths(&|path| rebase_path(path, from, to));
+    }
+
+    pub fn map_paths(&mut self, f: &dyn Fn(&Path) -> PathBuf) {
+        self.source_path = f(&self.source_path);
+        self.target_path = f(&self.target_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(game_dir: &Path, storage_root: &Path) -> Profile {
+        let mod_dir = storage_root.join("Default/Patch");
+        let mod_file = FileMetadata::new(
+            true,
+            &mod_dir.join("Data/Patch.vdf"),
+            &game_dir.join("Data/Patch.vdf"),
+            "Patch",
+        );
+        let mod_info = ModInfo::new(
+            true,
+            "Patch",
+            &mod_dir,
+            Lookup::from_iter([(mod_file.source_path.clone(), mod_file.clone())]),
+        );
+        let game_file = FileMetadata::new(
+            false,
+            &game_dir.join("System/Gothic.ini"),
+            &game_dir.join("System/Gothic.ini"),
+            "",
+        );
+        let outside_file =
+            FileMetadata::new(true, Path::new("/elsewhere/Extra.vdf"), Path::new("/elsewhere/Extra.vdf"), "");
+
+        let mut instance = Instance::new(
+            "Default",
+            Lookup::from_iter([
+                (game_file.target_path.clone(), game_file),
+                (outside_file.target_path.clone(), outside_file),
+            ]),
+            vec![mod_info],
+        );
+        instance.conflicts = Conflicts::new(Lookup::from_iter([(
+            mod_file.target_path.clone(),
+            Lookup::from_iter([(0, mod_file)]),
+        )]));
+
+        Profile::new("Test", game_dir)
+            .with_instances(Some(Lookup::from_iter([("Default".to_owned(), instance)])))
+    }
+
+    #[test]
+    fn portable_paths_are_relative_and_resolve_back() {
+        let (game_dir, storage_root) = (Path::new("/games/Gothic"), Path::new("/data/Test/mods"));
+        let profile = profile(game_dir, storage_root);
+
+        let portable = profile.to_portable(storage_root);
+        let instance = &portable.instances.as_ref().unwrap()["Default"];
+        assert_eq!(instance.mods[0].path, Path::new("$MODS/Default/Patch"));
+        assert!(instance.files.contains_key(Path::new("$GAME/System/Gothic.ini")));
+        assert!(instance.files.contains_key(Path::new("/elsewhere/Extra.vdf")));
+        assert!(instance.conflicts.entries.contains_key(Path::new("$GAME/Data/Patch.vdf")));
+        assert_eq!(portable.path, game_dir);
+
+        let mut resolved = portable.clone();
+        resolved.resolve_portable(storage_root);
+        assert_eq!(resolved, profile);
+
+        let mut moved = portable;
+        moved.path = PathBuf::from("/games/Moved");
+        moved.resolve_portable(Path::new("/data/Moved/mods"));
+        let instance = &moved.instances.as_ref().unwrap()["Default"];
+        assert_eq!(instance.mods[0].path, Path::new("/data/Moved/mods/Default/Patch"));
+        assert!(instance.files.contains_key(Path::new("/games/Moved/System/Gothic.ini")));
+    }
+
+    #[test]
+    fn absolute_paths_of_older_versions_resolve_to_themselves() {
+        let (game_dir, storage_root) = (Path::new("/games/Gothic"), Path::new("/data/Test/mods"));
+        let profile = profile(game_dir, storage_root);
+
+        let mut resolved = profile.clone();
+        resolved.resolve_portable(storage_root);
+
+        assert_eq!(resolved, profile);
+    }
 }
@@ -0,0 +1,333 @@
+//! Versioning of the files the application keeps in the user data directory.
+//!
+//! Every file is written with a `schema_version` field. Files written before versioning was
+//! introduced have no such field and are treated as version 0. When a file is read, the
+//! migrations between its version and the current one are applied in order before it is
+//! deserialized.
+//!
+//! Files are replaced atomically and the previous versions are kept as numbered backups, which
+//! are offered as a replacement when the file itself cannot be loaded.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::app::session;
+use crate::core::basefiles;
+use crate::core::deploy;
+use crate::core::profile;
+use crate::core::utils;
+use crate::error;
+use crate::error::ErrorContext;
+
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+pub const PROFILE_SCHEMA_VERSION: u32 = 2;
+pub const SESSION_SCHEMA_VERSION: u32 = 2;
+pub const DEPLOY_SCHEMA_VERSION: u32 = 1;
+pub const ERROR_HISTORY_SCHEMA_VERSION: u32 = 1;
+pub const BASE_FILES_SCHEMA_VERSION: u32 = 1;
+/// Number of previous versions kept next to each file.
+pub const BACKUP_COUNT: usize = 3;
+/// Inserted between the file name and the timestamp of copies of files that could not be loaded.
+pub const UNREADABLE_SUFFIX: &str = ".unreadable-";
+
+/// Upgrades a document by one version. The migration at index `n` turns version `n` into `n + 1`.
+pub type Migration = fn(&mut serde_json::Value) -> Result<(), String>;
+
+pub const PROFILE_MIGRATIONS: [Migration; PROFILE_SCHEMA_VERSION as usize] = [
+    |value| fill_missing_fields(value, profile::Profile::default()),
+    // Version 2 stores paths relative to the game directory or mod storage root. The storage root
+    // is a session setting this migration cannot see, so the absolute paths of version 1 are left
+    // as they are: `Profile::resolve_portable` keeps absolute paths and `Profile::to_portable`
+    // makes them relative on the next save.
+    |_| Ok(()),
+];
+
+pub const SESSION_MIGRATIONS: [Migration; SESSION_SCHEMA_VERSION as usize] = [
+    |value| fill_missing_fields(value, session::ApplicationSession::default()),
+    // Version 2 adds the log settings.
+    |value| fill_missing_fields(value, session::ApplicationSession::default()),
+];
+
+pub const DEPLOY_MIGRATIONS: [Migration; DEPLOY_SCHEMA_VERSION as usize] =
+    [|value| fill_missing_fields(value, deploy::DeployManifest::default())];
+
+pub const ERROR_HISTORY_MIGRATIONS: [Migration; ERROR_HISTORY_SCHEMA_VERSION as usize] =
+    [|value| fill_missing_fields(value, error::ErrorHistory::default())];
+
+pub const BASE_FILES_MIGRATIONS: [Migration; BASE_FILES_SCHEMA_VERSION as usize] =
+    [|value| fill_missing_fields(value, basefiles::BaseFileIndex::default())];
+
+/// A kind of versioned file together with its current version and migration chain.
+#[derive(Debug, Clone, Copy)]
+pub struct Schema {
+    pub name: &'static str,
+    pub version: u32,
+    pub migrations: &'static [Migration],
+}
+
+pub const PROFILE_SCHEMA: Schema =
+    Schema { name: "profile", version: PROFILE_SCHEMA_VERSION, migrations: &PROFILE_MIGRATIONS };
+
+pub const SESSION_SCHEMA: Schema =
+    Schema { name: "session", version: SESSION_SCHEMA_VERSION, migrations: &SESSION_MIGRATIONS };
+
+pub const DEPLOY_SCHEMA: Schema =
+    Schema { name: "deploy manifest", version: DEPLOY_SCHEMA_VERSION, migrations: &DEPLOY_MIGRATIONS };
+
+pub const ERROR_HISTORY_SCHEMA: Schema = Schema {
+    name: "error history",
+    version: ERROR_HISTORY_SCHEMA_VERSION,
+    migrations: &ERROR_HISTORY_MIGRATIONS,
+};
+
+pub const BASE_FILES_SCHEMA: Schema = Schema {
+    name: "base file index",
+    version: BASE_FILES_SCHEMA_VERSION,
+    migrations: &BASE_FILES_MIGRATIONS,
+};
+
+impl Schema {
+    pub fn serialize<T: Serialize>(&self, value: &T) -> Result<String, serde_json::Error> {
+        let mut json = serde_json::to_value(value)?;
+        if let Some(object) = json.as_object_mut() {
+            object.insert(SCHEMA_VERSION_KEY.to_owned(), self.version.into());
+        }
+
+        serde_json::to_string_pretty(&json)
+    }
+
+    /// Writes a file of this schema atomically, rotating its backups.
+    pub fn write<T: Serialize>(&self, path: &Path, value: &T) -> std::io::Result<()> {
+        let contents = self.serialize(value).map_err(std::io::Error::other)?;
+        utils::write_atomic(path, contents.as_bytes(), BACKUP_COUNT)
+    }
+
+    /// Parses a document of this schema, migrating it to the current version first.
+    pub fn parse<T: DeserializeOwned>(&self, contents: &str) -> Result<T, String> {
+        let mut json: serde_json::Value = serde_json::from_str(contents).map_err(|err| err.to_string())?;
+        let object = json.as_object_mut().ok_or("expected a JSON object")?;
+        let version = match object.remove(SCHEMA_VERSION_KEY) {
+            Some(version) => version
+                .as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or(format!("invalid {SCHEMA_VERSION_KEY}: {version}"))?,
+            None => 0,
+        };
+
+        if version > self.version {
+            return Err(format!(
+                "{} schema version {version} is newer than the supported version {}",
+                self.name, self.version
+            ));
+        }
+
+        for (from, migration) in self.migrations.iter().enumerate().skip(version as usize) {
+            tracing::info!("Migrating {} from schema version {from} to {}", self.name, from + 1);
+            migration(&mut json).map_err(|err| format!("migration to version {} failed: {err}", from + 1))?;
+        }
+
+        serde_json::from_value(json).map_err(|err| err.to_string())
+    }
+
+    /// Reads and migrates a file of this schema. A missing file is not an error. A file that
+    /// cannot be used is copied aside first, so that it survives being overwritten on the next
+    /// save. If one of its backups is still valid, `confirm_recovery` decides whether the newest
+    /// of them is used instead.
+    pub fn read<T: DeserializeOwned>(
+        &self,
+        path: &Path,
+        confirm_recovery: impl FnOnce(&Path) -> bool,
+    ) -> Result<Option<T>, ErrorContext> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(ErrorContext::builder()
+                    .error(
+                        error::Error::file_system(format!("Could not read {}", self.name), "Load Data")
+                            .with_path(path)
+                            .with_source(&err),
+                    )
+                    .build());
+            }
+        };
+
+        match self.parse(&contents) {
+            Ok(value) => Ok(Some(value)),
+            Err(err) => {
+                let backup = keep_unreadable(path);
+                tracing::error!("Could not load {}: {err}", path.display());
+
+                if let Some((backup_path, value)) = self.newest_valid_backup(path)
+                    && confirm_recovery(&backup_path)
+                {
+                    tracing::warn!("Recovered {} from {}", path.display(), backup_path.display());
+                    return Ok(Some(value));
+                }
+
+                let suggested_action = match &backup {
+                    Some(backup) => format!(
+                        "The unreadable file was kept as {}. Fix it or restore one of its .bak copies",
+                        backup.display()
+                    ),
+                    None => "Back up the file before it is overwritten".to_owned(),
+                };
+
+                Err(ErrorContext::builder()
+                    .error(
+                        error::Error::file_system(
+                            format!("Could not load {}: {err}", self.name),
+                            "Load Data",
+                        )
+                        .with_kind(error::ErrorKind::InvalidData)
+                        .with_path(path),
+                    )
+                    .suggested_action(&suggested_action)
+                    .build())
+            }
+        }
+    }
+
+    /// Returns the newest backup of a file that can still be loaded.
+    pub fn newest_valid_backup<T: DeserializeOwned>(&self, path: &Path) -> Option<(PathBuf, T)> {
+        (1..=BACKUP_COUNT).map(|index| utils::backup_path(path, index)).find_map(|backup_path| {
+            let contents = std::fs::read_to_string(&backup_path).ok()?;
+            match self.parse(&contents) {
+                Ok(value) => Some((backup_path, value)),
+                Err(err) => {
+                    tracing::warn!("Backup {} is not usable either: {err}", backup_path.display());
+                    None
+                }
+            }
+        })
+    }
+}
+
+/// Copies a file that could not be loaded next to itself with a timestamped suffix. A file that
+/// keeps failing to load is only copied once, an earlier copy with the same contents is reused.
+pub fn keep_unreadable(path: &Path) -> Option<PathBuf> {
+    let prefix = format!("{}{UNREADABLE_SUFFIX}", path.file_name()?.to_string_lossy());
+    let contents = std::fs::read(path).ok()?;
+    let parent = path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+
+    let existing_copy = std::fs::read_dir(parent).ok()?.flatten().map(|entry| entry.path()).find(|copy| {
+        copy.file_name().is_some_and(|name| name.to_string_lossy().starts_with(&prefix))
+            && std::fs::read(copy).is_ok_and(|copy_contents| copy_contents == contents)
+    });
+    if existing_copy.is_some() {
+        return existing_copy;
+    }
+
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let backup_path = (1..)
+        .map(|n| match n {
+            1 => path.with_file_name(format!("{prefix}{timestamp}")),
+            n => path.with_file_name(format!("{prefix}{timestamp}-{n}")),
+        })
+        .find(|backup_path| !backup_path.exists())?;
+    match std::fs::write(&backup_path, contents) {
+        Ok(_) => Some(backup_path),
+        Err(err) => {
+            tracing::error!("Could not back up {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+/// Adds the fields of `defaults` the document does not have yet. Used by migrations of files
+/// written before a field was introduced.
+fn fill_missing_fields<T: Serialize>(value: &mut serde_json::Value, defaults: T) -> Result<(), String> {
+    let serde_json::Value::Object(defaults) =
+        serde_json::to_value(defaults).map_err(|err| err.to_string())?
+    else {
+        return Err("defaults are not a JSON object".to_owned());
+    };
+
+    let object = value.as_object_mut().ok_or("expected a JSON object")?;
+    defaults.into_iter().for_each(|(key, default)| {
+        object.entry(key).or_insert(default);
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::session::ApplicationSession;
+    use crate::core::testing::TempDir;
+
+    fn session(active_profile: &str) -> ApplicationSession {
+        ApplicationSession { active_profile: Some(active_profile.to_owned()), ..Default::default() }
+    }
+
+    fn unreadable_copies(root: &TempDir) -> Vec<PathBuf> {
+        std::fs::read_dir(root.path())
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.to_string_lossy().contains(UNREADABLE_SUFFIX))
+            .collect()
+    }
+
+    #[test]
+    fn written_files_read_back_with_the_current_version() {
+        let root = TempDir::new();
+        let path = root.path().join("session.json");
+
+        SESSION_SCHEMA.write(&path, &session("Gothic")).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json[SCHEMA_VERSION_KEY], SESSION_SCHEMA_VERSION);
+        let read: Option<ApplicationSession> = SESSION_SCHEMA.read(&path, |_| false).unwrap();
+        assert_eq!(read, Some(session("Gothic")));
+        assert_eq!(
+            SESSION_SCHEMA.read::<ApplicationSession>(&root.path().join("missing.json"), |_| false).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn unversioned_files_are_migrated() {
+        let session: ApplicationSession = SESSION_SCHEMA
+            .parse(r#"{ "active_profile": "Gothic", "error_notifications_enabled": false }"#)
+            .unwrap();
+
+        assert_eq!(session.active_profile.as_deref(), Some("Gothic"));
+        assert!(!session.error_notifications_enabled);
+        assert_eq!(session.log_settings, session::LogSettings::default());
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        let err = SESSION_SCHEMA.parse::<ApplicationSession>(r#"{ "schema_version": 99 }"#).unwrap_err();
+        assert!(err.contains("newer than the supported version"), "{err}");
+    }
+
+    #[test]
+    fn unreadable_files_are_kept_once_and_recovered_from_backups() {
+        let root = TempDir::new();
+        let path = root.path().join("session.json");
+        SESSION_SCHEMA.write(&path, &session("Gothic")).unwrap();
+        SESSION_SCHEMA.write(&path, &session("Gothic 2 Classic")).unwrap();
+        std::fs::write(&path, "{ broken").unwrap();
+
+        assert!(SESSION_SCHEMA.read::<ApplicationSession>(&path, |_| false).is_err());
+        assert!(SESSION_SCHEMA.read::<ApplicationSession>(&path, |_| false).is_err());
+        let copies = unreadable_copies(&root);
+        assert_eq!(copies.len(), 1);
+        assert_eq!(std::fs::read_to_string(&copies[0]).unwrap(), "{ broken");
+
+        let recovered = SESSION_SCHEMA
+            .read::<ApplicationSession>(&path, |backup| backup == utils::backup_path(&path, 1))
+            .unwrap();
+        assert_eq!(recovered, Some(session("Gothic")));
+
+        std::fs::write(&path, "{ broken differently").unwrap();
+        assert!(SESSION_SCHEMA.read::<ApplicationSession>(&path, |_| false).is_err());
+        assert_eq!(unreadable_copies(&root).len(), 2);
+    }
+}
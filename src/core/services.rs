@@ -1,5 +1,8 @@
 use std::process;
 
+pub mod conflicts;
+pub mod history;
+pub mod modlist;
 pub mod mods;
 pub mod profile;
 pub mod session;
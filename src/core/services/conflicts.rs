@@ -0,0 +1,276 @@
+use std::path::Path;
+
+use iced::Task;
+
+use crate::app::message;
+use crate::app::session;
+use crate::app::state;
+use crate::app::window::ApplicationWindow;
+use crate::core::compare;
+use crate::core::diff;
+use crate::core::overlaps;
+use crate::core::profile;
+use crate::core::services;
+use crate::error;
+use crate::error::ErrorContext;
+
+pub struct ConflictsService<'a> {
+    session: &'a mut session::ApplicationSession,
+    state: &'a mut state::ApplicationState,
+}
+
+impl<'a> ConflictsService<'a> {
+    pub fn new(session: &'a mut session::ApplicationSession, state: &'a mut state::ApplicationState) -> Self {
+        Self { session, state }
+    }
+
+    /// Collects the providers of the given target path and compares two of them, opening the diff
+    /// window if it is not open yet.
+    pub fn open_diff(&mut self, target_path: &Path) -> Task<message::Message> {
+        if let Err(err) = self.try_open_diff(target_path) {
+            return Task::done(message::ErrorMessage::Handle(err).into());
+        }
+
+        self.ensure_window_open(ApplicationWindow::Diff)
+    }
+
+    /// Builds the mod overlap report for the active instance and shows it in the overlaps window.
+    pub fn analyze_overlaps(&mut self) -> Task<message::Message> {
+        if let Err(err) = self.try_analyze_overlaps() {
+            return Task::done(message::ErrorMessage::Handle(err).into());
+        }
+
+        self.ensure_window_open(ApplicationWindow::Overlaps)
+    }
+
+    /// Opens the instance comparison window, preselecting the active instance on the left side.
+    pub fn open_comparison(&mut self) -> Task<message::Message> {
+        // Pending file toggles of the active instance have to be part of the comparison.
+        let commit = match self.session.active_instance.is_some() {
+            true => services::profile::ProfileService::new(self.session, self.state).commit_session_files(),
+            false => Task::none(),
+        };
+
+        let instances = compare::InstanceRef::all(&self.state.profile.profiles);
+        let compare_state = &mut self.state.compare;
+        compare_state.left.take_if(|left| !instances.contains(left));
+        compare_state.right.take_if(|right| !instances.contains(right));
+
+        if compare_state.left.is_none()
+            && let (Some(profile), Some(instance)) =
+                (&self.session.active_profile, &self.session.active_instance)
+        {
+            compare_state.left =
+                Some(compare::InstanceRef { profile: profile.clone(), instance: instance.clone() });
+        }
+
+        compare_state.choices = iced::widget::combo_box::State::new(instances);
+        commit.chain(self.refresh_comparison()).chain(self.ensure_window_open(ApplicationWindow::Compare))
+    }
+
+    pub fn set_compared_instances(
+        &mut self,
+        left: Option<compare::InstanceRef>,
+        right: Option<compare::InstanceRef>,
+    ) -> Task<message::Message> {
+        if let Some(left) = left {
+            self.state.compare.left = Some(left);
+        }
+
+        if let Some(right) = right {
+            self.state.compare.right = Some(right);
+        }
+
+        self.refresh_comparison()
+    }
+
+    pub fn set_diff_providers(
+        &mut self,
+        left: Option<state::DiffProvider>,
+        right: Option<state::DiffProvider>,
+    ) -> Task<message::Message> {
+        if let Some(left) = left {
+            self.state.diff.left = Some(left);
+        }
+
+        if let Some(right) = right {
+            self.state.diff.right = Some(right);
+        }
+
+        self.refresh_diff()
+    }
+
+    pub fn set_diff_encoding(&mut self, encoding: diff::TextEncoding) -> Task<message::Message> {
+        tracing::info!("Setting diff encoding to {encoding}");
+        self.state.diff.encoding = encoding;
+        self.refresh_diff()
+    }
+
+    pub fn swap_diff_sides(&mut self) -> Task<message::Message> {
+        let diff_state = &mut self.state.diff;
+        std::mem::swap(&mut diff_state.left, &mut diff_state.right);
+        self.refresh_diff()
+    }
+
+    fn ensure_window_open(&self, window: ApplicationWindow) -> Task<message::Message> {
+        if self.state.ui.is_window_open(window) {
+            Task::none()
+        } else {
+            Task::done(message::WindowMessage::Open(window).into())
+        }
+    }
+
+    fn refresh_diff(&mut self) -> Task<message::Message> {
+        match self.try_refresh_diff() {
+            Ok(()) => Task::none(),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    fn refresh_comparison(&mut self) -> Task<message::Message> {
+        match self.try_refresh_comparison() {
+            Ok(()) => Task::none(),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    fn try_refresh_comparison(&mut self) -> Result<(), ErrorContext> {
+        self.state.compare.result = None;
+        let (Some(left), Some(right)) = (&self.state.compare.left, &self.state.compare.right) else {
+            return Ok(());
+        };
+
+        let profiles = &self.state.profile.profiles;
+        let (Some(left_instance), Some(right_instance)) = (left.resolve(profiles), right.resolve(profiles))
+        else {
+            return Err(ErrorContext::builder()
+                .error(error::Error::conflicts_service("Compared instance not found", "Compare Instances"))
+                .suggested_action("Select both instances again")
+                .build());
+        };
+
+        let comparison = compare::InstanceComparison::new(left_instance, right_instance);
+        tracing::info!(
+            "Compared {left} with {right}: {} mods only left, {} only right, {} different winners",
+            comparison.only_left.len(),
+            comparison.only_right.len(),
+            comparison.winners.len()
+        );
+
+        self.state.compare.result = Some(comparison);
+        Ok(())
+    }
+
+    fn try_analyze_overlaps(&mut self) -> Result<(), ErrorContext> {
+        let (active_profile, active_instance) = self.active_context("Analyze Overlaps")?;
+        let report = overlaps::OverlapReport::new(active_instance, &active_profile.path);
+
+        tracing::info!(
+            "Analyzed {} mod pairs in instance \"{}\", {} fully overridden",
+            report.pairs.len(),
+            report.instance_name,
+            report.fully_overridden_mods().count()
+        );
+
+        self.state.overlaps.report = Some(report);
+        Ok(())
+    }
+
+    fn try_open_diff(&mut self, target_path: &Path) -> Result<(), ErrorContext> {
+        let (_, active_instance) = self.active_context("Open Diff")?;
+
+        let Some(conflict_list) = active_instance.conflicts.entries.get(target_path) else {
+            return Err(ErrorContext::builder()
+                .error(error::Error::conflicts_service(
+                    format!("No conflict recorded for {}", target_path.display()),
+                    "Open Diff",
+                ))
+                .suggested_action("Reload the mods and try again")
+                .build());
+        };
+
+        let mut providers = conflict_list
+            .iter()
+            .map(|(priority, metadata)| state::DiffProvider {
+                priority: *priority,
+                name: metadata.parent_name.clone(),
+                source_path: metadata.source_path.clone(),
+            })
+            .collect::<Vec<_>>();
+        providers.sort_unstable_by_key(|p| std::cmp::Reverse(p.priority));
+
+        // Compare the selected mod against the file that currently wins, or against the runner-up
+        // if the selected mod is the winner itself.
+        let selected_mod_name = self
+            .session
+            .mod_selected
+            .and_then(|index| active_instance.mods.get(index))
+            .map(|mod_info| mod_info.name.clone());
+
+        let winner = providers.first().cloned();
+        let selected = providers.iter().find(|p| Some(&p.name) == selected_mod_name.as_ref()).cloned();
+        let (left, right) = match selected {
+            Some(selected) if Some(&selected) == winner.as_ref() => (providers.get(1).cloned(), winner),
+            Some(selected) => (Some(selected), winner),
+            None => (providers.get(1).cloned(), winner),
+        };
+
+        tracing::info!("Comparing providers of {}", target_path.display());
+        self.state.diff.target_path = Some(target_path.to_path_buf());
+        self.state.diff.provider_choices = iced::widget::combo_box::State::new(providers.clone());
+        self.state.diff.providers = providers;
+        self.state.diff.left = left;
+        self.state.diff.right = right;
+
+        self.try_refresh_diff()
+    }
+
+    fn try_refresh_diff(&mut self) -> Result<(), ErrorContext> {
+        let diff_state = &mut self.state.diff;
+        diff_state.result = None;
+
+        let (Some(left), Some(right)) = (diff_state.left.as_ref(), diff_state.right.as_ref()) else {
+            tracing::warn!("Not enough providers selected to compare");
+            return Ok(());
+        };
+
+        diff_state.result =
+            Some(diff::diff_files(&left.source_path, &right.source_path, diff_state.encoding)?);
+        Ok(())
+    }
+
+    fn active_context(
+        &self,
+        operation: &str,
+    ) -> Result<(&profile::Profile, &profile::Instance), ErrorContext> {
+        self.validate_context(operation)?;
+        let active_profile_name = self.session.active_profile.as_ref().unwrap();
+        let active_instance_name = self.session.active_instance.as_ref().unwrap();
+        let active_profile = self.state.profile.profiles.get(active_profile_name).unwrap();
+        let active_instance =
+            active_profile.instances.as_ref().and_then(|i| i.get(active_instance_name)).ok_or_else(|| {
+                ErrorContext::builder()
+                    .error(error::Error::conflicts_service("Active instance not found", operation))
+                    .suggested_action("Select an instance and try again")
+                    .build()
+            })?;
+
+        Ok((active_profile, active_instance))
+    }
+
+    fn validate_context(&self, operation: &str) -> Result<(), ErrorContext> {
+        if self.session.active_profile.is_none() {
+            Err(ErrorContext::builder()
+                .error(error::Error::conflicts_service("No active profile", operation))
+                .suggested_action("Select a profile and try again")
+                .build())
+        } else if self.session.active_instance.is_none() {
+            Err(ErrorContext::builder()
+                .error(error::Error::conflicts_service("No active instance", operation))
+                .suggested_action("Select an instance and try again")
+                .build())
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use std::path::PathBuf;
+
+use iced::Task;
+
+use crate::app::message;
+use crate::app::session;
+use crate::app::state;
+use crate::core;
+use crate::core::history;
+use crate::core::services;
+use crate::error;
+use crate::error::ErrorContext;
+
+pub struct HistoryService<'a> {
+    session: &'a mut session::ApplicationSession,
+    state: &'a mut state::ApplicationState,
+}
+
+impl<'a> HistoryService<'a> {
+    pub fn new(session: &'a mut session::ApplicationSession, state: &'a mut state::ApplicationState) -> Self {
+        Self { session, state }
+    }
+
+    /// Records the state of the active instance before a change. Returns the trash directory of
+    /// the new step, where the mods removed by the change are kept until the step is discarded.
+    pub fn record(&mut self, description: &str) -> Option<PathBuf> {
+        if self.session.active_profile.is_none() || self.session.active_instance.is_none() {
+            return None;
+        }
+
+        // Staged file toggles are part of the state that undoing has to return to.
+        services::profile::ProfileService::new(self.session, self.state).try_commit_changes().ok()?;
+
+        let active_profile_name = self.session.active_profile.clone().unwrap();
+        let active_instance_name = self.session.active_instance.clone().unwrap();
+        let active_instance = self.state.profile.profiles[&active_profile_name]
+            .instances
+            .as_ref()?
+            .get(&active_instance_name)?;
+
+        tracing::debug!("Recording history step: {description}");
+        let (id, discarded) = self.state.history.record(description, &active_profile_name, active_instance);
+        self.purge(discarded);
+        Some(core::helpers::trash_dir(self.session, &active_profile_name).join(id))
+    }
+
+    /// Drops the latest step again if the command it was recorded for left the instance as it
+    /// was, e.g. because it was cancelled or failed.
+    pub fn discard_if_unchanged(&mut self) {
+        let Some(step) = self.state.history.undo.last() else {
+            return;
+        };
+
+        let unchanged = self
+            .state
+            .profile
+            .profiles
+            .get(&step.profile_name)
+            .and_then(|profile| profile.instances.as_ref())
+            .and_then(|instances| instances.get(&step.instance_name))
+            .is_some_and(|instance| *instance == step.instance);
+
+        if unchanged {
+            let step = self.state.history.undo.pop().unwrap();
+            self.purge(vec![step]);
+        }
+    }
+
+    pub fn undo(&mut self) -> Task<message::Message> {
+        self.step(true)
+    }
+
+    pub fn redo(&mut self) -> Task<message::Message> {
+        self.step(false)
+    }
+
+    /// Forgets every step and permanently deletes the mods kept in the trash.
+    pub fn clear(&mut self) {
+        let steps = self.state.history.clear();
+        if !steps.is_empty() {
+            tracing::info!("Clearing {} history steps", steps.len());
+        }
+
+        self.purge(steps);
+    }
+
+    fn step(&mut self, undo: bool) -> Task<message::Message> {
+        match self.try_step(undo) {
+            Ok(true) => {
+                let current_dir = self.state.ui.current_dir.clone();
+                services::ui::UiService::new(self.session, self.state)
+                    .reload_displayed_directory(Some(&current_dir))
+            }
+            Ok(false) => Task::none(),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    fn try_step(&mut self, undo: bool) -> Result<bool, ErrorContext> {
+        let operation = if undo { "Undo" } else { "Redo" };
+        let stack = if undo { &mut self.state.history.undo } else { &mut self.state.history.redo };
+        let Some(mut step) = stack.pop() else {
+            tracing::info!("Nothing to {}", operation.to_lowercase());
+            return Ok(false);
+        };
+
+        // History is cleared whenever the active instance changes, so this only guards against
+        // steps recorded for an instance that was replaced in the meantime.
+        if self.session.active_profile.as_ref() != Some(&step.profile_name)
+            || self.session.active_instance.as_ref() != Some(&step.instance_name)
+        {
+            self.clear();
+            return Err(ErrorContext::builder()
+                .error(error::Error::new(
+                    format!("{operation} step belongs to instance {}", step.instance_name),
+                    "History Service",
+                    operation,
+                ))
+                .suggested_action("Switch to the instance of the step, history has been cleared")
+                .build());
+        }
+
+        services::profile::ProfileService::new(self.session, self.state).try_commit_changes()?;
+
+        let trash_dir = core::helpers::trash_dir(self.session, &step.profile_name).join(&step.id);
+        let active_profile = self.state.profile.profiles.get_mut(&step.profile_name).unwrap();
+        let instance = active_profile.instances.as_mut().unwrap().get_mut(&step.instance_name).unwrap();
+
+        if let Err(err) = history::sync_mod_dirs(instance, &step.instance, &trash_dir) {
+            let stack = if undo { &mut self.state.history.undo } else { &mut self.state.history.redo };
+            stack.push(step);
+            return Err(err);
+        }
+
+        tracing::info!("{operation}: {}", step.description);
+        std::mem::swap(instance, &mut step.instance);
+        if undo {
+            self.state.history.redo.push(step)
+        } else {
+            self.state.history.undo.push(step)
+        }
+
+        // The staged entries belong to the replaced state, they are reloaded from the current one.
+        self.session.files.clear();
+        self.state.ui.dir_entries.clear();
+        self.session.mod_selected = None;
+        Ok(true)
+    }
+
+    /// Deletes the trash directories of steps that can no longer be undone or redone.
+    fn purge(&self, steps: Vec<history::HistoryStep>) {
+        for step in steps {
+            let trash_dir = core::helpers::trash_dir(self.session, &step.profile_name).join(&step.id);
+            if !trash_dir.exists() {
+                continue;
+            }
+
+            tracing::info!("Removing trash of \"{}\"", step.description);
+            if let Err(err) = std::fs::remove_dir_all(&trash_dir) {
+                tracing::warn!("Failed to remove {}: {err}", trash_dir.display());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::profile::Instance;
+    use crate::core::profile::Lookup;
+    use crate::core::profile::ModInfo;
+    use crate::core::profile::Profile;
+    use crate::core::testing::TempDir;
+
+    #[test]
+    fn undo_and_redo_restore_a_removed_mod() {
+        let root = TempDir::new();
+        let mod_dir = root.dir("mods/Test/mods/Default/Patch");
+        root.file("mods/Test/mods/Default/Patch/Data/Patch.vdf", "patch");
+
+        let mut session = session::ApplicationSession {
+            active_profile: Some("Test".to_owned()),
+            active_instance: Some("Default".to_owned()),
+            mod_storage_dir: Some(root.path().join("mods")),
+            ..Default::default()
+        };
+        let mut state = state::ApplicationState::default();
+        let instance = Instance::new(
+            "Default",
+            Lookup::default(),
+            vec![ModInfo::new(true, "Patch", &mod_dir, Lookup::default())],
+        );
+        let profile = Profile::new("Test", &root.dir("game"))
+            .with_instances(Some(Lookup::from_iter([("Default".to_owned(), instance)])));
+        state.profile.profiles.insert("Test".to_owned(), profile);
+
+        let mods = |state: &state::ApplicationState| {
+            state.profile.profiles["Test"].instances.as_ref().unwrap()["Default"].mods.len()
+        };
+
+        let trash_dir = HistoryService::new(&mut session, &mut state).record("Remove Patch").unwrap();
+        assert!(!trash_dir.starts_with(core::helpers::mod_storage_root(&session, "Test")));
+        services::mods::ModService::new(&mut session, &mut state)
+            .try_remove_mod(0, Some(&trash_dir))
+            .unwrap();
+        assert_eq!(mods(&state), 0);
+        assert!(!mod_dir.exists() && trash_dir.join("Patch").exists());
+
+        assert!(HistoryService::new(&mut session, &mut state).try_step(true).unwrap());
+        assert_eq!(mods(&state), 1);
+        assert_eq!(std::fs::read_to_string(mod_dir.join("Data/Patch.vdf")).unwrap(), "patch");
+        assert_eq!(state.history.redo_description(), Some("Remove Patch"));
+        assert_eq!(state.history.undo_description(), None);
+
+        assert!(HistoryService::new(&mut session, &mut state).try_step(false).unwrap());
+        assert_eq!(mods(&state), 0);
+        assert!(!mod_dir.exists() && trash_dir.join("Patch").exists());
+        assert_eq!(state.history.undo_description(), Some("Remove Patch"));
+
+        HistoryService::new(&mut session, &mut state).clear();
+        assert!(!trash_dir.exists());
+    }
+
+    #[test]
+    fn recording_drops_the_redo_steps_and_the_oldest_undo_steps() {
+        let mut history = history::History::default();
+        let instance = Instance::new("Default", Lookup::default(), Vec::new());
+
+        history.record("First", "Test", &instance);
+        history.redo.push(history.undo.pop().unwrap());
+        let (_, discarded) = history.record("Second", "Test", &instance);
+        assert_eq!(discarded.iter().map(|step| step.description.as_str()).collect::<Vec<_>>(), ["First"]);
+
+        let discarded = (0..history::HISTORY_LIMIT).flat_map(|_| history.record("Next", "Test", &instance).1);
+        assert_eq!(discarded.map(|step| step.description).collect::<Vec<_>>(), ["Second"]);
+        assert_eq!(history.undo.len(), history::HISTORY_LIMIT);
+    }
+}
@@ -0,0 +1,516 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use iced::Task;
+
+use crate::app::message;
+use crate::app::session;
+use crate::app::state;
+use crate::app::window::ApplicationWindow;
+use crate::core;
+use crate::core::modlist;
+use crate::core::modpack;
+use crate::core::services;
+use crate::error;
+use crate::error::ErrorContext;
+
+pub struct ModListService<'a> {
+    session: &'a mut session::ApplicationSession,
+    state: &'a mut state::ApplicationState,
+}
+
+impl<'a> ModListService<'a> {
+    pub fn new(session: &'a mut session::ApplicationSession, state: &'a mut state::ApplicationState) -> Self {
+        Self { session, state }
+    }
+
+    pub fn export_modlist(&mut self) -> Task<message::Message> {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Export mod list")
+            .set_file_name(format!(
+                "{}.{}",
+                self.session.active_instance.clone().unwrap_or_default(),
+                modlist::MODLIST_EXTENSION
+            ))
+            .add_filter("Mod list", &[modlist::MODLIST_EXTENSION])
+            .save_file()
+        else {
+            tracing::warn!("No path selected");
+            return Task::none();
+        };
+
+        // Pending file toggles of the active instance have to be part of the exported list.
+        let commit = services::profile::ProfileService::new(self.session, self.state).commit_session_files();
+        match self.try_export_modlist(&path) {
+            Ok(()) => commit,
+            Err(err) => commit.chain(Task::done(message::ErrorMessage::Handle(err).into())),
+        }
+    }
+
+    /// Loads a mod list, matches it against the local mods and shows the result in the mod list
+    /// window.
+    pub fn load_modlist(&mut self, path: Option<PathBuf>) -> Task<message::Message> {
+        let Some(path) = path.or_else(|| {
+            rfd::FileDialog::new()
+                .set_title("Load mod list")
+                .add_filter("Mod list", &[modlist::MODLIST_EXTENSION])
+                .pick_file()
+        }) else {
+            tracing::warn!("No path selected");
+            return Task::none();
+        };
+
+        if let Err(err) = self.try_load_modlist(&path) {
+            return Task::done(message::ErrorMessage::Handle(err).into());
+        }
+
+        if self.state.ui.is_window_open(ApplicationWindow::ModList) {
+            Task::none()
+        } else {
+            Task::done(message::WindowMessage::Open(ApplicationWindow::ModList).into())
+        }
+    }
+
+    pub fn set_archive_dir(&mut self, path: Option<PathBuf>) -> Task<message::Message> {
+        let Some(path) = path
+            .or_else(|| rfd::FileDialog::new().set_title("Select directory with mod archives").pick_folder())
+        else {
+            tracing::warn!("No path selected");
+            return Task::none();
+        };
+
+        tracing::info!("Searching mod archives in {}", path.display());
+        self.state.modlist.archive_dir = Some(path);
+        self.refresh_matches();
+        Task::none()
+    }
+
+    /// Creates a new instance from the matched mods of the loaded mod list. Missing mods are left
+    /// out, mods with a different version are installed as they are.
+    pub fn build_instance(&mut self) -> Task<message::Message> {
+        match self.try_build_instance() {
+            Ok(task) => task,
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    /// Makes the active instance match a modpack file.
+    pub fn apply_modpack(&mut self, path: Option<PathBuf>) -> Task<message::Message> {
+        let Some(path) = path.or_else(|| {
+            rfd::FileDialog::new()
+                .set_title("Apply modpack")
+                .add_filter("Modpack", &modpack::MODPACK_EXTENSIONS)
+                .pick_file()
+        }) else {
+            tracing::warn!("No path selected");
+            return Task::none();
+        };
+
+        // Pending file toggles are committed first, the modpack decides which of them remain.
+        let commit = services::profile::ProfileService::new(self.session, self.state).commit_session_files();
+        let result = modpack::Modpack::load(&path).and_then(|modpack| self.try_apply_modpack(&modpack));
+        match result {
+            Ok(report) => {
+                tracing::info!("Applied modpack {}: {}", path.display(), report.summary());
+                commit.chain(Task::done(message::UiMessage::ReloadDirEntries.into()))
+            }
+            Err(err) => commit.chain(Task::done(message::ErrorMessage::Handle(err).into())),
+        }
+    }
+
+    /// Installs the mods of a modpack missing from the active instance, reinstalls the ones whose
+    /// hash changed and uninstalls the ones it does not list. The mods are then ordered, enabled
+    /// and their files excluded as the modpack declares. Every source that has to be installed is
+    /// checked before the instance is changed, and a change that fails on the way rolls back the
+    /// ones made before it.
+    pub fn try_apply_modpack(
+        &mut self,
+        modpack: &modpack::Modpack,
+    ) -> Result<modpack::ModpackReport, ErrorContext> {
+        self.validate_context("Apply Modpack", false)?;
+        let active_profile_name = self.session.active_profile.clone().unwrap();
+        let active_instance_name = self.session.active_instance.clone().unwrap();
+        let before = self.state.profile.profiles[&active_profile_name].instances.as_ref().unwrap()
+            [&active_instance_name]
+            .clone();
+
+        let installed_hashes = before
+            .mods
+            .iter()
+            .map(|mod_info| (mod_info.name.clone(), mod_info.source_hash.clone()))
+            .collect::<core::profile::Lookup<_, _>>();
+
+        let mut outdated = Vec::new();
+        for entry in &modpack.mods {
+            let installed_hash = installed_hashes.get(&entry.name());
+            let is_outdated = match (installed_hash, &entry.sha256) {
+                (Some(Some(installed_hash)), Some(hash)) => !installed_hash.eq_ignore_ascii_case(hash),
+                _ => false,
+            };
+
+            if installed_hash.is_some() && !is_outdated {
+                continue;
+            }
+
+            Self::verify_modpack_source(entry)?;
+            if is_outdated {
+                outdated.push(entry.name());
+            }
+        }
+
+        // Removed mods are kept aside until every change succeeded, so that a failure can bring
+        // them back.
+        let staging_dir =
+            core::helpers::trash_dir(self.session, &active_profile_name).join(modpack::STAGING_DIR_NAME);
+        Self::remove_staging_dir(&staging_dir);
+
+        let mut report = modpack::ModpackReport::default();
+        let mut installed_dirs = Vec::new();
+        if let Err(err) =
+            self.apply_modpack_mods(modpack, &outdated, &staging_dir, &mut installed_dirs, &mut report)
+        {
+            tracing::warn!("Applying the modpack failed, rolling back its changes");
+            self.roll_back_modpack(before, &installed_dirs, &staging_dir);
+            return Err(err);
+        }
+        Self::remove_staging_dir(&staging_dir);
+
+        let active_profile = self.state.profile.profiles.get_mut(&active_profile_name).unwrap();
+        let game_dir = active_profile.path.clone();
+        let instance = active_profile.instances.as_mut().unwrap().get_mut(&active_instance_name).unwrap();
+        for entry in &modpack.mods {
+            let name = entry.name();
+            let excluded = entry.exclude.iter().map(|path| game_dir.join(path)).collect::<Vec<_>>();
+
+            // Files overridden by a later mod are only listed in the conflicts, they take effect
+            // again once the overriding mod is gone.
+            let conflicting = instance
+                .conflicts
+                .iter_mut()
+                .flat_map(|(path, providers)| providers.values_mut().map(move |metadata| (path, metadata)));
+            instance
+                .files
+                .iter_mut()
+                .chain(conflicting)
+                .filter(|(_, metadata)| metadata.parent_name == name)
+                .for_each(|(path, metadata)| {
+                    let enabled = !excluded.iter().any(|excluded| path.starts_with(excluded));
+                    if metadata.enabled != enabled {
+                        metadata.enabled = enabled;
+                        report.toggled_files += 1;
+                    }
+                });
+        }
+
+        if let Some(launch) = &modpack.launch {
+            launch.apply_to(self.session);
+            report.launch_settings = true;
+        }
+
+        // History steps and staged entries belong to the state before the modpack was applied.
+        services::history::HistoryService::new(self.session, self.state).clear();
+        self.session.files.clear();
+        self.state.ui.dir_entries.clear();
+        Ok(report)
+    }
+
+    /// Installs, removes, orders and toggles the mods of the active instance to match a modpack.
+    /// Removed mods are moved to `staging_dir` and the directories of installed mods are added to
+    /// `installed_dirs` before they are created.
+    fn apply_modpack_mods(
+        &mut self,
+        modpack: &modpack::Modpack,
+        outdated: &[String],
+        staging_dir: &Path,
+        installed_dirs: &mut Vec<PathBuf>,
+        report: &mut modpack::ModpackReport,
+    ) -> Result<(), ErrorContext> {
+        let active_profile_name = self.session.active_profile.clone().unwrap();
+        let active_instance_name = self.session.active_instance.clone().unwrap();
+        let mod_storage_dir =
+            core::helpers::mod_storage_dir(self.session, &active_profile_name, &active_instance_name);
+        let active_mods = |state: &state::ApplicationState| {
+            state.profile.profiles[&active_profile_name].instances.as_ref().unwrap()[&active_instance_name]
+                .mods
+                .clone()
+        };
+
+        let wanted = modpack.mods.iter().map(modpack::ModpackEntry::name).collect::<Vec<_>>();
+        for (index, mod_info) in active_mods(self.state).iter().enumerate().rev() {
+            if wanted.contains(&mod_info.name) && !outdated.contains(&mod_info.name) {
+                continue;
+            }
+
+            services::mods::ModService::new(self.session, self.state)
+                .try_remove_mod(index, Some(staging_dir))?;
+            if !outdated.contains(&mod_info.name) {
+                report.removed.push(mod_info.name.clone());
+            }
+        }
+
+        for entry in &modpack.mods {
+            let name = entry.name();
+            if active_mods(self.state).iter().any(|mod_info| mod_info.name == name) {
+                continue;
+            }
+
+            let mod_dir = mod_storage_dir.join(&name);
+            if !mod_dir.exists() {
+                installed_dirs.push(mod_dir);
+            }
+
+            services::mods::ModService::new(self.session, self.state).try_add_mod(&entry.source)?;
+            match outdated.contains(&name) {
+                true => report.reinstalled.push(name),
+                false => report.installed.push(name),
+            }
+        }
+
+        for (position, entry) in modpack.mods.iter().enumerate() {
+            let name = entry.name();
+            let mods = active_mods(self.state);
+            let Some(index) = mods.iter().position(|mod_info| mod_info.name == name) else {
+                continue;
+            };
+
+            if index != position {
+                services::mods::ModService::new(self.session, self.state).try_move_mod(index, position)?;
+                if !report.installed.contains(&name) && !report.reinstalled.contains(&name) {
+                    report.moved.push(name.clone());
+                }
+            }
+
+            if mods[index].enabled != entry.enabled {
+                services::mods::ModService::new(self.session, self.state)
+                    .try_toggle_mod(position, entry.enabled)?;
+                match entry.enabled {
+                    true => report.enabled.push(name),
+                    false => report.disabled.push(name),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the active instance to `before` after a failed modpack application: installed mods
+    /// are deleted and removed mods are moved back from `staging_dir`.
+    fn roll_back_modpack(
+        &mut self,
+        before: core::profile::Instance,
+        installed_dirs: &[PathBuf],
+        staging_dir: &Path,
+    ) {
+        for dir in installed_dirs.iter().filter(|dir| dir.exists()) {
+            if let Err(err) = std::fs::remove_dir_all(dir) {
+                tracing::warn!("Failed to remove {}: {err}", dir.display());
+            }
+        }
+
+        let nothing_installed = core::profile::Instance::default();
+        if let Err(err) = core::history::sync_mod_dirs(&nothing_installed, &before, staging_dir) {
+            tracing::warn!(
+                "Failed to restore the removed mods from {}: {}",
+                staging_dir.display(),
+                err.error
+            );
+            return;
+        }
+        Self::remove_staging_dir(staging_dir);
+
+        let active_profile_name = self.session.active_profile.clone().unwrap();
+        let active_profile = self.state.profile.profiles.get_mut(&active_profile_name).unwrap();
+        active_profile.instances.get_or_insert_default().insert(before.name.clone(), before);
+    }
+
+    fn remove_staging_dir(staging_dir: &Path) {
+        if staging_dir.exists()
+            && let Err(err) = std::fs::remove_dir_all(staging_dir)
+        {
+            tracing::warn!("Failed to remove {}: {err}", staging_dir.display());
+        }
+    }
+
+    fn try_export_modlist(&mut self, path: &Path) -> Result<(), ErrorContext> {
+        self.validate_context("Export Mod List", false)?;
+
+        let active_profile_name = self.session.active_profile.clone().unwrap();
+        let active_instance_name = self.session.active_instance.clone().unwrap();
+        let active_profile = &self.state.profile.profiles[&active_profile_name];
+        let active_instance = &active_profile.instances.as_ref().unwrap()[&active_instance_name];
+        let mod_storage_dir =
+            core::helpers::mod_storage_dir(self.session, &active_profile_name, &active_instance_name);
+
+        let modlist = modlist::ModList::new(
+            active_instance,
+            &active_profile.path,
+            &mod_storage_dir,
+            active_profile.game_type,
+        );
+
+        tracing::info!("Exporting mod list of {active_instance_name} to {}", path.display());
+        modlist.save(path)
+    }
+
+    fn try_load_modlist(&mut self, path: &Path) -> Result<(), ErrorContext> {
+        self.validate_context("Load Mod List", true)?;
+        let modlist = modlist::ModList::load(path)?;
+
+        let active_profile = &self.state.profile.profiles[self.session.active_profile.as_ref().unwrap()];
+        if modlist.game_type != active_profile.game_type {
+            tracing::warn!(
+                "Mod list was made for {}, active profile is {}",
+                modlist.game_type,
+                active_profile.game_type
+            );
+        }
+
+        tracing::info!("Loaded mod list {} with {} mods", modlist.instance_name, modlist.mods.len());
+        self.state.modlist.modlist = Some(modlist);
+        self.refresh_matches();
+        Ok(())
+    }
+
+    fn refresh_matches(&mut self) {
+        let Some(modlist) = self.state.modlist.modlist.as_ref() else {
+            return;
+        };
+
+        // Archives take precedence over installed mods, they carry the exact source.
+        let mut local_mods =
+            self.state.modlist.archive_dir.as_deref().map(modlist::scan_archives).unwrap_or_default();
+        if let Some(instances) = self
+            .session
+            .active_profile
+            .as_ref()
+            .and_then(|name| self.state.profile.profiles.get(name))
+            .and_then(|profile| profile.instances.as_ref())
+        {
+            local_mods.extend(
+                instances
+                    .values()
+                    .flat_map(|instance| instance.mods.iter())
+                    .filter(|mod_info| mod_info.path.exists())
+                    .map(modlist::LocalMod::from),
+            );
+        }
+
+        self.state.modlist.matches = modlist::match_mods(modlist, &local_mods);
+    }
+
+    fn try_build_instance(&mut self) -> Result<Task<message::Message>, ErrorContext> {
+        self.validate_context("Build Instance", true)?;
+        let Some(modlist) = self.state.modlist.modlist.clone() else {
+            return Err(ErrorContext::builder()
+                .error(error::Error::mods_service("No mod list loaded", "Build Instance"))
+                .suggested_action("Load a mod list and try again")
+                .build());
+        };
+
+        let active_profile_name = self.session.active_profile.clone().unwrap();
+        if self.state.profile.profiles[&active_profile_name].path.as_os_str().is_empty() {
+            return Err(ErrorContext::builder()
+                .error(error::Error::mods_service("Active profile has no game directory", "Build Instance"))
+                .suggested_action("Set the game directory of the profile and try again")
+                .build());
+        }
+
+        let existing_instances =
+            self.state.profile.profiles[&active_profile_name].instances.clone().unwrap_or_default();
+        let instance_name = std::iter::once(modlist.instance_name.clone())
+            .chain((2..).map(|n| format!("{} ({n})", modlist.instance_name)))
+            .find(|name| !existing_instances.contains_key(name))
+            .unwrap();
+
+        self.state.profile.instance_name_field = instance_name.clone();
+        let mut tasks = vec![services::profile::ProfileService::new(self.session, self.state).add_instance()];
+        if self.session.active_instance.as_ref() != Some(&instance_name) {
+            return Ok(Task::batch(tasks));
+        }
+
+        let matches = self.state.modlist.matches.clone();
+        let mut disabled_mods = Vec::new();
+        for (entry, mod_match) in modlist.mods.iter().zip(&matches) {
+            let Some(local_mod) = mod_match.local_mod() else {
+                tracing::warn!("Skipping missing mod {}", entry.name);
+                continue;
+            };
+
+            tasks.push(
+                services::mods::ModService::new(self.session, self.state)
+                    .add_mod(Some(local_mod.path.clone())),
+            );
+            if !entry.enabled {
+                disabled_mods.push(local_mod.name.clone());
+            }
+        }
+
+        let active_profile = self.state.profile.profiles.get_mut(&active_profile_name).unwrap();
+        let game_dir = active_profile.path.clone();
+        let mod_storage_dir =
+            core::helpers::mod_storage_dir(self.session, &active_profile_name, &instance_name);
+        let instance = active_profile.instances.as_mut().unwrap().get_mut(&instance_name).unwrap();
+
+        let disabled_indices =
+            instance.mods.iter().enumerate().filter(|(_, m)| disabled_mods.contains(&m.name)).map(|(i, _)| i);
+        for index in disabled_indices.collect::<Vec<_>>() {
+            tasks.push(services::mods::ModService::new(self.session, self.state).toggle_mod(index, false));
+        }
+
+        let active_profile = self.state.profile.profiles.get_mut(&active_profile_name).unwrap();
+        let instance = active_profile.instances.as_mut().unwrap().get_mut(&instance_name).unwrap();
+        for portable_path in &modlist.disabled_files {
+            let path = core::bundle::from_portable_path(portable_path, &game_dir, &mod_storage_dir);
+            if let Some(metadata) = instance.files.get_mut(&path) {
+                metadata.enabled = false;
+            }
+        }
+
+        tracing::info!("Built instance {instance_name} from mod list");
+        Ok(Task::batch(tasks).chain(Task::done(message::UiMessage::ReloadDirEntries.into())))
+    }
+
+    fn verify_modpack_source(entry: &modpack::ModpackEntry) -> Result<(), ErrorContext> {
+        if !entry.source.exists() {
+            return Err(ErrorContext::builder()
+                .error(error::Error::mods_service(
+                    format!("Mod source not found: {}", entry.source.display()),
+                    "Apply Modpack",
+                ))
+                .suggested_action("Check the source paths of the modpack")
+                .build());
+        }
+
+        let Some(hash) = &entry.sha256 else {
+            return Ok(());
+        };
+
+        let source_hash = modlist::source_hash(&entry.source)?;
+        match source_hash.eq_ignore_ascii_case(hash) {
+            true => Ok(()),
+            false => Err(ErrorContext::builder()
+                .error(error::Error::mods_service(
+                    format!("{} has hash {source_hash}, the modpack expects {hash}", entry.source.display()),
+                    "Apply Modpack",
+                ))
+                .suggested_action("Replace the mod source or update its hash in the modpack")
+                .build()),
+        }
+    }
+
+    fn validate_context(&self, operation: &str, ignore_instance: bool) -> Result<(), ErrorContext> {
+        if self.session.active_profile.is_none() {
+            Err(ErrorContext::builder()
+                .error(error::Error::mods_service("No active profile", operation))
+                .suggested_action("Select a profile and try again")
+                .build())
+        } else if !ignore_instance && self.session.active_instance.is_none() {
+            Err(ErrorContext::builder()
+                .error(error::Error::mods_service("No active instance", operation))
+                .suggested_action("Select an instance and try again")
+                .build())
+        } else {
+            Ok(())
+        }
+    }
+}
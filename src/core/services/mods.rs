@@ -8,7 +8,6 @@ use crate::app::message;
 use crate::app::session;
 use crate::app::state;
 use crate::core;
-use crate::core::constants::APP_NAME;
 use crate::core::profile::Conflicts;
 use crate::core::profile::FileMetadata;
 use crate::core::profile::Lookup;
@@ -42,8 +41,10 @@ impl<'a> ModService<'a> {
         }
     }
 
-    pub fn remove_mod(&mut self, mod_index: usize) -> Task<message::Message> {
-        match self.try_remove_mod(mod_index) {
+    /// Uninstalls a mod. Its files are moved to `trash_dir` if given, so that the removal can be
+    /// undone, and deleted otherwise.
+    pub fn remove_mod(&mut self, mod_index: usize, trash_dir: Option<&Path>) -> Task<message::Message> {
+        match self.try_remove_mod(mod_index, trash_dir) {
             Ok(()) => Task::done(message::UiMessage::ReloadDirEntries.into()),
             Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
         }
@@ -63,11 +64,12 @@ impl<'a> ModService<'a> {
         }
     }
 
-    fn try_add_mod(&mut self, mod_path: &Path) -> Result<(), ErrorContext> {
+    pub fn try_add_mod(&mut self, mod_path: &Path) -> Result<(), ErrorContext> {
         self.validate_context("Add", false)?;
+        self.validate_no_transfer("Add")?;
         Self::validate_mod(mod_path)?;
 
-        let mod_name = Self::get_mod_name(mod_path)?;
+        let (mod_name, mod_version) = Self::get_mod_name(mod_path)?;
         let mod_storage_dir = self.get_mod_storage_dir();
         let mod_dst_path = mod_storage_dir.join(&mod_name);
         tracing::info!("Installing mod \"{}\"", mod_name);
@@ -83,8 +85,11 @@ impl<'a> ModService<'a> {
             return Ok(());
         }
 
+        let source_hash = core::modlist::source_hash(mod_path)?;
         Self::install_mod(mod_path, &mod_dst_path)?;
-        let mod_info = Self::get_mod_info(&mod_dst_path, &mod_name)?;
+        let mod_info = Self::get_mod_info(&mod_dst_path, &mod_name)?
+            .with_version(mod_version)
+            .with_source_hash(Some(source_hash));
         active_instance.mods.push(mod_info.clone());
         active_instance
             .load_order
@@ -100,8 +105,9 @@ impl<'a> ModService<'a> {
         Ok(())
     }
 
-    fn try_remove_mod(&mut self, index: usize) -> Result<(), ErrorContext> {
+    pub fn try_remove_mod(&mut self, index: usize, trash_dir: Option<&Path>) -> Result<(), ErrorContext> {
         self.validate_context("Remove", false)?;
+        self.validate_no_transfer("Remove")?;
         let active_profile_name = &self.session.active_profile.clone().unwrap();
         let active_profile = self.state.profile.profiles.get_mut(active_profile_name).unwrap();
         let active_instance_name = &self.session.active_instance.clone().unwrap();
@@ -129,15 +135,24 @@ impl<'a> ModService<'a> {
         active_instance.mods.remove(index);
         active_instance.load_order.remove(&target_mod_name);
 
-        let mut errors: usize = target_mod_files.iter().fold(0, |mut errors, (path, _)| {
-            let remove = if path.is_dir() { std::fs::remove_dir_all } else { std::fs::remove_file };
-            if remove(path).is_err() {
-                errors += 1;
-            }
-            errors
-        });
+        if let Some(trash_dir) = trash_dir
+            && let Some(dir_name) = target_mod_path.file_name()
+        {
+            tracing::info!("Moving mod \"{target_mod_name}\" to trash");
+            return core::utils::move_dir(&target_mod_path, &trash_dir.join(dir_name));
+        }
 
-        if std::fs::remove_dir_all(&target_mod_path).is_err() {
+        // Files inside a removed directory are already gone.
+        let mut errors: usize =
+            target_mod_files.iter().filter(|(path, _)| path.exists()).fold(0, |mut errors, (path, _)| {
+                let remove = if path.is_dir() { std::fs::remove_dir_all } else { std::fs::remove_file };
+                if remove(path).is_err() {
+                    errors += 1;
+                }
+                errors
+            });
+
+        if target_mod_path.exists() && std::fs::remove_dir_all(&target_mod_path).is_err() {
             errors += 1;
         }
 
@@ -153,7 +168,7 @@ impl<'a> ModService<'a> {
         Ok(())
     }
 
-    fn try_toggle_mod(&mut self, mod_index: usize, enabled: bool) -> Result<(), ErrorContext> {
+    pub fn try_toggle_mod(&mut self, mod_index: usize, enabled: bool) -> Result<(), ErrorContext> {
         self.validate_context("Toggle", false)?;
         let active_profile_name = &self.session.active_profile.clone().unwrap();
         let active_profile = self.state.profile.profiles.get_mut(active_profile_name).unwrap();
@@ -188,6 +203,46 @@ impl<'a> ModService<'a> {
         Ok(())
     }
 
+    /// Moves a mod to another position of the load order and rebuilds the instance files, as
+    /// later mods override the files of earlier ones.
+    pub fn try_move_mod(&mut self, from: usize, to: usize) -> Result<(), ErrorContext> {
+        self.validate_context("Move", false)?;
+        self.validate_no_transfer("Move")?;
+        let active_profile_name = &self.session.active_profile.clone().unwrap();
+        let active_profile = self.state.profile.profiles.get_mut(active_profile_name).unwrap();
+        let active_instance_name = &self.session.active_instance.clone().unwrap();
+        let active_instance =
+            active_profile.instances.as_mut().unwrap().get_mut(active_instance_name).unwrap();
+
+        if from >= active_instance.mods.len() || to >= active_instance.mods.len() {
+            return Err(ErrorContext::builder()
+                .error(error::Error::mods_service(
+                    format!("Mod position out of range: {} mods installed", active_instance.mods.len()),
+                    "Move",
+                ))
+                .suggested_action("Choose a position within the installed mods")
+                .build());
+        }
+
+        let mod_info = active_instance.mods.remove(from);
+        tracing::info!("Moving mod \"{}\" from position {from} to {to}", mod_info.name);
+        active_instance.mods.insert(to, mod_info);
+        for (priority, mod_info) in active_instance.mods.iter().enumerate() {
+            active_instance.load_order.insert(mod_info.name.clone(), priority);
+        }
+
+        let base_files = core::basefiles::instance_base_paths(active_instance)
+            .map(|path| (path.clone(), core::basefiles::FileStamp::default()))
+            .collect();
+        core::services::profile::ProfileService::rebuild_instance(
+            active_instance,
+            &base_files,
+            &active_profile.path,
+        );
+
+        Ok(())
+    }
+
     fn try_reload_mods(&mut self) -> Result<(), ErrorContext> {
         if self.session.active_profile.is_none() || self.session.active_instance.is_none() {
             return Ok(());
@@ -227,10 +282,10 @@ impl<'a> ModService<'a> {
             let target_file_info = mod_file_info.clone().with_target_path(&dst_path);
 
             let Some(conflict_list) = instance_conflicts.entries.get_mut(&dst_path) else {
-                if let Some(current_file) = instance_files.get(&dst_path) {
-                    if current_file.parent_name == mod_file_info.parent_name {
-                        instance_files.remove(&dst_path);
-                    }
+                if let Some(current_file) = instance_files.get(&dst_path)
+                    && current_file.parent_name == mod_file_info.parent_name
+                {
+                    instance_files.remove(&dst_path);
                 }
                 return;
             };
@@ -270,13 +325,12 @@ impl<'a> ModService<'a> {
                     instance_files.remove(&dst_path).unwrap();
                     conflict_list.remove(existing_file_priority);
 
-                    if existing_file_parent_active {
-                        if let Some((_, original_file)) =
+                    if existing_file_parent_active
+                        && let Some((_, original_file)) =
                             conflict_list.iter().max_by_key(|(priority, _)| *priority)
-                        {
-                            tracing::info!("Restoring previous version of {}", dst_path.display());
-                            instance_files.insert(dst_path.clone(), original_file.clone());
-                        }
+                    {
+                        tracing::info!("Restoring previous version of {}", dst_path.display());
+                        instance_files.insert(dst_path.clone(), original_file.clone());
                     }
                 }
             }
@@ -288,7 +342,7 @@ impl<'a> ModService<'a> {
         });
     }
 
-    fn apply_mod_files(
+    pub fn apply_mod_files(
         instance_files: &mut Lookup<PathBuf, FileMetadata>,
         instance_conflicts: &mut Conflicts,
         load_order: &mut Lookup<String, usize>,
@@ -369,53 +423,35 @@ impl<'a> ModService<'a> {
             .with_files(mod_files))
     }
 
-    fn get_mod_name(mod_path: &Path) -> Result<String, ErrorContext> {
-        let mut mod_name =
-            mod_path.file_name().map(|name| name.to_string_lossy().to_string()).ok_or_else(|| {
-                tracing::error!("Failed to get mod name");
-                ErrorContext::builder()
-                    .error(error::Error::new("Failed to get mod name", "Mods Service", "Add"))
-                    .suggested_action(
-                        "Select a valid mod file. It should be either a directory or a zip archive.",
-                    )
-                    .build()
-            })?;
-
-        if mod_name.contains(".") {
-            mod_name = mod_name.split_once(".").unwrap().0.to_string();
+    /// Name the mod is installed under and the version in its file name.
+    fn get_mod_name(mod_path: &Path) -> Result<(String, Option<String>), ErrorContext> {
+        let (mod_name, mod_version) = core::modlist::split_file_name(mod_path);
+        if mod_name.is_empty() {
+            tracing::error!("Failed to get mod name");
+            return Err(ErrorContext::builder()
+                .error(error::Error::new("Failed to get mod name", "Mods Service", "Add"))
+                .suggested_action(
+                    "Select a valid mod file. It should be either a directory or a zip archive.",
+                )
+                .build());
         }
 
-        Ok(mod_name)
+        Ok((mod_name, mod_version))
     }
 
     fn get_mod_storage_dir(&self) -> PathBuf {
         let profile_name = self.session.active_profile.clone().unwrap();
         let instance_name = self.session.active_instance.clone().unwrap();
-        match self.session.mod_storage_dir.as_ref() {
-            Some(dir) => {
-                std::path::absolute(dir.join(APP_NAME).join(profile_name).join("mods").join(instance_name))
-                    .unwrap()
-            }
-            None => match self.session.custom_user_data_path.as_ref() {
-                Some(custom_path) => std::path::absolute(
-                    custom_path.join(APP_NAME).join(profile_name).join("mods").join(instance_name),
-                )
-                .unwrap(),
-                None => {
-                    let default_data_path = crate::core::constants::local_app_data_path().join(APP_NAME);
-                    std::path::absolute(default_data_path.join(profile_name).join("mods").join(instance_name))
-                        .unwrap()
-                }
-            },
-        }
+        core::helpers::mod_storage_dir(self.session, &profile_name, &instance_name)
     }
 
     fn install_mod(src_mod_path: &Path, dst_mod_path: &Path) -> Result<(), ErrorContext> {
         std::fs::create_dir_all(dst_mod_path).map_err(|e| {
             ErrorContext::builder()
-                .error(error::Error::new(e.to_string(), "Mods Service", "Add"))
-                .suggested_action(
-                    "Check if the mod storage directory is writable or permissions are set correctly.",
+                .error(
+                    error::Error::new("Could not create mod directory", "Mods Service", "Add")
+                        .with_path(dst_mod_path)
+                        .with_source(&e),
                 )
                 .build()
         })?;
@@ -449,15 +485,26 @@ impl<'a> ModService<'a> {
                 .suggested_action("Select a profile and try again")
                 .build())
         } else if !ignore_instance && self.session.active_instance.is_none() {
-            return Err(ErrorContext::builder()
+            Err(ErrorContext::builder()
                 .error(error::Error::new("No active instance", "Mods Service", operation))
                 .suggested_action("Select an instance and try again")
-                .build());
+                .build())
         } else {
             Ok(())
         }
     }
 
+    /// Refuses changes to the installed mods while they are transferred to a new mods directory.
+    fn validate_no_transfer(&self, operation: &str) -> Result<(), ErrorContext> {
+        match self.state.mod_management.storage_transfer {
+            Some(_) => Err(ErrorContext::builder()
+                .error(error::Error::mods_service("Mods are being transferred to a new directory", operation))
+                .suggested_action("Wait for the transfer to finish and try again")
+                .build()),
+            None => Ok(()),
+        }
+    }
+
     fn is_valid_mod_source(mod_path: &path::Path) -> bool {
         mod_path.exists()
             && (mod_path.is_dir() || mod_path.extension().and_then(|e| e.to_str()) == Some("zip"))
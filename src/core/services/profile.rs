@@ -7,8 +7,10 @@ use crate::app::message;
 use crate::app::session;
 use crate::app::state;
 use crate::core;
+use crate::core::constants;
 use crate::core::profile;
 use crate::core::profile::Lookup;
+use crate::core::services;
 use crate::error;
 use crate::error::ErrorContext;
 
@@ -40,6 +42,114 @@ impl<'a> ProfileService<'a> {
         }
     }
 
+    pub fn duplicate_instance(&mut self) -> Task<message::Message> {
+        let new_instance_name = self.state.profile.instance_name_field.trim().to_owned();
+        match self.try_duplicate_instance(&new_instance_name) {
+            Ok(()) => self.switch_instance(&new_instance_name),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    pub fn rename_instance(&mut self) -> Task<message::Message> {
+        let new_instance_name = self.state.profile.instance_name_field.trim().to_owned();
+        match self.try_rename_instance(&new_instance_name) {
+            Ok(()) => Task::done(message::UiMessage::ReloadDirEntries.into()),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    pub fn export_instance(&mut self) -> Task<message::Message> {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Export instance")
+            .set_file_name(format!(
+                "{}.{}",
+                self.session.active_instance.clone().unwrap_or_default(),
+                core::bundle::BUNDLE_EXTENSION
+            ))
+            .add_filter("Instance bundle", &[core::bundle::BUNDLE_EXTENSION])
+            .save_file()
+        else {
+            tracing::warn!("No path selected");
+            return Task::none();
+        };
+
+        match self.try_export_instance(&path) {
+            Ok(()) => Task::none(),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    pub fn import_instance(&mut self, path: Option<path::PathBuf>) -> Task<message::Message> {
+        let Some(path) = path.or_else(|| {
+            rfd::FileDialog::new()
+                .set_title("Import instance")
+                .add_filter("Instance bundle", &[core::bundle::BUNDLE_EXTENSION])
+                .pick_file()
+        }) else {
+            tracing::warn!("No path selected");
+            return Task::none();
+        };
+
+        match self.try_import_instance(&path) {
+            Ok(instance_name) => self.switch_instance(&instance_name),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    /// Saves the state of the active instance under the name and note entered in the snapshot
+    /// fields.
+    pub fn create_snapshot(&mut self) -> Task<message::Message> {
+        let snapshot_name = self.state.profile.snapshot_name_field.trim().to_owned();
+        match self.try_create_snapshot(&snapshot_name) {
+            Ok(()) => Task::none(),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    /// Replaces the active instance with the state captured in a snapshot.
+    pub fn restore_snapshot(&mut self, path: &Path) -> Task<message::Message> {
+        let confirmed = rfd::MessageDialog::new()
+            .set_title("Restore snapshot")
+            .set_description(
+                "Restore this snapshot? Changes made to the instance since it was taken will be lost.",
+            )
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .set_level(rfd::MessageLevel::Warning)
+            .show();
+
+        if confirmed != rfd::MessageDialogResult::Yes {
+            return Task::none();
+        }
+
+        match self.try_restore_snapshot(path) {
+            Ok(()) => Task::done(message::UiMessage::ReloadDirEntries.into()),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    pub fn delete_snapshot(&mut self, path: &Path) -> Task<message::Message> {
+        tracing::info!("Removing snapshot {}", path.display());
+        let result = std::fs::remove_file(path);
+        self.state.profile.refresh_snapshots(self.session);
+
+        match result {
+            Ok(()) => Task::none(),
+            Err(err) => Task::done(
+                message::ErrorMessage::Handle(
+                    ErrorContext::builder()
+                        .error(
+                            error::Error::file_system("Could not delete snapshot", "Delete Snapshot")
+                                .with_path(path)
+                                .with_source(&err),
+                        )
+                        .suggested_action("Make sure the snapshot file is not in use")
+                        .build(),
+                )
+                .into(),
+            ),
+        }
+    }
+
     pub fn switch_profile(&mut self, profile_name: &str) -> Task<message::Message> {
         match self.try_switch_profile(profile_name) {
             Ok(()) => Task::done(message::UiMessage::ReloadDirEntries.into()),
@@ -61,6 +171,24 @@ impl<'a> ProfileService<'a> {
         }
     }
 
+    /// Copies the enabled mod files of the active instance into the game directory.
+    pub fn deploy_instance(&mut self) -> Task<message::Message> {
+        // Pending file toggles of the active instance have to be part of the deployment.
+        let commit = self.commit_session_files();
+        match self.try_deploy_instance() {
+            Ok(()) => commit,
+            Err(err) => commit.chain(Task::done(message::ErrorMessage::Handle(err).into())),
+        }
+    }
+
+    /// Removes the deployed mod files from the game directory and restores the files they replaced.
+    pub fn purge_deployment(&mut self) -> Task<message::Message> {
+        match self.try_purge_deployment() {
+            Ok(()) => Task::none(),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
     pub fn set_game_dir(&mut self, path: Option<path::PathBuf>) -> Task<message::Message> {
         let Some(path) =
             path.or_else(|| rfd::FileDialog::new().set_title("Select game directory").pick_folder())
@@ -79,6 +207,17 @@ impl<'a> ProfileService<'a> {
         }
     }
 
+    /// Looks for base files added, removed or replaced since the last scan and rebuilds every
+    /// instance of the active profile on top of the new base files.
+    pub fn rescan_game_dir(&mut self) -> Task<message::Message> {
+        match self.try_rescan_game_dir() {
+            Ok(()) => Task::done(message::UiMessage::ReloadDirEntries.into()),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    /// Changes the mods directory. Installed mods are moved or copied to the new location in the
+    /// background, the new directory is only used once they arrived.
     pub fn set_mods_dir(&mut self, path: Option<path::PathBuf>) -> Task<message::Message> {
         let Some(path) =
             path.or_else(|| rfd::FileDialog::new().set_title("Select mod storage directory").pick_folder())
@@ -87,15 +226,403 @@ impl<'a> ProfileService<'a> {
             return Task::none();
         };
 
-        match self.try_set_mods_dir(&path) {
-            Ok(_) => Task::batch([
+        let mut transfer = match self.try_plan_mods_dir_transfer(&path) {
+            Ok(transfer) => transfer,
+            Err(err) => return Task::done(message::ErrorMessage::Handle(err).into()),
+        };
+
+        if !transfer.has_files() {
+            return self.finish_mods_dir_transfer(transfer, Ok(()));
+        }
+
+        let answer = rfd::MessageDialog::new()
+            .set_title("Change mods directory")
+            .set_description(format!(
+                "Installed mods have to be transferred to {}.\n\nYes: move them\nNo: copy them and keep the \
+                 current directory\nCancel: keep using the current directory",
+                path.display()
+            ))
+            .set_buttons(rfd::MessageButtons::YesNoCancel)
+            .set_level(rfd::MessageLevel::Info)
+            .show();
+
+        transfer.mode = match answer {
+            rfd::MessageDialogResult::Yes => core::storage::TransferMode::Move,
+            rfd::MessageDialogResult::No => core::storage::TransferMode::Copy,
+            _ => return Task::none(),
+        };
+
+        tracing::info!("Transferring mods to {} ({:?})", path.display(), transfer.mode);
+        self.state.mod_management.storage_transfer = Some(transfer.clone());
+        self.state.mod_management.storage_transfer_progress = Default::default();
+        Task::run(transfer.run(), |event| message::ModMessage::StorageTransfer(event).into())
+    }
+
+    pub fn finish_mods_dir_transfer(
+        &mut self,
+        transfer: core::storage::StorageTransfer,
+        result: Result<(), ErrorContext>,
+    ) -> Task<message::Message> {
+        let mods_dir = transfer.mods_dir.display().to_string();
+        match result.and_then(|()| self.try_set_mods_dir(transfer)) {
+            Ok(()) => Task::batch([
                 Task::done(message::UiMessage::ReloadDirEntries.into()),
-                Task::done(message::ModMessage::UpdateModsDirField(path.display().to_string()).into()),
+                Task::done(message::ModMessage::UpdateModsDirField(mods_dir).into()),
             ]),
             Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
         }
     }
 
+    pub fn create_profile(&mut self) -> Task<message::Message> {
+        let profile_name = self.state.profile.profile_name_field.trim().to_owned();
+        let game_type = self.state.profile.new_profile_game_type;
+        match self.try_create_profile(&profile_name, game_type) {
+            Ok(()) => self.switch_profile(&profile_name),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    pub fn rename_active_profile(&mut self) -> Task<message::Message> {
+        let new_profile_name = self.state.profile.profile_name_field.trim().to_owned();
+        match self.try_rename_active_profile(&new_profile_name) {
+            Ok(()) => Task::done(message::UiMessage::ReloadDirEntries.into()),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    pub fn duplicate_active_profile(&mut self) -> Task<message::Message> {
+        let new_profile_name = self.state.profile.profile_name_field.trim().to_owned();
+        match self.try_duplicate_active_profile(&new_profile_name) {
+            Ok(()) => self.switch_profile(&new_profile_name),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    pub fn delete_active_profile(&mut self) -> Task<message::Message> {
+        let Some(profile_name) = self.session.active_profile.clone() else {
+            tracing::warn!("No active profile");
+            return Task::none();
+        };
+
+        let confirmed = rfd::MessageDialog::new()
+            .set_title("Delete profile")
+            .set_description(format!(
+                "Delete profile \"{profile_name}\" together with all of its instances and installed mods?"
+            ))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .set_level(rfd::MessageLevel::Warning)
+            .show();
+
+        if confirmed != rfd::MessageDialogResult::Yes {
+            return Task::none();
+        }
+
+        match self.try_delete_profile(&profile_name) {
+            Ok(()) => Task::none(),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    pub fn set_game_type(&mut self, game_type: constants::DefaultProfile) -> Task<message::Message> {
+        match self
+            .try_update_active_profile("Set Game Type", |profile| profile.game_type = game_type)
+            .and_then(|()| self.try_validate_game_dir())
+        {
+            Ok(()) => Task::none(),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    pub fn set_executable(&mut self, executable: &str) -> Task<message::Message> {
+        let executable = (!executable.trim().is_empty()).then(|| path::PathBuf::from(executable.trim()));
+        match self.try_update_active_profile("Set Executable", |profile| profile.executable = executable) {
+            Ok(()) => Task::none(),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    pub fn set_layout_dir(&mut self, dir: profile::LayoutDir, path: &str) -> Task<message::Message> {
+        match self.try_update_active_profile("Set Layout", |profile| profile.layout.set(dir, Path::new(path)))
+        {
+            Ok(()) => Task::none(),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    /// Checks the game directory against the game settings of the active profile.
+    pub fn validate_game_dir(&mut self) -> Task<message::Message> {
+        match self.try_validate_game_dir() {
+            Ok(()) => Task::none(),
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+
+    pub fn discover_installations(&mut self) -> Task<message::Message> {
+        tracing::info!("Searching for Gothic installations");
+        let installations = core::discovery::discover_installations();
+        if installations.is_empty() {
+            tracing::warn!("No Gothic installations found");
+        }
+
+        self.state.profile.discovered_installations = installations;
+        Task::none()
+    }
+
+    /// Assigns a discovered installation to the active profile, switching to the suggested default
+    /// profile first if none is active.
+    pub fn use_installation(&mut self, index: usize) -> Task<message::Message> {
+        let Some(installation) = self.state.profile.discovered_installations.get(index).cloned() else {
+            tracing::warn!("No discovered installation at index {index}");
+            return Task::none();
+        };
+
+        let set_game_dir = Task::done(message::ProfileMessage::SetGameDir(Some(installation.path)).into());
+        match (&self.session.active_profile, installation.suggested_profile) {
+            (None, Some(suggested_profile)) => {
+                Task::done(message::ProfileMessage::SetActive(suggested_profile.to_string()).into())
+                    .chain(set_game_dir)
+            }
+            _ => set_game_dir,
+        }
+    }
+
+    fn try_create_profile(
+        &mut self,
+        profile_name: &str,
+        game_type: constants::DefaultProfile,
+    ) -> Result<(), ErrorContext> {
+        self.validate_profile_name(profile_name, "Create Profile")?;
+
+        tracing::info!("Creating {game_type} profile: {profile_name}");
+        let new_profile =
+            profile::Profile::default().with_name(profile_name).with_game_type(game_type).with_custom(true);
+        self.state.profile.profiles.insert(profile_name.to_owned(), new_profile);
+        self.state.profile.refresh_profile_choices();
+        self.state.profile.profile_name_field.clear();
+
+        Ok(())
+    }
+
+    fn try_rename_active_profile(&mut self, new_profile_name: &str) -> Result<(), ErrorContext> {
+        self.validate_context("Rename Profile", true)?;
+        self.validate_profile_name(new_profile_name, "Rename Profile")?;
+        let old_profile_name = self.session.active_profile.clone().unwrap();
+        self.validate_custom_profile(&old_profile_name, "Rename Profile")?;
+
+        if self.session.active_instance.is_some() {
+            self.try_commit_changes()?;
+        }
+
+        self.clear_history();
+        tracing::info!("Renaming profile {old_profile_name} to {new_profile_name}");
+        let user_data_dir = self.session.custom_user_data_path.clone();
+        let old_profile_dir = core::helpers::profile_dir(&old_profile_name, user_data_dir.as_deref());
+        let new_profile_dir = core::helpers::profile_dir(new_profile_name, user_data_dir.as_deref());
+        if old_profile_dir.exists() {
+            std::fs::rename(&old_profile_dir, &new_profile_dir).map_err(|err| {
+                ErrorContext::builder()
+                    .error(
+                        error::Error::file_system("Could not rename profile directory", "Rename Profile")
+                            .with_path(&old_profile_dir)
+                            .with_source(&err),
+                    )
+                    .suggested_action("Make sure no other program is using the profile directory")
+                    .build()
+            })?;
+        }
+
+        // With the default storage location mods live inside the profile directory and were moved
+        // together with it, otherwise the separate storage directory has to be moved as well.
+        let old_storage_root = core::helpers::mod_storage_root(self.session, &old_profile_name);
+        let new_storage_root = core::helpers::mod_storage_root(self.session, new_profile_name);
+        if old_storage_root.exists() {
+            std::fs::create_dir_all(new_storage_root.parent().unwrap_or(&new_storage_root))
+                .and_then(|_| std::fs::rename(&old_storage_root, &new_storage_root))
+                .map_err(|err| {
+                    ErrorContext::builder()
+                        .error(
+                            error::Error::file_system("Could not move mod storage", "Rename Profile")
+                                .with_path(&old_storage_root)
+                                .with_source(&err),
+                        )
+                        .suggested_action("Make sure no other program is using the mod storage directory")
+                        .build()
+                })?;
+        }
+
+        let mut profile =
+            self.state.profile.profiles.remove(&old_profile_name).unwrap().with_name(new_profile_name);
+        profile.rebase_paths(&old_storage_root, &new_storage_root);
+        self.session
+            .files
+            .values_mut()
+            .for_each(|metadata| metadata.rebase_paths(&old_storage_root, &new_storage_root));
+        self.state
+            .ui
+            .dir_entries
+            .iter_mut()
+            .for_each(|(_, metadata)| metadata.rebase_paths(&old_storage_root, &new_storage_root));
+
+        self.state.profile.profiles.insert(new_profile_name.to_owned(), profile);
+        self.state.profile.refresh_profile_choices();
+        self.state.profile.profile_name_field.clear();
+        self.session.active_profile = Some(new_profile_name.to_owned());
+
+        Ok(())
+    }
+
+    fn try_duplicate_active_profile(&mut self, new_profile_name: &str) -> Result<(), ErrorContext> {
+        self.validate_context("Duplicate Profile", true)?;
+        self.validate_profile_name(new_profile_name, "Duplicate Profile")?;
+        let source_profile_name = self.session.active_profile.clone().unwrap();
+
+        if self.session.active_instance.is_some() {
+            self.try_commit_changes()?;
+        }
+
+        // Copy installed mods so that removing a mod from one profile leaves the other intact.
+        let source_storage_root = core::helpers::mod_storage_root(self.session, &source_profile_name);
+        let new_storage_root = core::helpers::mod_storage_root(self.session, new_profile_name);
+        if source_storage_root.exists() {
+            tracing::info!("Copying mod storage to {}", new_storage_root.display());
+            core::utils::copy_recursive(&source_storage_root, &new_storage_root)?;
+        }
+
+        tracing::info!("Duplicating profile {source_profile_name} as {new_profile_name}");
+        let mut new_profile = self.state.profile.profiles.get(&source_profile_name).unwrap().clone();
+        let new_profile_dir =
+            core::helpers::profile_dir(new_profile_name, self.session.custom_user_data_path.as_deref());
+        let base_files = self.recorded_base_files(&source_profile_name)?;
+        core::basefiles::save_base_files(&new_profile_dir, &new_profile.path, &base_files)?;
+
+        new_profile = new_profile.with_name(new_profile_name).with_custom(true);
+        new_profile.rebase_paths(&source_storage_root, &new_storage_root);
+
+        self.state.profile.profiles.insert(new_profile_name.to_owned(), new_profile);
+        self.state.profile.refresh_profile_choices();
+        self.state.profile.profile_name_field.clear();
+
+        Ok(())
+    }
+
+    fn try_delete_profile(&mut self, profile_name: &str) -> Result<(), ErrorContext> {
+        self.validate_custom_profile(profile_name, "Delete Profile")?;
+
+        // The profile stays loaded until its files are gone, so that a failed deletion can be retried.
+        tracing::info!("Deleting profile: {profile_name}");
+        let storage_root = core::helpers::mod_storage_root(self.session, profile_name);
+        let trash_dir = core::helpers::trash_dir(self.session, profile_name);
+        for dir in [storage_root, trash_dir].iter().filter(|dir| dir.exists()) {
+            std::fs::remove_dir_all(dir).map_err(|err| {
+                ErrorContext::builder()
+                    .error(
+                        error::Error::file_system("Could not delete mod storage", "Delete Profile")
+                            .with_path(dir)
+                            .with_source(&err),
+                    )
+                    .suggested_action("Make sure no other program is using the mod storage directory")
+                    .build()
+            })?;
+        }
+
+        let user_data_dir = self.session.custom_user_data_path.clone();
+        core::helpers::remove_profile(profile_name, user_data_dir.as_deref()).map_err(|err| {
+            ErrorContext::builder()
+                .error(
+                    error::Error::file_system("Could not delete profile", "Delete Profile")
+                        .with_path(core::helpers::profile_dir(profile_name, user_data_dir.as_deref()))
+                        .with_source(&err),
+                )
+                .suggested_action("Make sure no other program is using the profile directory")
+                .build()
+        })?;
+
+        self.clear_history();
+        self.state.profile.profiles.remove(profile_name);
+        self.state.profile.refresh_profile_choices();
+        self.state.profile.instance_choices = iced::widget::combo_box::State::new(Vec::new());
+
+        if self.session.active_profile.as_deref() == Some(profile_name) {
+            self.session.active_profile = None;
+            self.session.active_instance = None;
+            self.session.mod_selected = None;
+            self.session.files.clear();
+            self.state.ui.dir_entries.clear();
+        }
+
+        Ok(())
+    }
+
+    fn try_update_active_profile<F>(&mut self, operation: &str, update: F) -> Result<(), ErrorContext>
+    where
+        F: FnOnce(&mut profile::Profile),
+    {
+        self.validate_context(operation, true)?;
+        let active_profile_name = self.session.active_profile.clone().unwrap();
+        // Default profiles get their game settings from the built-in defaults on every load.
+        self.validate_custom_profile(&active_profile_name, operation)?;
+        let active_profile = self.state.profile.profiles.get_mut(&active_profile_name).unwrap();
+        update(active_profile);
+
+        // Validating hashes the executable, so edits only drop the outdated result and the game
+        // directory is validated again once they are submitted.
+        active_profile.fingerprint = None;
+        Ok(())
+    }
+
+    fn try_validate_game_dir(&mut self) -> Result<(), ErrorContext> {
+        self.validate_context("Validate Game Directory", true)?;
+        let active_profile_name = self.session.active_profile.clone().unwrap();
+        let active_profile = self.state.profile.profiles.get_mut(&active_profile_name).unwrap();
+        if !active_profile.path.as_os_str().is_empty() {
+            active_profile.fingerprint =
+                Some(core::fingerprint::fingerprint(active_profile, &active_profile.path));
+        }
+
+        Ok(())
+    }
+
+    fn validate_profile_name(&self, profile_name: &str, operation: &str) -> Result<(), ErrorContext> {
+        let is_valid_dir_name = !profile_name.is_empty()
+            && profile_name != "."
+            && profile_name != ".."
+            && !profile_name.contains(['/', '\\', ':', '*', '?', '"', '<', '>', '|']);
+
+        if !is_valid_dir_name {
+            Err(ErrorContext::builder()
+                .error(error::Error::profile_service(
+                    format!("Invalid profile name: \"{profile_name}\""),
+                    operation,
+                ))
+                .suggested_action("Enter a name that can be used as a directory name")
+                .build())
+        } else if self.state.profile.profiles.contains_key(profile_name) {
+            Err(ErrorContext::builder()
+                .error(error::Error::profile_service(
+                    format!("Profile already exists: {profile_name}"),
+                    operation,
+                ))
+                .suggested_action("Choose a different profile name")
+                .build())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn validate_custom_profile(&self, profile_name: &str, operation: &str) -> Result<(), ErrorContext> {
+        match self.state.profile.profiles.get(profile_name) {
+            Some(profile) if profile.is_custom => Ok(()),
+            Some(_) => Err(ErrorContext::builder()
+                .error(error::Error::profile_service("Default profiles can not be changed", operation))
+                .suggested_action("Duplicate the profile and modify the copy instead")
+                .build()),
+            None => Err(ErrorContext::builder()
+                .error(error::Error::profile_service("Profile not found", operation))
+                .suggested_action("Make sure to select a valid profile")
+                .build()),
+        }
+    }
+
     fn try_add_instance(&mut self, instance_name: &str) -> Result<(), ErrorContext> {
         if self.session.active_profile.is_none() {
             tracing::warn!("No active profile");
@@ -104,17 +631,21 @@ impl<'a> ProfileService<'a> {
 
         let active_profile_name = &self.session.active_profile.clone().unwrap();
         let active_profile = self.state.profile.profiles.get_mut(active_profile_name).unwrap();
-        let active_profile_instances = active_profile.instances.get_or_insert_default();
-        let profile_path = active_profile.path.clone();
-
-        if active_profile_instances.contains_key(instance_name) {
+        if active_profile.instances.as_ref().is_some_and(|instances| instances.contains_key(instance_name)) {
             tracing::warn!("Instance already exists: {instance_name}");
             return Ok(());
         }
 
-        let mut new_instance = Self::create_new_instance(instance_name, &profile_path);
+        // The game directory may have changed since the last scan, new instances start from its
+        // current state. The recorded base files stay those of the last rescan, which compares
+        // the instances already created against them.
+        let base_files =
+            self.scan_base_files(active_profile_name, &self.recorded_base_files(active_profile_name)?)?;
+        let active_profile = self.state.profile.profiles.get_mut(active_profile_name).unwrap();
+        let mut new_instance = Self::create_new_instance(instance_name, &base_files);
         new_instance.load_order.insert(instance_name.to_owned(), 0);
         tracing::info!("Adding instance: {instance_name}");
+        let active_profile_instances = active_profile.instances.get_or_insert_default();
         active_profile_instances.insert(instance_name.to_owned(), new_instance);
         let instance_names = active_profile_instances.keys().cloned().collect();
         self.state.profile.instance_choices = iced::widget::combo_box::State::new(instance_names);
@@ -123,8 +654,324 @@ impl<'a> ProfileService<'a> {
         Ok(())
     }
 
+    fn try_duplicate_instance(&mut self, new_instance_name: &str) -> Result<(), ErrorContext> {
+        self.validate_context("Duplicate Instance", false)?;
+        self.validate_instance_name(new_instance_name, "Duplicate Instance")?;
+        self.try_commit_changes()?;
+
+        let active_profile_name = self.session.active_profile.clone().unwrap();
+        let active_instance_name = self.session.active_instance.clone().unwrap();
+
+        // Each instance owns a copy of its mods, so uninstalling a mod from the duplicate leaves
+        // the original setup untouched.
+        let source_storage_dir =
+            core::helpers::mod_storage_dir(self.session, &active_profile_name, &active_instance_name);
+        let new_storage_dir =
+            core::helpers::mod_storage_dir(self.session, &active_profile_name, new_instance_name);
+        if source_storage_dir.exists() {
+            tracing::info!("Copying mod storage to {}", new_storage_dir.display());
+            core::utils::copy_recursive(&source_storage_dir, &new_storage_dir)?;
+        }
+
+        tracing::info!("Duplicating instance {active_instance_name} as {new_instance_name}");
+        let active_profile = self.state.profile.profiles.get_mut(&active_profile_name).unwrap();
+        let instances = active_profile.instances.get_or_insert_default();
+        let mut new_instance = instances.get(&active_instance_name).unwrap().clone();
+        new_instance.rename(new_instance_name);
+        new_instance.rebase_paths(&source_storage_dir, &new_storage_dir);
+        instances.insert(new_instance_name.to_owned(), new_instance);
+
+        self.state.profile.instance_choices =
+            iced::widget::combo_box::State::new(instances.keys().cloned().collect());
+        self.state.profile.instance_name_field.clear();
+
+        Ok(())
+    }
+
+    fn try_rename_instance(&mut self, new_instance_name: &str) -> Result<(), ErrorContext> {
+        self.validate_context("Rename Instance", false)?;
+        self.validate_instance_name(new_instance_name, "Rename Instance")?;
+        self.try_commit_changes()?;
+
+        let active_profile_name = self.session.active_profile.clone().unwrap();
+        let old_instance_name = self.session.active_instance.clone().unwrap();
+
+        let old_storage_dir =
+            core::helpers::mod_storage_dir(self.session, &active_profile_name, &old_instance_name);
+        let new_storage_dir =
+            core::helpers::mod_storage_dir(self.session, &active_profile_name, new_instance_name);
+        if old_storage_dir.exists() {
+            tracing::info!("Moving mod storage to {}", new_storage_dir.display());
+            std::fs::rename(&old_storage_dir, &new_storage_dir).map_err(|err| {
+                ErrorContext::builder()
+                    .error(
+                        error::Error::file_system("Could not move mod storage", "Rename Instance")
+                            .with_path(&old_storage_dir)
+                            .with_source(&err),
+                    )
+                    .suggested_action("Make sure no other program is using the mod storage directory")
+                    .build()
+            })?;
+        }
+
+        self.clear_history();
+        let old_snapshot_dir =
+            core::helpers::snapshot_dir(self.session, &active_profile_name, &old_instance_name);
+        if old_snapshot_dir.exists() {
+            let new_snapshot_dir =
+                core::helpers::snapshot_dir(self.session, &active_profile_name, new_instance_name);
+            tracing::info!("Moving snapshots to {}", new_snapshot_dir.display());
+            std::fs::rename(&old_snapshot_dir, &new_snapshot_dir).map_err(|err| {
+                ErrorContext::builder()
+                    .error(
+                        error::Error::file_system("Could not move snapshots", "Rename Instance")
+                            .with_path(&old_snapshot_dir)
+                            .with_source(&err),
+                    )
+                    .suggested_action("Make sure no other program is using the snapshot directory")
+                    .build()
+            })?;
+        }
+
+        tracing::info!("Renaming instance {old_instance_name} to {new_instance_name}");
+        let active_profile = self.state.profile.profiles.get_mut(&active_profile_name).unwrap();
+        let instances = active_profile.instances.get_or_insert_default();
+        let mut instance = instances.remove(&old_instance_name).unwrap();
+        instance.rename(new_instance_name);
+        instance.rebase_paths(&old_storage_dir, &new_storage_dir);
+        instances.insert(new_instance_name.to_owned(), instance);
+
+        self.state.profile.instance_choices =
+            iced::widget::combo_box::State::new(instances.keys().cloned().collect());
+        self.state.profile.instance_name_field.clear();
+        self.session.active_instance = Some(new_instance_name.to_owned());
+
+        // Drop the staged entries of the old name, they are reloaded from the renamed instance.
+        self.session.files.clear();
+        self.state.ui.dir_entries.clear();
+
+        Ok(())
+    }
+
+    fn try_export_instance(&mut self, bundle_path: &Path) -> Result<(), ErrorContext> {
+        self.validate_context("Export Instance", false)?;
+        self.try_commit_changes()?;
+
+        let active_profile_name = self.session.active_profile.clone().unwrap();
+        let active_instance_name = self.session.active_instance.clone().unwrap();
+        let active_profile = &self.state.profile.profiles[&active_profile_name];
+        let active_instance = &active_profile.instances.as_ref().unwrap()[&active_instance_name];
+        let mod_storage_dir =
+            core::helpers::mod_storage_dir(self.session, &active_profile_name, &active_instance_name);
+
+        let manifest = core::bundle::BundleManifest::new(
+            active_instance,
+            &active_profile.path,
+            &mod_storage_dir,
+            active_profile.game_type,
+            active_profile.fingerprint.as_ref().and_then(|f| f.version),
+            self.session.launch_options.clone(),
+            self.state.profile.export_include_mod_files,
+        );
+
+        tracing::info!("Exporting instance {active_instance_name} to {}", bundle_path.display());
+        core::bundle::write_bundle(bundle_path, &manifest, &mod_storage_dir)
+    }
+
+    fn try_import_instance(&mut self, bundle_path: &Path) -> Result<String, ErrorContext> {
+        self.validate_context("Import Instance", true)?;
+        if self.session.active_instance.is_some() {
+            self.try_commit_changes()?;
+        }
+
+        let manifest = core::bundle::read_manifest(bundle_path)?;
+        let active_profile_name = self.session.active_profile.clone().unwrap();
+        let active_profile = &self.state.profile.profiles[&active_profile_name];
+        if active_profile.path.as_os_str().is_empty() {
+            return Err(ErrorContext::builder()
+                .error(error::Error::profile_service(
+                    "Active profile has no game directory",
+                    "Import Instance",
+                ))
+                .suggested_action("Set the game directory of the profile and try again")
+                .build());
+        }
+
+        if manifest.game_type != active_profile.game_type {
+            tracing::warn!(
+                "Bundle was exported from a {} profile, importing into {}",
+                manifest.game_type,
+                active_profile.game_type
+            );
+        }
+
+        // Keep existing instances intact by picking a free name.
+        let existing_instances = active_profile.instances.clone().unwrap_or_default();
+        let instance_name = std::iter::once(manifest.instance.name.clone())
+            .chain((2..).map(|n| format!("{} ({n})", manifest.instance.name)))
+            .find(|name| !existing_instances.contains_key(name))
+            .unwrap();
+
+        let mod_storage_dir =
+            core::helpers::mod_storage_dir(self.session, &active_profile_name, &instance_name);
+        if manifest.includes_mod_files {
+            tracing::info!("Extracting bundled mods to {}", mod_storage_dir.display());
+            core::bundle::extract_mod_files(bundle_path, &mod_storage_dir)?;
+        }
+
+        // The base files listed in the bundle are those of the exporting installation, the mods are
+        // applied again on top of the local ones.
+        let mut instance = manifest.local_instance(&instance_name, &active_profile.path, &mod_storage_dir);
+        let base_files =
+            self.scan_base_files(&active_profile_name, &self.recorded_base_files(&active_profile_name)?)?;
+        Self::rebuild_instance(&mut instance, &base_files, &active_profile.path);
+        instance.mods.iter().filter(|mod_info| !mod_info.path.exists()).for_each(|mod_info| {
+            tracing::warn!("Mod files missing for {}, reinstall it to use it", mod_info.name)
+        });
+
+        // Launch options apply to every instance, bundled ones only fill in missing local ones.
+        match (&self.session.launch_options, &manifest.launch_options) {
+            (None, Some(launch_options)) => self.session.launch_options = Some(launch_options.clone()),
+            (Some(_), Some(_)) => tracing::info!("Keeping the local launch options over the bundled ones"),
+            _ => {}
+        }
+
+        tracing::info!("Imported instance {instance_name} from {}", bundle_path.display());
+        let active_profile = self.state.profile.profiles.get_mut(&active_profile_name).unwrap();
+        let instances = active_profile.instances.get_or_insert_default();
+        instances.insert(instance_name.clone(), instance);
+        self.state.profile.instance_choices =
+            iced::widget::combo_box::State::new(instances.keys().cloned().collect());
+
+        Ok(instance_name)
+    }
+
+    fn try_create_snapshot(&mut self, snapshot_name: &str) -> Result<(), ErrorContext> {
+        self.validate_context("Create Snapshot", false)?;
+        if snapshot_name.is_empty() {
+            return Err(ErrorContext::builder()
+                .error(error::Error::profile_service("Snapshot name is empty", "Create Snapshot"))
+                .suggested_action("Enter a name for the snapshot")
+                .build());
+        }
+
+        self.try_commit_changes()?;
+
+        let active_profile_name = self.session.active_profile.clone().unwrap();
+        let active_instance_name = self.session.active_instance.clone().unwrap();
+        let active_profile = &self.state.profile.profiles[&active_profile_name];
+        let active_instance = &active_profile.instances.as_ref().unwrap()[&active_instance_name];
+        let mod_storage_dir =
+            core::helpers::mod_storage_dir(self.session, &active_profile_name, &active_instance_name);
+
+        let snapshot = core::snapshot::Snapshot::new(
+            snapshot_name,
+            self.state.profile.snapshot_note_field.trim(),
+            active_instance,
+            &active_profile.path,
+            &mod_storage_dir,
+        );
+
+        let snapshot_dir =
+            core::helpers::snapshot_dir(self.session, &active_profile_name, &active_instance_name);
+        let snapshot_path = snapshot.save(&snapshot_dir)?;
+        tracing::info!("Saved snapshot of {active_instance_name} to {}", snapshot_path.display());
+
+        self.state.profile.snapshot_name_field.clear();
+        self.state.profile.snapshot_note_field.clear();
+        self.state.profile.refresh_snapshots(self.session);
+        Ok(())
+    }
+
+    fn try_restore_snapshot(&mut self, snapshot_path: &Path) -> Result<(), ErrorContext> {
+        self.validate_context("Restore Snapshot", false)?;
+        let snapshot = core::snapshot::Snapshot::load(snapshot_path)?;
+
+        let active_profile_name = self.session.active_profile.clone().unwrap();
+        let active_instance_name = self.session.active_instance.clone().unwrap();
+        let mod_storage_dir =
+            core::helpers::mod_storage_dir(self.session, &active_profile_name, &active_instance_name);
+        let active_profile = &self.state.profile.profiles[&active_profile_name];
+
+        let instance = snapshot.local_instance(&active_instance_name, &active_profile.path, &mod_storage_dir);
+        instance.mods.iter().filter(|mod_info| !mod_info.path.exists()).for_each(|mod_info| {
+            tracing::warn!("Mod files missing for {}, reinstall it to use it", mod_info.name)
+        });
+
+        tracing::info!("Restoring snapshot \"{}\" of {active_instance_name}", snapshot.name);
+
+        // A deployed instance is redeployed before it is replaced, so that a failed deployment
+        // leaves the instance as it was and the game keeps running what the instance shows.
+        let deployment =
+            core::deploy::Deployment::new(active_profile, self.session.custom_user_data_path.as_deref());
+        if deployment.load_manifest()?.instance.as_ref() == Some(&active_instance_name) {
+            tracing::info!("Redeploying {active_instance_name}");
+            deployment.deploy(&instance)?;
+        }
+
+        self.clear_history();
+        let active_profile = self.state.profile.profiles.get_mut(&active_profile_name).unwrap();
+        active_profile.instances.get_or_insert_default().insert(active_instance_name, instance);
+
+        // The staged entries belong to the replaced state, they are reloaded from the restored one.
+        self.session.files.clear();
+        self.state.ui.dir_entries.clear();
+
+        Ok(())
+    }
+
+    /// History steps refer to the active instance and its mod storage, so they are dropped
+    /// whenever either of them changes.
+    fn clear_history(&mut self) {
+        services::history::HistoryService::new(self.session, self.state).clear();
+    }
+
+    fn validate_instance_name(&self, instance_name: &str, operation: &str) -> Result<(), ErrorContext> {
+        let active_profile_name = self.session.active_profile.as_ref().unwrap();
+        let exists = self.state.profile.profiles[active_profile_name]
+            .instances
+            .as_ref()
+            .is_some_and(|instances| instances.contains_key(instance_name));
+        let storage_dir = core::helpers::mod_storage_dir(self.session, active_profile_name, instance_name);
+
+        if instance_name.is_empty() || instance_name.contains(['/', '\\', ':', '*', '?', '"', '<', '>', '|'])
+        {
+            Err(ErrorContext::builder()
+                .error(error::Error::profile_service(
+                    format!("Invalid instance name: \"{instance_name}\""),
+                    operation,
+                ))
+                .suggested_action("Enter a name that can be used as a directory name")
+                .build())
+        } else if exists {
+            Err(ErrorContext::builder()
+                .error(error::Error::profile_service(
+                    format!("Instance already exists: {instance_name}"),
+                    operation,
+                ))
+                .suggested_action("Choose a different instance name")
+                .build())
+        } else if storage_dir.exists() {
+            // Mods left behind by an instance removed outside of the application would otherwise
+            // be mixed into the mods of the new one.
+            Err(ErrorContext::builder()
+                .error(
+                    error::Error::profile_service(
+                        format!("Mod storage of instance {instance_name} already exists"),
+                        operation,
+                    )
+                    .with_path(&storage_dir),
+                )
+                .suggested_action("Choose a different instance name or remove the leftover directory")
+                .build())
+        } else {
+            Ok(())
+        }
+    }
+
     fn try_remove_instance(&mut self) -> Result<(), ErrorContext> {
         self.validate_context("Remove Instance", false)?;
+        self.clear_history();
         let active_profile_name = &self.session.active_profile.clone().unwrap();
         let active_profile = self.state.profile.profiles.get_mut(active_profile_name).unwrap();
         let active_profile_instances = active_profile.instances.get_or_insert_default();
@@ -147,6 +994,7 @@ impl<'a> ProfileService<'a> {
         self.state.profile.instance_choices =
             iced::widget::combo_box::State::new(instance_names.unwrap_or_default());
         self.state.profile.instance_name_field.clear();
+        self.state.profile.snapshots.clear();
         self.session.active_instance = None;
 
         Ok(())
@@ -157,10 +1005,13 @@ impl<'a> ProfileService<'a> {
             self.try_commit_changes()?;
         }
 
+        self.clear_history();
         if let Some(next_profile) = self.state.profile.profiles.get(profile_name) {
             tracing::info!("Switching to profile: {}", next_profile.name);
             self.session.active_profile = Some(profile_name.to_owned());
             self.session.active_instance = None;
+            self.state.profile.snapshots.clear();
+            self.state.profile.base_file_changes = None;
 
             let instances =
                 next_profile.instances.as_ref().map(|i| i.keys().cloned().collect()).unwrap_or_default();
@@ -184,12 +1035,46 @@ impl<'a> ProfileService<'a> {
             self.try_commit_changes()?;
         }
 
+        self.clear_history();
         tracing::info!("Switching to instance: {instance_name}");
         self.session.active_instance = Some(instance_name.to_owned());
+        self.state.profile.refresh_snapshots(self.session);
         Ok(())
     }
 
-    fn try_commit_changes(&mut self) -> Result<(), ErrorContext> {
+    fn try_deploy_instance(&mut self) -> Result<(), ErrorContext> {
+        self.validate_context("Deploy", false)?;
+        let active_profile = &self.state.profile.profiles[self.session.active_profile.as_ref().unwrap()];
+        let active_instance_name = self.session.active_instance.as_ref().unwrap();
+        let Some(instance) = active_profile.instances.as_ref().and_then(|i| i.get(active_instance_name))
+        else {
+            return Err(ErrorContext::builder()
+                .error(error::Error::profile_service(
+                    format!("Instance not found: {active_instance_name}"),
+                    "Deploy",
+                ))
+                .suggested_action("Select an instance and try again")
+                .build());
+        };
+
+        let deployment =
+            core::deploy::Deployment::new(active_profile, self.session.custom_user_data_path.as_deref());
+        let plan = deployment.deploy(instance)?;
+        tracing::info!("Deployed {active_instance_name}: {}", plan.summary());
+        Ok(())
+    }
+
+    fn try_purge_deployment(&mut self) -> Result<(), ErrorContext> {
+        self.validate_context("Purge", true)?;
+        let active_profile = &self.state.profile.profiles[self.session.active_profile.as_ref().unwrap()];
+        let deployment =
+            core::deploy::Deployment::new(active_profile, self.session.custom_user_data_path.as_deref());
+        let plan = deployment.purge()?;
+        tracing::info!("Purged {}: {}", active_profile.path.display(), plan.summary());
+        Ok(())
+    }
+
+    pub fn try_commit_changes(&mut self) -> Result<(), ErrorContext> {
         self.validate_context("Commit Changes", false)?;
         let active_profile_name = &self.session.active_profile.clone().unwrap();
         let active_profile = self.state.profile.profiles.get_mut(active_profile_name).unwrap();
@@ -212,19 +1097,69 @@ impl<'a> ProfileService<'a> {
             return Ok(());
         }
 
-        tracing::info!("Setting game directory to: {}", path.display());
         let active_profile_name = &self.session.active_profile.clone().unwrap();
         let active_profile = self.state.profile.profiles.get_mut(active_profile_name).unwrap();
 
-        active_profile.path = path.to_path_buf();
+        let fingerprint = core::fingerprint::fingerprint(active_profile, path);
+        if !fingerprint.is_valid() {
+            return Err(ErrorContext::builder()
+                .error(error::Error::profile_service(
+                    format!(
+                        "{} is not a valid game directory: {}",
+                        path.display(),
+                        fingerprint.errors.join(", ")
+                    ),
+                    "Set Game Dir",
+                ))
+                .suggested_action(
+                    "Select the game's root directory or adjust the profile's executable and layout",
+                )
+                .build());
+        }
+
+        fingerprint.warnings.iter().for_each(|warning| tracing::warn!("{warning}"));
+        tracing::info!("Setting game directory to: {} ({})", path.display(), fingerprint.summary());
+
+        // Staged file toggles are rebased together with the instance they belong to.
+        if self.session.active_instance.is_some() {
+            self.try_commit_changes()?;
+        }
+
+        let active_profile = self.state.profile.profiles.get_mut(active_profile_name).unwrap();
+        let old_path = std::mem::replace(&mut active_profile.path, path.to_path_buf());
+        if !old_path.as_os_str().is_empty() && old_path != path {
+            tracing::info!("Moving instance files from {} to {}", old_path.display(), path.display());
+            active_profile.rebase_paths(&old_path, path);
+            self.clear_history();
+        }
+
+        let active_profile = self.state.profile.profiles.get_mut(active_profile_name).unwrap();
+        active_profile.fingerprint = Some(fingerprint);
+        if let Err(err) = self.record_base_files(active_profile_name, &core::basefiles::scan_base_files(path))
+        {
+            tracing::warn!("Base files of {} were not recorded: {}", path.display(), err.error);
+        }
+
+        let active_profile = &self.state.profile.profiles[active_profile_name];
+        let known_files = self
+            .session
+            .active_instance
+            .as_ref()
+            .and_then(|instance_name| active_profile.instances.as_ref()?.get(instance_name))
+            .map(|instance| instance.files.clone())
+            .unwrap_or_default();
+        self.state.profile.base_file_changes = None;
         self.state.ui.current_dir = path.to_path_buf();
+        self.state.ui.dir_entries.clear();
         self.session.files.clear();
 
         self.session.files.extend(ignore::WalkBuilder::new(path).ignore(false).build().filter_map(|entry| {
             match entry {
                 Ok(e) if e.path() != path => Some((
                     e.path().to_path_buf(),
-                    core::profile::FileMetadata::default().with_source_path(e.path()).with_enabled(true),
+                    known_files.get(e.path()).cloned().unwrap_or_else(|| {
+                        core::profile::FileMetadata::default().with_source_path(e.path()).with_enabled(true)
+                    }),
                 )),
                 _ => None,
             }
@@ -237,41 +1172,306 @@ impl<'a> ProfileService<'a> {
         Ok(())
     }
 
-    fn try_set_mods_dir(&mut self, path: &Path) -> Result<(), ErrorContext> {
-        self.validate_context("Set Mods Dir", false)?;
+    fn try_rescan_game_dir(&mut self) -> Result<(), ErrorContext> {
+        self.validate_context("Rescan Game Dir", true)?;
+        let active_profile_name = self.session.active_profile.clone().unwrap();
+        let profile_path = self.state.profile.profiles[&active_profile_name].path.clone();
+        if profile_path.as_os_str().is_empty() || !profile_path.is_dir() {
+            return Err(ErrorContext::builder()
+                .error(error::Error::profile_service(
+                    format!("Game directory not found: {}", profile_path.display()),
+                    "Rescan Game Dir",
+                ))
+                .suggested_action("Set the game directory of the profile first")
+                .build());
+        }
 
-        tracing::info!("Setting mod storage directory to: {}", path.display());
-        self.session.mod_storage_dir = Some(path.to_path_buf());
-        std::fs::create_dir_all(path).map_err(|err| {
-            error::ErrorContext::builder()
-                .error(err.into())
-                .suggested_action("Check directory write permissions")
-                .build()
-        })?;
+        if self.session.active_instance.is_some() {
+            self.try_commit_changes()?;
+        }
+
+        self.clear_history();
+        let active_profile = &self.state.profile.profiles[&active_profile_name];
+
+        // Profiles set up before base files were recorded only know their paths from the instances.
+        let recorded = self.recorded_base_files(&active_profile_name)?;
+        let stamps_known = !recorded.is_empty();
+        let previous = match stamps_known {
+            true => recorded,
+            false => active_profile
+                .instances
+                .iter()
+                .flat_map(|instances| instances.values())
+                .flat_map(core::basefiles::instance_base_paths)
+                .map(|path| (path.clone(), core::basefiles::FileStamp::default()))
+                .collect(),
+        };
 
+        let current = self.scan_base_files(&active_profile_name, &previous)?;
+        let changes = core::basefiles::detect_changes(&previous, &current, stamps_known);
+        tracing::info!("Rescanned {}: {}", profile_path.display(), changes.summary());
+        self.record_base_files(&active_profile_name, &current)?;
+
+        let active_profile = self.state.profile.profiles.get_mut(&active_profile_name).unwrap();
+        active_profile
+            .instances
+            .iter_mut()
+            .flat_map(|instances| instances.values_mut())
+            .for_each(|instance| Self::rebuild_instance(instance, &current, &profile_path));
+
+        active_profile.fingerprint = Some(core::fingerprint::fingerprint(active_profile, &profile_path));
+        self.state.profile.base_file_changes = Some(changes);
+
+        // The staged entries belong to the old base files, they are reloaded from the instance.
+        self.session.files.clear();
+        self.state.ui.dir_entries.clear();
         Ok(())
     }
 
-    fn create_new_instance(name: &str, base_path: &Path) -> core::profile::Instance {
-        let base_files = ignore::WalkBuilder::new(base_path)
-            .ignore(false)
-            .build()
-            .filter_map(|e| match e {
-                Ok(e) if e.path() != base_path => Some((
-                    e.path().to_path_buf(),
+    fn try_plan_mods_dir_transfer(
+        &mut self,
+        path: &Path,
+    ) -> Result<core::storage::StorageTransfer, ErrorContext> {
+        self.validate_context("Set Mods Dir", true)?;
+        if self.state.mod_management.storage_transfer.is_some() {
+            return Err(ErrorContext::builder()
+                .error(error::Error::profile_service("Mods are still being transferred", "Set Mods Dir"))
+                .suggested_action("Wait for the current transfer to finish")
+                .build());
+        }
+
+        std::fs::create_dir_all(path)
+            .map_err(|err| error::ErrorContext::builder().error(err.into()).build())?;
+
+        let roots = self
+            .state
+            .profile
+            .profiles
+            .keys()
+            .map(|profile_name| core::storage::StorageRoot {
+                profile_name: profile_name.clone(),
+                from: core::helpers::mod_storage_root(self.session, profile_name),
+                to: core::helpers::mod_storage_root_in(Some(path), profile_name),
+            })
+            .filter(|root| root.from != root.to)
+            .collect();
+
+        let transfer = core::storage::StorageTransfer {
+            mode: core::storage::TransferMode::Copy,
+            mods_dir: path.to_path_buf(),
+            roots,
+        };
+
+        transfer.validate()?;
+        Ok(transfer)
+    }
+
+    /// Switches to the new mods directory once its files are in place and points every mod of
+    /// every profile to it.
+    fn try_set_mods_dir(&mut self, transfer: core::storage::StorageTransfer) -> Result<(), ErrorContext> {
+        if self.session.active_instance.is_some() {
+            self.try_commit_changes()?;
+        }
+
+        // Undo steps refer to the old storage and its trash directory.
+        self.clear_history();
+        tracing::info!("Setting mod storage directory to: {}", transfer.mods_dir.display());
+        self.session.mod_storage_dir = Some(transfer.mods_dir.clone());
+
+        for root in &transfer.roots {
+            if let Some(profile) = self.state.profile.profiles.get_mut(&root.profile_name) {
+                profile.rebase_paths(&root.from, &root.to);
+            }
+
+            if self.session.active_profile.as_ref() == Some(&root.profile_name) {
+                self.session
+                    .files
+                    .values_mut()
+                    .for_each(|metadata| metadata.rebase_paths(&root.from, &root.to));
+            }
+        }
+
+        self.state.ui.dir_entries.clear();
+
+        let missing = self
+            .state
+            .profile
+            .profiles
+            .values()
+            .flat_map(|profile| profile.instances.iter().flat_map(|instances| instances.values()))
+            .flat_map(|instance| instance.mods.iter())
+            .flat_map(|mod_info| std::iter::once(&mod_info.path).chain(mod_info.files.keys()))
+            .filter(|path| transfer.roots.iter().any(|root| path.starts_with(&root.to)))
+            .filter(|path| !path.exists())
+            .inspect(|path| tracing::warn!("Missing after changing the mods directory: {}", path.display()))
+            .count();
+
+        if missing > 0 {
+            return Err(ErrorContext::builder()
+                .error(error::Error::profile_service(
+                    format!("{missing} mod files are missing after changing the mods directory"),
+                    "Set Mods Dir",
+                ))
+                .suggested_action("Check the log for the missing files and reinstall the affected mods")
+                .build());
+        }
+
+        // The old storage is only removed once the saved profiles and session point to the new
+        // one, so that it is still found if the application exits before the next save.
+        if transfer.mode == core::storage::TransferMode::Move {
+            self.try_save_mods_dir()?;
+            transfer.remove_sources();
+        }
+
+        Ok(())
+    }
+
+    fn try_save_mods_dir(&self) -> Result<(), ErrorContext> {
+        let custom_path = self.session.custom_user_data_path.as_deref();
+        let save_error = |err: std::io::Error| {
+            ErrorContext::builder()
+                .error(
+                    error::Error::file_system(
+                        "Could not save the new mods directory, the old one was kept",
+                        "Set Mods Dir",
+                    )
+                    .with_source(&err),
+                )
+                .build()
+        };
+
+        for profile in self.state.profile.profiles.values() {
+            let mod_storage_root = core::helpers::mod_storage_root(self.session, &profile.name);
+            core::helpers::save_profile(&profile.to_portable(&mod_storage_root), custom_path)
+                .map_err(save_error)?;
+        }
+
+        core::helpers::save_app_session(self.session, custom_path).map_err(save_error)
+    }
+
+    /// Loads the base files recorded by the last scan of the game directory of a profile.
+    fn recorded_base_files(
+        &self,
+        profile_name: &str,
+    ) -> Result<Lookup<path::PathBuf, core::basefiles::FileStamp>, ErrorContext> {
+        let profile_dir =
+            core::helpers::profile_dir(profile_name, self.session.custom_user_data_path.as_deref());
+        core::basefiles::load_base_files(&profile_dir, &self.state.profile.profiles[profile_name].path)
+    }
+
+    fn record_base_files(
+        &self,
+        profile_name: &str,
+        base_files: &Lookup<path::PathBuf, core::basefiles::FileStamp>,
+    ) -> Result<(), ErrorContext> {
+        let profile_dir =
+            core::helpers::profile_dir(profile_name, self.session.custom_user_data_path.as_deref());
+        core::basefiles::save_base_files(
+            &profile_dir,
+            &self.state.profile.profiles[profile_name].path,
+            base_files,
+        )
+    }
+
+    /// Scans the game directory of a profile for its base files. Files deployed from the installed
+    /// mods or copied into the game directory from them are left out, unless `known_base_files`
+    /// lists them.
+    fn scan_base_files(
+        &self,
+        profile_name: &str,
+        known_base_files: &Lookup<path::PathBuf, core::basefiles::FileStamp>,
+    ) -> Result<Lookup<path::PathBuf, core::basefiles::FileStamp>, ErrorContext> {
+        let profile = &self.state.profile.profiles[profile_name];
+        let deployment =
+            core::deploy::Deployment::new(profile, self.session.custom_user_data_path.as_deref());
+        let manifest = deployment.load_manifest()?;
+
+        let mod_targets = profile
+            .instances
+            .iter()
+            .flat_map(|instances| instances.values())
+            .flat_map(|instance| instance.mods.iter())
+            .flat_map(|mod_info| {
+                mod_info
+                    .files
+                    .keys()
+                    .filter_map(|path| Some(profile.path.join(path.strip_prefix(&mod_info.path).ok()?)))
+            })
+            .collect::<hashbrown::HashSet<_>>();
+
+        // Deployed files are known exactly from the manifest, the mod targets only cover files
+        // copied into the game directory by other means.
+        let mut base_files = core::basefiles::scan_base_files(&profile.path);
+        deployment.restore_base_stamps(&manifest, &mut base_files);
+        base_files.retain(|path, _| known_base_files.contains_key(path) || !mod_targets.contains(path));
+        Ok(base_files)
+    }
+
+    fn create_new_instance(
+        name: &str,
+        base_files: &Lookup<path::PathBuf, core::basefiles::FileStamp>,
+    ) -> core::profile::Instance {
+        let base_files = base_files
+            .keys()
+            .map(|path| {
+                (
+                    path.clone(),
                     profile::FileMetadata::default()
-                        .with_source_path(e.path())
-                        .with_target_path(e.path())
+                        .with_source_path(path)
+                        .with_target_path(path)
                         .with_parent_name(name)
                         .with_enabled(true),
-                )),
-                _ => None,
+                )
             })
             .collect::<Lookup<path::PathBuf, profile::FileMetadata>>();
 
         core::profile::Instance::default().with_name(name).with_files(base_files)
     }
 
+    /// Rebuilds an instance on top of new base files: base entries are replaced, enabled mods
+    /// are applied again in load order and per-file toggles are carried over.
+    pub fn rebuild_instance(
+        instance: &mut core::profile::Instance,
+        base_files: &Lookup<path::PathBuf, core::basefiles::FileStamp>,
+        profile_path: &Path,
+    ) {
+        let core::profile::Instance { name, files, conflicts, mods, load_order } =
+            std::mem::replace(instance, Self::create_new_instance(&instance.name, base_files));
+        instance.name = name;
+        instance.mods = mods;
+        instance.load_order = load_order;
+
+        for mod_info in instance.mods.iter().filter(|m| m.enabled) {
+            services::mods::ModService::apply_mod_files(
+                &mut instance.files,
+                &mut instance.conflicts,
+                &mut instance.load_order,
+                profile_path,
+                mod_info,
+            );
+        }
+
+        let previous_toggles = files
+            .iter()
+            .chain(conflicts.iter().flat_map(|(path, providers)| providers.values().map(move |m| (path, m))))
+            .map(|(path, metadata)| ((path, &metadata.parent_name), metadata.enabled))
+            .collect::<Lookup<_, _>>();
+
+        instance
+            .files
+            .iter_mut()
+            .chain(
+                instance.conflicts.iter_mut().flat_map(|(path, providers)| {
+                    providers.values_mut().map(move |metadata| (path, metadata))
+                }),
+            )
+            .for_each(|(path, metadata)| {
+                if let Some(enabled) = previous_toggles.get(&(path, &metadata.parent_name)) {
+                    metadata.enabled = *enabled;
+                }
+            });
+    }
+
     fn validate_context(&self, operation: &str, ignore_instance: bool) -> Result<(), ErrorContext> {
         if self.session.active_profile.is_none() {
             Err(ErrorContext::builder()
@@ -288,3 +1488,362 @@ impl<'a> ProfileService<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::core::profile::FileMetadata;
+    use crate::core::profile::Instance;
+    use crate::core::profile::Lookup;
+    use crate::core::profile::ModInfo;
+    use crate::core::profile::Profile;
+    use crate::core::testing::TempDir;
+
+    fn game_dir(root: &TempDir, name: &str) -> PathBuf {
+        root.file(&format!("{name}/System/Gothic2.exe"), "");
+        root.file(&format!("{name}/System/Gothic.ini"), "");
+        root.dir(&format!("{name}/Data"));
+        root.path().join(name)
+    }
+
+    #[test]
+    fn changing_the_game_dir_moves_instance_paths_along() {
+        let root = TempDir::new();
+        let old_dir = game_dir(&root, "Old");
+        let new_dir = game_dir(&root, "New");
+        let mod_dir = root.dir("mods/Test/mods/Default/Patch");
+
+        let mod_file = FileMetadata::new(
+            true,
+            &mod_dir.join("Data/Patch.vdf"),
+            &old_dir.join("Data/Patch.vdf"),
+            "Patch",
+        );
+        let ini = old_dir.join("System/Gothic.ini");
+        let instance = Instance::new(
+            "Default",
+            Lookup::from_iter([(
+                ini.clone(),
+                FileMetadata::default().with_source_path(&ini).with_enabled(false),
+            )]),
+            vec![ModInfo::new(
+                true,
+                "Patch",
+                &mod_dir,
+                Lookup::from_iter([(mod_file.source_path.clone(), mod_file)]),
+            )],
+        );
+        let files = instance.files.clone();
+        let profile = Profile::new("Test", &old_dir)
+            .with_instances(Some(Lookup::from_iter([("Default".to_owned(), instance)])));
+
+        let mut session = session::ApplicationSession {
+            active_profile: Some("Test".to_owned()),
+            active_instance: Some("Default".to_owned()),
+            custom_user_data_path: Some(root.path().join("user")),
+            mod_storage_dir: Some(root.path().join("mods")),
+            files,
+            ..Default::default()
+        };
+        let mut state = state::ApplicationState::default();
+        state.profile.profiles.insert("Test".to_owned(), profile);
+
+        ProfileService::new(&mut session, &mut state).try_set_game_dir(&new_dir).unwrap();
+
+        let profile = &state.profile.profiles["Test"];
+        let instance = &profile.instances.as_ref().unwrap()["Default"];
+        assert_eq!(profile.path, new_dir);
+        assert_eq!(instance.mods[0].path, mod_dir);
+        let mod_file = instance.mods[0].files.values().next().unwrap();
+        assert_eq!(mod_file.target_path, new_dir.join("Data/Patch.vdf"));
+
+        let new_ini = new_dir.join("System/Gothic.ini");
+        assert!(!instance.files.keys().any(|path| path.starts_with(&old_dir)));
+        assert!(!instance.files[&new_ini].enabled);
+        assert_eq!(session.files[&new_ini], instance.files[&new_ini]);
+    }
+
+    #[test]
+    fn deleting_a_custom_profile_removes_its_directory_and_mods() {
+        let root = TempDir::new();
+        let profile_dir = root.dir("user/gothic-organizer-rs/Custom");
+        root.file("user/gothic-organizer-rs/Custom/profile.json", "{}");
+        root.file("user/gothic-organizer-rs/Custom/snapshots/Default/backup.json", "{}");
+        let storage_root = root.dir("mods/gothic-organizer-rs/Custom/mods");
+        root.file("mods/gothic-organizer-rs/Custom/mods/Default/Patch/Data/Patch.vdf", "patch");
+        let trash_dir = root.dir("mods/gothic-organizer-rs/Custom/trash/1/Old");
+
+        let mut session = session::ApplicationSession {
+            active_profile: Some("Custom".to_owned()),
+            custom_user_data_path: Some(root.path().join("user")),
+            mod_storage_dir: Some(root.path().join("mods")),
+            ..Default::default()
+        };
+        let mut state = state::ApplicationState::default();
+        let profile = Profile::new("Custom", &root.dir("game")).with_custom(true);
+        state.profile.profiles.insert("Custom".to_owned(), profile);
+
+        ProfileService::new(&mut session, &mut state).try_delete_profile("Custom").unwrap();
+
+        assert!(!profile_dir.exists() && !storage_root.exists() && !trash_dir.exists());
+        assert!(!state.profile.profiles.contains_key("Custom"));
+        assert_eq!(session.active_profile, None);
+    }
+
+    #[test]
+    fn default_profiles_can_not_be_deleted_or_reconfigured() {
+        let root = TempDir::new();
+        let profile_dir = root.dir("user/gothic-organizer-rs/Gothic II");
+        root.file("user/gothic-organizer-rs/Gothic II/profile.json", "{}");
+
+        let mut session = session::ApplicationSession {
+            active_profile: Some("Gothic II".to_owned()),
+            custom_user_data_path: Some(root.path().join("user")),
+            ..Default::default()
+        };
+        let mut state = state::ApplicationState::default();
+        let profile = Profile::new("Gothic II", &game_dir(&root, "Game"));
+        state.profile.profiles.insert("Gothic II".to_owned(), profile.clone());
+
+        let mut service = ProfileService::new(&mut session, &mut state);
+        assert!(service.try_delete_profile("Gothic II").is_err());
+        assert!(
+            service
+                .try_update_active_profile("Set Executable", |p| p.executable = Some("G.exe".into()))
+                .is_err()
+        );
+
+        assert!(profile_dir.join("profile.json").exists());
+        assert_eq!(state.profile.profiles["Gothic II"], profile);
+    }
+
+    #[test]
+    fn duplicating_an_instance_refuses_to_merge_into_leftover_mod_storage() {
+        let root = TempDir::new();
+        let mod_dir = root.dir("mods/gothic-organizer-rs/Test/mods/Default/Patch");
+        root.file("mods/gothic-organizer-rs/Test/mods/Default/Patch/Data/Patch.vdf", "patch");
+        root.file("mods/gothic-organizer-rs/Test/mods/Leftover/Stale/Data/Stale.vdf", "stale");
+
+        let instance = Instance::new(
+            "Default",
+            Lookup::default(),
+            vec![ModInfo::new(true, "Patch", &mod_dir, Lookup::default())],
+        );
+        let profile = Profile::new("Test", &game_dir(&root, "Game"))
+            .with_instances(Some(Lookup::from_iter([("Default".to_owned(), instance)])));
+
+        let mut session = session::ApplicationSession {
+            active_profile: Some("Test".to_owned()),
+            active_instance: Some("Default".to_owned()),
+            mod_storage_dir: Some(root.path().join("mods")),
+            ..Default::default()
+        };
+        let mut state = state::ApplicationState::default();
+        state.profile.profiles.insert("Test".to_owned(), profile);
+
+        let mut service = ProfileService::new(&mut session, &mut state);
+        assert!(service.try_duplicate_instance("Leftover").is_err());
+        service.try_duplicate_instance("Copy").unwrap();
+
+        let instances = state.profile.profiles["Test"].instances.as_ref().unwrap();
+        assert!(!instances.contains_key("Leftover"));
+        assert_eq!(
+            instances["Copy"].mods[0].path,
+            root.path().join("mods/gothic-organizer-rs/Test/mods/Copy/Patch")
+        );
+        assert!(root.path().join("mods/gothic-organizer-rs/Test/mods/Copy/Patch/Data/Patch.vdf").exists());
+        assert!(!root.path().join("mods/gothic-organizer-rs/Test/mods/Leftover/Patch").exists());
+    }
+
+    #[test]
+    fn exported_instances_are_imported_onto_the_local_game_files() {
+        let root = TempDir::new();
+        let exporting_game_dir = game_dir(&root, "Exporting");
+        root.file("Exporting/Data/Worlds.vdf", "");
+        let importing_game_dir = game_dir(&root, "Importing");
+        root.file("Importing/Data/Extra.vdf", "");
+
+        let mod_dir = root.dir("mods/gothic-organizer-rs/Exporting/mods/Default/Patch");
+        root.file("mods/gothic-organizer-rs/Exporting/mods/Default/Patch/Data/Patch.vdf", "patch");
+        let mod_file = mod_dir.join("Data/Patch.vdf");
+        let mod_info = ModInfo::new(
+            true,
+            "Patch",
+            &mod_dir,
+            Lookup::from_iter([
+                (
+                    mod_dir.join("Data"),
+                    FileMetadata::new(true, &mod_dir.join("Data"), Path::new(""), "Patch"),
+                ),
+                (mod_file.clone(), FileMetadata::new(true, &mod_file, Path::new(""), "Patch")),
+            ]),
+        );
+
+        let mut instance = Instance::new("Default", Lookup::default(), vec![mod_info]);
+        instance.load_order = Lookup::from_iter([("Default".to_owned(), 0), ("Patch".to_owned(), 1)]);
+        let base_files = core::basefiles::scan_base_files(&exporting_game_dir);
+        ProfileService::rebuild_instance(&mut instance, &base_files, &exporting_game_dir);
+
+        let exported_launch_options = session::GameLaunchConfiguration::default();
+        let mut local_launch_options = session::GameLaunchConfiguration::default();
+        local_launch_options.game_settings.is_marvin_mode_enabled = true;
+
+        let mut session = session::ApplicationSession {
+            active_profile: Some("Exporting".to_owned()),
+            active_instance: Some("Default".to_owned()),
+            mod_storage_dir: Some(root.path().join("mods")),
+            custom_user_data_path: Some(root.path().join("user")),
+            launch_options: Some(exported_launch_options),
+            files: instance.files.clone(),
+            ..Default::default()
+        };
+        let mut state = state::ApplicationState::default();
+        state.profile.export_include_mod_files = true;
+        state.profile.profiles.insert(
+            "Exporting".to_owned(),
+            Profile::new("Exporting", &exporting_game_dir)
+                .with_instances(Some(Lookup::from_iter([("Default".to_owned(), instance)]))),
+        );
+        state.profile.profiles.insert("Importing".to_owned(), Profile::new("Importing", &importing_game_dir));
+
+        let bundle_path = root.path().join("Default.zip");
+        ProfileService::new(&mut session, &mut state).try_export_instance(&bundle_path).unwrap();
+
+        session.active_profile = Some("Importing".to_owned());
+        session.active_instance = None;
+        session.launch_options = Some(local_launch_options.clone());
+        let instance_name =
+            ProfileService::new(&mut session, &mut state).try_import_instance(&bundle_path).unwrap();
+
+        let imported = &state.profile.profiles["Importing"].instances.as_ref().unwrap()[&instance_name];
+        let imported_mod_dir = root.path().join("mods/gothic-organizer-rs/Importing/mods/Default/Patch");
+        assert_eq!(imported.mods[0].path, imported_mod_dir);
+        assert!(imported_mod_dir.join("Data/Patch.vdf").exists());
+
+        assert!(imported.files.contains_key(&importing_game_dir.join("Data/Extra.vdf")));
+        assert!(!imported.files.contains_key(&importing_game_dir.join("Data/Worlds.vdf")));
+        assert_eq!(imported.files[&importing_game_dir.join("Data/Patch.vdf")].parent_name, "Patch");
+        assert!(imported.files.keys().all(|path| path.starts_with(&importing_game_dir)));
+        assert_eq!(session.launch_options, Some(local_launch_options));
+    }
+
+    #[test]
+    fn restoring_a_saved_snapshot_brings_the_instance_back() {
+        let root = TempDir::new();
+        let game_dir = game_dir(&root, "Game");
+        let mod_dir = root.dir("mods/gothic-organizer-rs/Test/mods/Default/Patch");
+        let mod_source =
+            root.file("mods/gothic-organizer-rs/Test/mods/Default/Patch/Data/Patch.vdf", "patch");
+
+        let mod_file = FileMetadata::new(true, &mod_source, &game_dir.join("Data/Patch.vdf"), "Patch");
+        let ini = game_dir.join("System/Gothic.ini");
+        let mut instance = Instance::new(
+            "Default",
+            Lookup::from_iter([
+                (ini.clone(), FileMetadata::new(false, &ini, &ini, "Default")),
+                (mod_file.target_path.clone(), mod_file.clone()),
+            ]),
+            vec![ModInfo::new(true, "Patch", &mod_dir, Lookup::from_iter([(mod_source.clone(), mod_file)]))],
+        );
+        instance.load_order.insert("Patch".to_owned(), 1);
+        let profile = Profile::new("Test", &game_dir)
+            .with_instances(Some(Lookup::from_iter([("Default".to_owned(), instance.clone())])));
+
+        let mut session = session::ApplicationSession {
+            active_profile: Some("Test".to_owned()),
+            active_instance: Some("Default".to_owned()),
+            custom_user_data_path: Some(root.path().join("user")),
+            mod_storage_dir: Some(root.path().join("mods")),
+            files: instance.files.clone(),
+            ..Default::default()
+        };
+        let mut state = state::ApplicationState::default();
+        state.profile.profiles.insert("Test".to_owned(), profile);
+
+        ProfileService::new(&mut session, &mut state).try_create_snapshot("Backup").unwrap();
+        let snapshot_path = state.profile.snapshots[0].path.clone();
+        assert_eq!(core::snapshot::Snapshot::load(&snapshot_path).unwrap().name, "Backup");
+
+        let changed = Instance::new("Default", Lookup::default(), Vec::new());
+        state
+            .profile
+            .profiles
+            .get_mut("Test")
+            .unwrap()
+            .instances
+            .as_mut()
+            .unwrap()
+            .insert("Default".to_owned(), changed);
+        session.files.clear();
+
+        ProfileService::new(&mut session, &mut state).try_restore_snapshot(&snapshot_path).unwrap();
+
+        assert_eq!(state.profile.profiles["Test"].instances.as_ref().unwrap()["Default"], instance);
+    }
+
+    #[test]
+    fn rescanning_rebuilds_instances_on_the_changed_base_files() {
+        let root = TempDir::new();
+        let game_dir = game_dir(&root, "Game");
+        root.file("Game/Data/Base.vdf", "base");
+        let mod_dir = root.dir("mods/gothic-organizer-rs/Test/mods/Default/Patch");
+        let mod_file = root.file("mods/gothic-organizer-rs/Test/mods/Default/Patch/Data/Base.vdf", "patch");
+        let mod_info = ModInfo::new(
+            true,
+            "Patch",
+            &mod_dir,
+            Lookup::from_iter([(
+                mod_file.clone(),
+                FileMetadata::new(true, &mod_file, Path::new(""), "Patch"),
+            )]),
+        );
+
+        let mut instance = Instance::new("Default", Lookup::default(), vec![mod_info]);
+        instance.load_order = Lookup::from_iter([("Default".to_owned(), 0), ("Patch".to_owned(), 1)]);
+        ProfileService::rebuild_instance(
+            &mut instance,
+            &core::basefiles::scan_base_files(&game_dir),
+            &game_dir,
+        );
+        let ini = game_dir.join("System/Gothic.ini");
+        instance.files.get_mut(&ini).unwrap().enabled = false;
+
+        let mut session = session::ApplicationSession {
+            active_profile: Some("Test".to_owned()),
+            custom_user_data_path: Some(root.path().join("user")),
+            mod_storage_dir: Some(root.path().join("mods")),
+            ..Default::default()
+        };
+        let mut state = state::ApplicationState::default();
+        let profile = Profile::new("Test", &game_dir)
+            .with_instances(Some(Lookup::from_iter([("Default".to_owned(), instance)])));
+        state.profile.profiles.insert("Test".to_owned(), profile);
+
+        ProfileService::new(&mut session, &mut state).try_rescan_game_dir().unwrap();
+        assert!(state.profile.base_file_changes.as_ref().unwrap().is_empty());
+
+        root.file("Game/Data/Added.vdf", "added");
+        root.file("Game/Data/Base.vdf", "patched base");
+
+        // New instances start from the game directory as it is, not from the last rescan.
+        ProfileService::new(&mut session, &mut state).try_add_instance("Fresh").unwrap();
+        let fresh = &state.profile.profiles["Test"].instances.as_ref().unwrap()["Fresh"];
+        assert!(fresh.files.contains_key(&game_dir.join("Data/Added.vdf")));
+
+        ProfileService::new(&mut session, &mut state).try_rescan_game_dir().unwrap();
+        let changes = state.profile.base_file_changes.as_ref().unwrap();
+        assert_eq!(changes.added, [game_dir.join("Data/Added.vdf")]);
+        assert_eq!(changes.changed, [game_dir.join("Data/Base.vdf")]);
+
+        let instance = &state.profile.profiles["Test"].instances.as_ref().unwrap()["Default"];
+        assert!(instance.files.contains_key(&game_dir.join("Data/Added.vdf")));
+        assert_eq!(instance.files[&game_dir.join("Data/Base.vdf")].parent_name, "Patch");
+        assert!(instance.conflicts.entries.contains_key(&game_dir.join("Data/Base.vdf")));
+        assert!(!instance.files[&ini].enabled);
+
+        ProfileService::new(&mut session, &mut state).try_rescan_game_dir().unwrap();
+        assert!(state.profile.base_file_changes.as_ref().unwrap().is_empty());
+    }
+}
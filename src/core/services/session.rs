@@ -1,8 +1,13 @@
+use std::path::Path;
+use std::path::PathBuf;
+
 use iced::Task;
 
 use crate::app::message;
+use crate::app::session;
 use crate::app::window::ApplicationWindow;
 use crate::app::window::WindowInfo;
+use crate::core::helpers;
 use crate::error;
 use crate::save_app_session;
 use crate::save_profile;
@@ -29,10 +34,13 @@ impl<'a> SessionService<'a> {
 
         self.state.profile.profiles.values().for_each(|p| {
             tracing::info!("Saving profile: {}", p.name);
-            if let Err(e) = save_profile!(p, user_data_dir) {
+            let mod_storage_root = crate::core::helpers::mod_storage_root(self.session, &p.name);
+            if let Err(e) = save_profile!(&p.to_portable(&mod_storage_root), user_data_dir) {
                 tracing::error!("Failed saving {} profile: {e}", p.name);
-                profile_saving_error_tasks.push(Task::done(
-                    message::ErrorMessage::Handle(error::ErrorContext::from(error::Error::from(e))).into(),
+                profile_saving_error_tasks.push(save_error(
+                    &e,
+                    &format!("Could not save profile {}", p.name),
+                    "Save Profile",
                 ));
             }
         });
@@ -40,12 +48,22 @@ impl<'a> SessionService<'a> {
         tracing::info!("Saving session");
         if let Err(e) = save_app_session!(self.session, self.session.custom_user_data_path.as_deref()) {
             tracing::error!("Failed saving session: {e}");
-            session_save_error_task = Task::done(
-                message::ErrorMessage::Handle(error::ErrorContext::from(error::Error::from(e))).into(),
-            );
+            session_save_error_task = save_error(&e, "Could not save session", "Save Session");
         }
 
-        Task::batch(profile_saving_error_tasks).chain(session_save_error_task)
+        tracing::info!("Saving error history");
+        let history_save_error_task = match crate::core::helpers::save_error_history(
+            &self.state.errors.error_history,
+            self.session.custom_user_data_path.as_deref(),
+        ) {
+            Ok(()) => Task::none(),
+            Err(e) => {
+                tracing::error!("Failed saving error history: {e}");
+                save_error(&e, "Could not save error history", "Save Error History")
+            }
+        };
+
+        Task::batch(profile_saving_error_tasks).chain(session_save_error_task).chain(history_save_error_task)
     }
 
     /// Exits the application if all windows are closed or if the editor window is closed otherwise
@@ -65,6 +83,8 @@ impl<'a> SessionService<'a> {
 
         if all_closed || main_window_closed {
             tracing::info!("Exiting application");
+            // Mods kept for undoing are deleted for good once the history is gone.
+            crate::core::services::history::HistoryService::new(self.session, self.state).clear();
             let custom_path = self.session.custom_user_data_path.as_deref();
             return self.save_current_session(custom_path).chain(iced::exit());
         }
@@ -105,4 +125,116 @@ impl<'a> SessionService<'a> {
         self.state.ui.windows.insert(id, WindowInfo { window_type: *window, is_closed: false });
         task.then(|_| Task::none())
     }
+
+    pub fn set_log_level(&mut self, level: session::LogLevel) -> Task<message::Message> {
+        if let Err(err) = crate::logger::set_level(level.into()) {
+            return Task::done(message::ErrorMessage::Handle(err).into());
+        }
+
+        self.session.log_settings.level = level;
+        Task::none()
+    }
+
+    /// Switches logging to another directory, or back to the default one if `directory` is empty.
+    /// Asks for the directory if none is given.
+    pub fn set_log_dir(&mut self, directory: Option<PathBuf>) -> Task<message::Message> {
+        let directory = match directory {
+            Some(directory) => Some(directory).filter(|directory| !directory.as_os_str().is_empty()),
+            None => match rfd::FileDialog::new().set_title("Select log directory").pick_folder() {
+                Some(directory) => Some(directory),
+                None => {
+                    tracing::warn!("No path selected");
+                    return Task::none();
+                }
+            },
+        };
+
+        let mut settings = self.session.log_settings.clone();
+        settings.directory = directory;
+        let config = helpers::log_file_config(&settings, self.session.custom_user_data_path.as_deref());
+        if let Err(err) = crate::logger::set_log_file(config) {
+            return Task::done(message::ErrorMessage::Handle(err).into());
+        }
+
+        self.state.settings.log_dir_field =
+            settings.directory.as_ref().map(|directory| directory.display().to_string()).unwrap_or_default();
+        self.session.log_settings = settings;
+        Task::none()
+    }
+
+    /// Writes a diagnostics bundle for the active profile and instance. Asks where to save it if
+    /// no path is given.
+    pub fn export_diagnostics(&mut self, path: Option<PathBuf>) -> Task<message::Message> {
+        let Some(path) = path.or_else(|| {
+            rfd::FileDialog::new()
+                .set_title("Export diagnostics")
+                .set_file_name(crate::core::diagnostics::default_file_name())
+                .add_filter("Diagnostics bundle", &["zip"])
+                .save_file()
+        }) else {
+            tracing::warn!("No path selected");
+            return Task::none();
+        };
+
+        // Pending file toggles of the active instance have to be part of the conflict report.
+        let commit = match self.session.active_instance.is_some() {
+            true => crate::core::services::profile::ProfileService::new(self.session, self.state)
+                .commit_session_files(),
+            false => Task::none(),
+        };
+
+        let profile = self
+            .session
+            .active_profile
+            .as_ref()
+            .and_then(|name| self.state.profile.profiles.get(name))
+            .cloned();
+        let instance = profile
+            .as_ref()
+            .and_then(|profile| profile.instances.as_ref())
+            .zip(self.session.active_instance.as_ref())
+            .and_then(|(instances, name)| instances.get(name))
+            .cloned();
+        let session = self.session.clone();
+
+        // Reading the logs and running the version commands takes a while, the bundle is written
+        // off the UI thread from a copy of the current state.
+        let export = Task::perform(
+            async move {
+                let source = crate::core::diagnostics::DiagnosticsSource {
+                    session: &session,
+                    profile: profile.as_ref(),
+                    instance: instance.as_ref(),
+                };
+                let result = crate::core::diagnostics::write_diagnostics(&path, &source)
+                    .map(|report| report.files.len() + 1);
+                (path, result)
+            },
+            |(path, result)| message::LogsMessage::DiagnosticsExported(path, result).into(),
+        );
+
+        commit.chain(export)
+    }
+
+    pub fn finish_diagnostics_export(
+        &mut self,
+        path: &Path,
+        result: Result<usize, error::ErrorContext>,
+    ) -> Task<message::Message> {
+        match result {
+            Ok(file_count) => {
+                tracing::info!("Diagnostics written to {} ({file_count} files)", path.display());
+                Task::none()
+            }
+            Err(err) => Task::done(message::ErrorMessage::Handle(err).into()),
+        }
+    }
+}
+
+/// Reports a failed save, suggesting a remedy based on the kind of the error.
+fn save_error(err: &std::io::Error, msg: &str, operation: &str) -> Task<message::Message> {
+    let error = error::ErrorContext::builder()
+        .error(error::Error::file_system(msg, operation).with_source(err))
+        .build();
+    Task::done(message::ErrorMessage::Handle(error).into())
 }
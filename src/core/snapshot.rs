@@ -0,0 +1,178 @@
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use chrono::Local;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::core::bundle;
+use crate::core::constants;
+use crate::core::profile::Instance;
+use crate::error::Error;
+use crate::error::ErrorContext;
+
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+pub const SNAPSHOTS_DIR_NAME: &str = "snapshots";
+pub const SNAPSHOT_EXTENSION: &str = "json";
+
+/// A named copy of the full state of an instance that can be restored later.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub format_version: u32,
+    pub app_version: String,
+    pub name: String,
+    pub note: String,
+    pub created: DateTime<Local>,
+    /// The instance with its paths in the portable form of [`bundle::to_portable_path`], so that
+    /// renaming the instance or moving the game directory does not invalidate the snapshot.
+    pub instance: Instance,
+}
+
+/// The part of a snapshot shown in the snapshot list, without the instance itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotInfo {
+    pub path: PathBuf,
+    pub name: String,
+    pub note: String,
+    pub created: DateTime<Local>,
+    pub mod_count: usize,
+}
+
+impl Snapshot {
+    pub fn new(name: &str, note: &str, instance: &Instance, game_dir: &Path, mod_storage_dir: &Path) -> Self {
+        let mut instance = instance.clone();
+        instance.map_paths(&|path| bundle::to_portable_path(path, game_dir, mod_storage_dir));
+
+        Self {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            app_version: constants::APP_VERSION.to_owned(),
+            name: name.to_owned(),
+            note: note.to_owned(),
+            created: Local::now(),
+            instance,
+        }
+    }
+
+    /// Returns the captured instance with its paths resolved against the local directories.
+    pub fn local_instance(&self, instance_name: &str, game_dir: &Path, mod_storage_dir: &Path) -> Instance {
+        let mut instance = self.instance.clone();
+        instance.map_paths(&|path| bundle::from_portable_path(path, game_dir, mod_storage_dir));
+        instance.rename(instance_name);
+        instance
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ErrorContext> {
+        let content = std::fs::read_to_string(path).map_err(|e| snapshot_error(e, path, "Load Snapshot"))?;
+        let snapshot: Snapshot =
+            serde_json::from_str(&content).map_err(|e| snapshot_error(e, path, "Load Snapshot"))?;
+
+        if snapshot.format_version > SNAPSHOT_FORMAT_VERSION {
+            return Err(ErrorContext::builder()
+                .error(Error::profile_service(
+                    format!("Unsupported snapshot format version {}", snapshot.format_version),
+                    "Load Snapshot",
+                ))
+                .suggested_action("Update the application to restore this snapshot")
+                .build());
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Writes the snapshot into `dir` under a file name made of its creation time and name. A
+    /// counter is appended when a snapshot of the same name was saved within the same second.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, ErrorContext> {
+        let stem = format!(
+            "{}-{}",
+            self.created.format("%Y%m%d-%H%M%S"),
+            self.name.replace(|c: char| !c.is_alphanumeric() && c != '-' && c != '_', "_")
+        );
+
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| snapshot_error(e, &dir.join(&stem), "Save Snapshot"))?;
+        std::fs::create_dir_all(dir).map_err(|e| snapshot_error(e, dir, "Save Snapshot"))?;
+
+        for counter in 1.. {
+            let path = match counter {
+                1 => dir.join(format!("{stem}.{SNAPSHOT_EXTENSION}")),
+                _ => dir.join(format!("{stem}-{counter}.{SNAPSHOT_EXTENSION}")),
+            };
+
+            match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(content.as_bytes())
+                        .map_err(|e| snapshot_error(e, &path, "Save Snapshot"))?;
+                    return Ok(path);
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(snapshot_error(err, &path, "Save Snapshot")),
+            }
+        }
+
+        unreachable!("ran out of snapshot file names")
+    }
+
+    pub fn info(&self, path: &Path) -> SnapshotInfo {
+        SnapshotInfo {
+            path: path.to_path_buf(),
+            name: self.name.clone(),
+            note: self.note.clone(),
+            created: self.created,
+            mod_count: self.instance.mods.len(),
+        }
+    }
+}
+
+/// Lists the readable snapshots in `dir`, newest first. Unreadable files are skipped with a
+/// warning.
+pub fn list_snapshots(dir: &Path) -> Vec<SnapshotInfo> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut snapshots = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == SNAPSHOT_EXTENSION))
+        .filter_map(|path| match Snapshot::load(&path) {
+            Ok(snapshot) => Some(snapshot.info(&path)),
+            Err(err) => {
+                tracing::warn!("Skipping snapshot {}: {}", path.display(), err.error);
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    snapshots.sort_unstable_by_key(|snapshot| std::cmp::Reverse(snapshot.created));
+    snapshots
+}
+
+fn snapshot_error(err: impl std::error::Error + 'static, path: &Path, operation: &str) -> ErrorContext {
+    ErrorContext::builder()
+        .error(
+            Error::file_system("Could not access the snapshot", operation).with_path(path).with_source(&err),
+        )
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::testing::TempDir;
+
+    #[test]
+    fn snapshots_saved_within_the_same_second_get_distinct_files() {
+        let root = TempDir::new();
+        let snapshot =
+            Snapshot::new("Backup", "", &Instance::default(), Path::new("/game"), Path::new("/mods"));
+
+        let first = snapshot.save(root.path()).unwrap();
+        let second = snapshot.save(root.path()).unwrap();
+
+        assert_ne!(first, second);
+        assert_eq!(Snapshot::load(&first).unwrap(), snapshot);
+        assert_eq!(list_snapshots(root.path()).len(), 2);
+    }
+}
@@ -0,0 +1,237 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use iced::futures::SinkExt;
+use iced::futures::Stream;
+
+use crate::core::modlist;
+use crate::error::Error;
+use crate::error::ErrorContext;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferMode {
+    /// Copies the mods and removes the old storage afterwards.
+    Move,
+    /// Copies the mods and leaves the old storage in place.
+    Copy,
+}
+
+/// Mod storage root of a single profile before and after changing the mods directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageRoot {
+    pub profile_name: String,
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// Moving or copying the mod storage of every profile into a new mods directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageTransfer {
+    pub mode: TransferMode,
+    pub mods_dir: PathBuf,
+    pub roots: Vec<StorageRoot>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransferProgress {
+    pub done: usize,
+    pub total: usize,
+    pub current: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub enum TransferEvent {
+    Progress(TransferProgress),
+    Finished(Result<(), ErrorContext>),
+}
+
+impl StorageTransfer {
+    /// Refuses targets that already hold files or that overlap the storage they are copied from.
+    pub fn validate(&self) -> Result<(), ErrorContext> {
+        for root in self.roots.iter().filter(|root| has_files(&root.from)) {
+            if root.to.starts_with(&root.from) || root.from.starts_with(&root.to) {
+                return Err(storage_error(
+                    format!("{} and {} overlap", root.from.display(), root.to.display()),
+                    "Choose a mods directory outside of the current one",
+                ));
+            }
+
+            if has_files(&root.to) {
+                return Err(storage_error(
+                    format!("{} already contains mods of profile {}", root.to.display(), root.profile_name),
+                    "Choose an empty mods directory",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether any profile has installed mods that would be left behind.
+    pub fn has_files(&self) -> bool {
+        self.roots.iter().any(|root| has_files(&root.from))
+    }
+
+    /// Copies every storage root, reporting progress after each file, and verifies the copies.
+    /// The old storage is left in place, see [`StorageTransfer::remove_sources`].
+    pub fn run(self) -> impl Stream<Item = TransferEvent> {
+        iced::stream::channel(64, move |mut sender| async move {
+            let result = self.transfer(&mut sender).await;
+            let _ = sender.send(TransferEvent::Finished(result)).await;
+        })
+    }
+
+    async fn transfer(
+        &self,
+        sender: &mut iced::futures::channel::mpsc::Sender<TransferEvent>,
+    ) -> Result<(), ErrorContext> {
+        let files = self
+            .roots
+            .iter()
+            .flat_map(|root| list_files(&root.from).into_iter().map(move |file| (root, file)))
+            .collect::<Vec<_>>();
+
+        let mut progress = TransferProgress { done: 0, total: files.len(), current: PathBuf::new() };
+        for (root, file) in &files {
+            let target = root.to.join(file.strip_prefix(&root.from).unwrap_or(file));
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent).map_err(|err| {
+                    ErrorContext::builder()
+                        .error(
+                            Error::file_system("Could not create directory", "Set Mods Dir")
+                                .with_path(parent)
+                                .with_source(&err),
+                        )
+                        .build()
+                })?;
+            }
+
+            std::fs::copy(file, &target).map_err(|err| {
+                ErrorContext::builder()
+                    .error(
+                        Error::file_system("Could not copy file", "Set Mods Dir")
+                            .with_path(file)
+                            .with_source(&err),
+                    )
+                    .build()
+            })?;
+
+            progress.done += 1;
+            progress.current = target;
+            let _ = sender.send(TransferEvent::Progress(progress.clone())).await;
+        }
+
+        for (root, file) in &files {
+            let target = root.to.join(file.strip_prefix(&root.from).unwrap_or(file));
+            let hash = |path: &Path| modlist::source_hash(path).ok();
+            if hash(file).is_none() || hash(file) != hash(&target) {
+                return Err(storage_error(
+                    format!("{} does not match {}", target.display(), file.display()),
+                    "The old mods directory was kept, try again",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the storage the mods were copied from in [`TransferMode::Move`]. Must only be
+    /// called once the application uses the new mods directory, so that a failed switch still
+    /// finds its mods.
+    pub fn remove_sources(&self) {
+        if self.mode != TransferMode::Move {
+            return;
+        }
+
+        for root in self.roots.iter().filter(|root| root.from.exists()) {
+            tracing::info!("Removing {}", root.from.display());
+            if let Err(err) = std::fs::remove_dir_all(&root.from) {
+                tracing::warn!("Could not remove {}: {err}", root.from.display());
+            }
+        }
+    }
+}
+
+fn list_files(dir: &Path) -> Vec<PathBuf> {
+    ignore::WalkBuilder::new(dir)
+        .standard_filters(false)
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|file_type| file_type.is_file()))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+fn has_files(dir: &Path) -> bool {
+    dir.is_dir() && !list_files(dir).is_empty()
+}
+
+fn storage_error(msg: impl Into<String>, suggested_action: &str) -> ErrorContext {
+    ErrorContext::builder()
+        .error(Error::file_system(msg, "Set Mods Dir"))
+        .suggested_action(suggested_action)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use iced::futures::StreamExt;
+
+    use super::*;
+    use crate::core::testing::TempDir;
+
+    fn transfer(root: &TempDir, mode: TransferMode) -> StorageTransfer {
+        root.file("old/Test/mods/Default/Patch/Data/Patch.vdf", "patch");
+        StorageTransfer {
+            mode,
+            mods_dir: root.path().join("new"),
+            roots: vec![StorageRoot {
+                profile_name: "Test".to_owned(),
+                from: root.path().join("old/Test/mods"),
+                to: root.path().join("new/Test/mods"),
+            }],
+        }
+    }
+
+    fn finish(transfer: &StorageTransfer) -> Result<(), ErrorContext> {
+        let events = iced::futures::executor::block_on(transfer.clone().run().collect::<Vec<_>>());
+        match events.into_iter().last() {
+            Some(TransferEvent::Finished(result)) => result,
+            event => panic!("transfer did not finish: {event:?}"),
+        }
+    }
+
+    #[test]
+    fn moved_storage_is_kept_until_the_sources_are_removed() {
+        let root = TempDir::new();
+        let transfer = transfer(&root, TransferMode::Move);
+
+        finish(&transfer).unwrap();
+        let copy = root.path().join("new/Test/mods/Default/Patch/Data/Patch.vdf");
+        assert_eq!(std::fs::read_to_string(copy).unwrap(), "patch");
+        assert!(transfer.roots[0].from.exists());
+
+        transfer.remove_sources();
+        assert!(!transfer.roots[0].from.exists());
+    }
+
+    #[test]
+    fn copied_storage_is_never_removed() {
+        let root = TempDir::new();
+        let transfer = transfer(&root, TransferMode::Copy);
+
+        finish(&transfer).unwrap();
+        transfer.remove_sources();
+
+        assert!(transfer.roots[0].from.exists());
+    }
+
+    #[test]
+    fn targets_with_mods_are_refused() {
+        let root = TempDir::new();
+        let transfer = transfer(&root, TransferMode::Move);
+        root.file("new/Test/mods/Default/Other/Data/Other.vdf", "other");
+
+        assert!(transfer.validate().is_err());
+    }
+}
@@ -1,4 +1,6 @@
+use std::io::Write;
 use std::path::Path;
+use std::path::PathBuf;
 
 use zip::ZipArchive;
 
@@ -30,18 +32,20 @@ pub fn copy_recursive(src: &Path, dst: &Path) -> Result<(), ErrorContext> {
 
             std::fs::create_dir_all(parent_path).map_err(|e| {
                 ErrorContext::builder()
-                    .error(Error::file_system(e.to_string(), "Create Directory"))
-                    .suggested_action(
-                        "Check if the mod storage directory is writable or permissions are set correctly.",
+                    .error(
+                        Error::file_system("Could not create directory", "Create Directory")
+                            .with_path(parent_path)
+                            .with_source(&e),
                     )
                     .build()
             })?;
 
             std::fs::copy(entry.path(), &dst_path).map_err(|e| {
                 ErrorContext::builder()
-                    .error(Error::file_system(e.to_string(), "Copy File"))
-                    .suggested_action(
-                        "Check if the mod storage directory is writable or permissions are set correctly.",
+                    .error(
+                        Error::file_system("Could not copy file", "Copy File")
+                            .with_path(&dst_path)
+                            .with_source(&e),
                     )
                     .build()
             })?;
@@ -49,10 +53,7 @@ pub fn copy_recursive(src: &Path, dst: &Path) -> Result<(), ErrorContext> {
     } else {
         std::fs::copy(src, dst).map_err(|e| {
             ErrorContext::builder()
-                .error(Error::file_system(e.to_string(), "Copy File"))
-                .suggested_action(
-                    "Check if the mod storage directory is writable or permissions are set correctly.",
-                )
+                .error(Error::file_system("Could not copy file", "Copy File").with_path(dst).with_source(&e))
                 .build()
         })?;
     }
@@ -62,15 +63,21 @@ pub fn copy_recursive(src: &Path, dst: &Path) -> Result<(), ErrorContext> {
 pub fn extract_zip(zip_path: &Path, dst_path: &Path) -> Result<(), ErrorContext> {
     let handle = std::fs::File::open(zip_path).map_err(|e| {
         ErrorContext::builder()
-            .error(Error::file_system(e.to_string(), "Extract Zip"))
-            .suggested_action("Check if the file is readable or permissions are set correctly.")
+            .error(
+                Error::file_system("Could not open archive", "Extract Zip")
+                    .with_path(zip_path)
+                    .with_source(&e),
+            )
             .build()
     })?;
 
     let mut archive = ZipArchive::new(handle).map_err(|e| {
         ErrorContext::builder()
-            .error(Error::file_system(e.to_string(), "Extract Zip"))
-            .suggested_action("Check if the zip file is valid and not corrupted.")
+            .error(
+                Error::file_system("Could not read archive", "Extract Zip")
+                    .with_path(zip_path)
+                    .with_source(&e),
+            )
             .build()
     })?;
 
@@ -78,8 +85,11 @@ pub fn extract_zip(zip_path: &Path, dst_path: &Path) -> Result<(), ErrorContext>
     for i in 0..archive.len() {
         let mut file = archive.by_index(i).map_err(|e| {
             ErrorContext::builder()
-                .error(Error::file_system(e.to_string(), "Extract Zip"))
-                .suggested_action("Check if the zip file is valid and not corrupted.")
+                .error(
+                    Error::file_system("Could not read archive entry", "Extract Zip")
+                        .with_path(zip_path)
+                        .with_source(&e),
+                )
                 .build()
         })?;
 
@@ -91,9 +101,10 @@ pub fn extract_zip(zip_path: &Path, dst_path: &Path) -> Result<(), ErrorContext>
         if file.is_dir() {
             std::fs::create_dir_all(&output_path).map_err(|e| {
                 ErrorContext::builder()
-                    .error(Error::file_system(e.to_string(), "Extract Zip"))
-                    .suggested_action(
-                        "Check if the mod storage directory is writable or permissions are set correctly.",
+                    .error(
+                        Error::file_system("Could not create directory", "Extract Zip")
+                            .with_path(&output_path)
+                            .with_source(&e),
                     )
                     .build()
             })?;
@@ -101,27 +112,114 @@ pub fn extract_zip(zip_path: &Path, dst_path: &Path) -> Result<(), ErrorContext>
             if let Some(parent) = output_path.parent() {
                 std::fs::create_dir_all(parent).map_err(|e| {
                     ErrorContext::builder()
-                    .error(Error::file_system(e.to_string(), "Extract Zip"))
-                    .suggested_action(
-                        "Check if the mod storage directory is writable or permissions are set correctly.",
-                    )
-                    .build()
+                        .error(
+                            Error::file_system("Could not create directory", "Extract Zip")
+                                .with_path(parent)
+                                .with_source(&e),
+                        )
+                        .build()
                 })?;
             }
 
             let mut output_file = std::fs::File::create(&output_path).map_err(|e| {
                 ErrorContext::builder()
-                    .error(Error::file_system(e.to_string(), "Extract Zip"))
-                    .suggested_action(
-                        "Check if the mod storage directory is writable or permissions are set correctly.",
+                    .error(
+                        Error::file_system("Could not create file", "Extract Zip")
+                            .with_path(&output_path)
+                            .with_source(&e),
                     )
                     .build()
             })?;
 
             std::io::copy(&mut file, &mut output_file).map_err(|e| {
-                ErrorContext::builder().error(Error::file_system(e.to_string(), "Extract Zip")).build()
+                ErrorContext::builder()
+                    .error(
+                        Error::file_system("Could not extract file", "Extract Zip")
+                            .with_path(&output_path)
+                            .with_source(&e),
+                    )
+                    .build()
             })?;
         }
     }
     Ok(())
 }
+
+/// Moves a directory, falling back to copying and removing it when `src` and `dst` are on
+/// different file systems.
+pub fn move_dir(src: &Path, dst: &Path) -> Result<(), ErrorContext> {
+    if let Some(parent) = dst.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            ErrorContext::builder()
+                .error(
+                    Error::file_system("Could not create directory", "Move Directory")
+                        .with_path(parent)
+                        .with_source(&e),
+                )
+                .build()
+        })?;
+    }
+
+    if std::fs::rename(src, dst).is_ok() {
+        return Ok(());
+    }
+
+    copy_recursive(src, dst)?;
+    std::fs::remove_dir_all(src).map_err(|e| {
+        ErrorContext::builder()
+            .error(
+                Error::file_system("Could not remove directory", "Move Directory")
+                    .with_path(src)
+                    .with_source(&e),
+            )
+            .suggested_action("Make sure no other program is using the source directory.")
+            .build()
+    })
+}
+
+/// Returns the path of the `index`-th backup of a file, `1` being the newest.
+pub fn backup_path(path: &Path, index: usize) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".bak.{index}"));
+    path.with_file_name(file_name)
+}
+
+/// Replaces a file without ever leaving it half-written. The contents are written to a temporary
+/// file and synced to disk before it is renamed over the target. Up to `backups` previous versions
+/// are kept next to the file, see [`backup_path`].
+pub fn write_atomic(path: &Path, contents: &[u8], backups: usize) -> std::io::Result<()> {
+    // Rewriting identical contents would only push older versions out of the backups.
+    if std::fs::read(path).is_ok_and(|current| current == contents) {
+        return Ok(());
+    }
+
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let mut temp_file = std::fs::File::create(&temp_path)?;
+    temp_file.write_all(contents)?;
+    temp_file.sync_all()?;
+    drop(temp_file);
+
+    if backups > 0 && path.is_file() {
+        for index in (1..backups).rev() {
+            let older = backup_path(path, index);
+            if older.is_file() {
+                std::fs::rename(&older, backup_path(path, index + 1))?;
+            }
+        }
+
+        std::fs::copy(path, backup_path(path, 1))?;
+    }
+
+    std::fs::rename(&temp_path, path)?;
+
+    // Make the rename itself durable. Directories cannot be opened like this on Windows.
+    #[cfg(unix)]
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::File::open(parent)?.sync_all()?;
+    }
+
+    Ok(())
+}
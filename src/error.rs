@@ -1,4 +1,7 @@
 #![allow(dead_code)]
+use std::path::Path;
+use std::path::PathBuf;
+
 use chrono::DateTime;
 use chrono::Local;
 use derive_more::Display;
@@ -7,12 +10,12 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Serialize, Deserialize, Clone, Display)]
 #[display("{}, {}", timestamp.format("%Y-%m-%d %H:%M:%S"), error)]
 pub struct ErrorContext {
-    pub error: Error,
+    pub error: Box<Error>,
     pub timestamp: DateTime<Local>,
     pub suggested_action: String,
 }
 
-impl<T: std::error::Error> From<T> for ErrorContext {
+impl<T: std::error::Error + 'static> From<T> for ErrorContext {
     fn from(value: T) -> Self {
         ErrorContext::from(Error::from(value))
     }
@@ -20,19 +23,19 @@ impl<T: std::error::Error> From<T> for ErrorContext {
 
 impl From<Error> for ErrorContext {
     fn from(value: Error) -> Self {
-        ErrorContext { error: value, timestamp: Local::now(), suggested_action: "N/A".to_string() }
+        ErrorContext::builder().error(value).build()
     }
 }
 
 impl From<ErrorData> for ErrorContext {
     fn from(value: ErrorData) -> Self {
-        ErrorContext { error: value.into(), timestamp: Local::now(), suggested_action: "N/A".to_string() }
+        ErrorContext::builder().error(value.into()).build()
     }
 }
 
 impl ErrorContext {
     pub fn new(error: Error, timestamp: DateTime<Local>, suggested_action: String) -> ErrorContext {
-        ErrorContext { error, timestamp, suggested_action }
+        ErrorContext { error: Box::new(error), timestamp, suggested_action }
     }
 
     pub fn builder() -> ErrorContextBuilder {
@@ -44,6 +47,12 @@ impl ErrorContext {
     }
 }
 
+/// Errors of previous sessions as saved in the user data directory, oldest first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ErrorHistory {
+    pub errors: Vec<ErrorContext>,
+}
+
 #[derive(Debug)]
 pub struct ErrorContextBuilder {
     error: Option<Error>,
@@ -71,17 +80,136 @@ impl ErrorContextBuilder {
         self
     }
 
+    /// Builds the context. Without a suggested action given here, errors of a kind with a known
+    /// remedy get the suggestion of their kind.
     pub fn build(self) -> ErrorContext {
+        let error = self.error.unwrap_or(Error::other("N/A", "N/A"));
+        let suggested_action = self
+            .suggested_action
+            .or_else(|| error.kind().suggestion(error.path()))
+            .unwrap_or("N/A".to_string());
+
         ErrorContext {
-            error: self.error.unwrap_or(Error::other("N/A", "N/A")),
+            error: Box::new(error),
             timestamp: self.timestamp.unwrap_or(Local::now()),
-            suggested_action: self.suggested_action.unwrap_or("N/A".to_string()),
+            suggested_action,
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Display)]
-#[display("{}, During \"{}\" - {}", _0.source, _0.operation, _0.msg)]
+/// What went wrong, independent of where. Every kind has a stable code that can be searched for
+/// and reported.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Display)]
+pub enum ErrorKind {
+    #[default]
+    Other,
+    Io,
+    NotFound,
+    PermissionDenied,
+    DiskFull,
+    AlreadyExists,
+    InUse,
+    InvalidArchive,
+    InvalidData,
+    Unsupported,
+    InvalidInput,
+}
+
+impl ErrorKind {
+    pub fn code(self) -> &'static str {
+        match self {
+            ErrorKind::Other => "E0000",
+            ErrorKind::Io => "E1000",
+            ErrorKind::NotFound => "E1001",
+            ErrorKind::PermissionDenied => "E1002",
+            ErrorKind::DiskFull => "E1003",
+            ErrorKind::AlreadyExists => "E1004",
+            ErrorKind::InUse => "E1005",
+            ErrorKind::InvalidArchive => "E2001",
+            ErrorKind::InvalidData => "E2002",
+            ErrorKind::Unsupported => "E2003",
+            ErrorKind::InvalidInput => "E3001",
+        }
+    }
+
+    pub fn from_io(err: &std::io::Error) -> Self {
+        // Sharing violation, reported when another program holds the file open.
+        if cfg!(windows) && err.raw_os_error() == Some(32) {
+            return ErrorKind::InUse;
+        }
+
+        match err.kind() {
+            std::io::ErrorKind::NotFound => ErrorKind::NotFound,
+            std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ReadOnlyFilesystem => {
+                ErrorKind::PermissionDenied
+            }
+            std::io::ErrorKind::StorageFull | std::io::ErrorKind::QuotaExceeded => ErrorKind::DiskFull,
+            std::io::ErrorKind::AlreadyExists => ErrorKind::AlreadyExists,
+            std::io::ErrorKind::ResourceBusy | std::io::ErrorKind::ExecutableFileBusy => ErrorKind::InUse,
+            std::io::ErrorKind::InvalidData | std::io::ErrorKind::UnexpectedEof => ErrorKind::InvalidData,
+            std::io::ErrorKind::Unsupported => ErrorKind::Unsupported,
+            std::io::ErrorKind::InvalidInput => ErrorKind::InvalidInput,
+            _ => ErrorKind::Io,
+        }
+    }
+
+    /// Derives the kind from the first error of a source chain that tells something about it.
+    pub fn from_source(err: &(dyn std::error::Error + 'static)) -> Self {
+        std::iter::successors(Some(err), |err| err.source())
+            .find_map(|err| {
+                if let Some(err) = err.downcast_ref::<std::io::Error>() {
+                    Some(Self::from_io(err))
+                } else if let Some(err) = err.downcast_ref::<zip::result::ZipError>() {
+                    match err {
+                        zip::result::ZipError::Io(err) => Some(Self::from_io(err)),
+                        _ => Some(ErrorKind::InvalidArchive),
+                    }
+                } else if err.is::<serde_json::Error>() || err.is::<toml::de::Error>() {
+                    Some(ErrorKind::InvalidData)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    /// Suggestion for the kinds that have a remedy regardless of what the application was doing.
+    pub fn suggestion(self, path: Option<&Path>) -> Option<String> {
+        let subject = path.map(|path| path.display().to_string());
+        let subject = subject.as_deref();
+        match self {
+            ErrorKind::NotFound => {
+                Some(format!("Check that {} exists", subject.unwrap_or("the file or directory")))
+            }
+            ErrorKind::PermissionDenied => Some(format!(
+                "Check the permissions of {}, or run the application as a user allowed to change it",
+                subject.unwrap_or("the file or directory")
+            )),
+            ErrorKind::DiskFull => Some(format!(
+                "Free up disk space on the drive of {} and try again",
+                subject.unwrap_or("the file")
+            )),
+            ErrorKind::AlreadyExists => {
+                Some(format!("Move or rename {} and try again", subject.unwrap_or("the existing file")))
+            }
+            ErrorKind::InUse => Some(format!(
+                "Close the programs using {}, such as the game, and try again",
+                subject.unwrap_or("the file")
+            )),
+            ErrorKind::InvalidArchive => Some(format!(
+                "{} is damaged or not a zip archive, download it again",
+                subject.unwrap_or("The archive")
+            )),
+            ErrorKind::InvalidData => Some(format!(
+                "Check that {} is complete and in the expected format",
+                subject.unwrap_or("the file")
+            )),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum Error {
     Service(ErrorData),
     FileSystem(ErrorData),
@@ -90,77 +218,142 @@ pub enum Error {
     Other(ErrorData),
 }
 
-impl<T: std::error::Error> From<T> for Error {
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let data = self.data();
+        write!(f, "{}", self.summary())?;
+        if let Some(path) = &data.path {
+            write!(f, " ({})", path.display())?;
+        }
+
+        data.causes.iter().try_for_each(|cause| write!(f, ": {cause}"))
+    }
+}
+
+impl<T: std::error::Error + 'static> From<T> for Error {
     fn from(value: T) -> Self {
-        Error::other(value.to_string(), "N/A")
+        let kind = ErrorKind::from_source(&value);
+        let msg = match kind {
+            ErrorKind::Other => "Unexpected error",
+            _ => "Operation failed",
+        };
+
+        Error::other(msg, "N/A").with_source(&value)
     }
 }
 
 impl From<ErrorData> for Error {
     fn from(info: ErrorData) -> Self {
-        Error::new(info.msg, &info.source, &info.operation)
+        Error::Other(info)
     }
 }
 
 impl Error {
+    fn data(&self) -> &ErrorData {
+        match self {
+            Error::Service(data)
+            | Error::FileSystem(data)
+            | Error::System(data)
+            | Error::External(data)
+            | Error::Other(data) => data,
+        }
+    }
+
+    fn data_mut(&mut self) -> &mut ErrorData {
+        match self {
+            Error::Service(data)
+            | Error::FileSystem(data)
+            | Error::System(data)
+            | Error::External(data)
+            | Error::Other(data) => data,
+        }
+    }
+
+    /// Code, origin and message of the error, without its path and causes.
+    pub fn summary(&self) -> String {
+        let data = self.data();
+        format!("[{}] {}, During \"{}\" - {}", data.kind.code(), data.source, data.operation, data.msg)
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.data().kind
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.data().kind.code()
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.data().path.as_deref()
+    }
+
+    /// Messages of the errors that caused this one, outermost first.
+    pub fn causes(&self) -> &[String] {
+        &self.data().causes
+    }
+
+    pub fn with_kind(mut self, kind: ErrorKind) -> Self {
+        self.data_mut().kind = kind;
+        self
+    }
+
+    /// Records the file or directory the error is about.
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.data_mut().path = Some(path.into().into_boxed_path());
+        self
+    }
+
+    /// Records the error that caused this one together with its own sources. Unless a kind was
+    /// set already, it is derived from them.
+    pub fn with_source(mut self, source: &(dyn std::error::Error + 'static)) -> Self {
+        let data = self.data_mut();
+        let causes = std::iter::successors(Some(source), |err| err.source()).map(|err| err.to_string());
+        data.causes = data.causes.iter().cloned().chain(causes).collect();
+        if data.kind == ErrorKind::Other {
+            data.kind = ErrorKind::from_source(source);
+        }
+
+        self
+    }
+
     pub fn new<S: Into<String>>(msg: S, source: &str, operation: &str) -> Error {
-        Error::Other(ErrorData {
-            msg: msg.into(),
-            source: source.to_owned(),
-            operation: operation.to_owned(),
-        })
+        Error::Other(ErrorData::new(msg.into(), source.to_owned(), operation.to_owned()))
     }
 
     pub fn other<S: Into<String>>(msg: S, operation: &str) -> Error {
-        Error::Other(ErrorData { msg: msg.into(), source: "Unknown".into(), operation: operation.into() })
+        Error::Other(ErrorData::new(msg.into(), "Unknown".into(), operation.into()))
     }
 
     pub fn mods_service<S: Into<String>>(msg: S, operation: &str) -> Error {
-        Error::Service(ErrorData {
-            msg: msg.into(),
-            source: "Mods Service".into(),
-            operation: operation.into(),
-        })
+        Error::Service(ErrorData::new(msg.into(), "Mods Service".into(), operation.into()))
     }
 
     pub fn profile_service<S: Into<String>>(msg: S, operation: &str) -> Error {
-        Error::Service(ErrorData {
-            msg: msg.into(),
-            source: "Profile Service".into(),
-            operation: operation.into(),
-        })
+        Error::Service(ErrorData::new(msg.into(), "Profile Service".into(), operation.into()))
     }
 
     pub fn session_service<S: Into<String>>(msg: S, operation: &str) -> Error {
-        Error::Service(ErrorData {
-            msg: msg.into(),
-            source: "Session Service".into(),
-            operation: operation.into(),
-        })
+        Error::Service(ErrorData::new(msg.into(), "Session Service".into(), operation.into()))
+    }
+
+    pub fn conflicts_service<S: Into<String>>(msg: S, operation: &str) -> Error {
+        Error::Service(ErrorData::new(msg.into(), "Conflicts Service".into(), operation.into()))
     }
 
     pub fn ui_service<S: Into<String>>(msg: S, operation: &str) -> Error {
-        Error::Service(ErrorData {
-            msg: msg.into(),
-            source: "UI Service".into(),
-            operation: operation.into(),
-        })
+        Error::Service(ErrorData::new(msg.into(), "UI Service".into(), operation.into()))
     }
 
     pub fn file_system<S: Into<String>>(msg: S, operation: &str) -> Error {
-        Error::FileSystem(ErrorData {
-            msg: msg.into(),
-            source: "File System".into(),
-            operation: operation.into(),
-        })
+        Error::FileSystem(ErrorData::new(msg.into(), "File System".into(), operation.into()))
     }
 
     pub fn system<S: Into<String>>(msg: S, operation: &str) -> Error {
-        Error::System(ErrorData { msg: msg.into(), source: "System".into(), operation: operation.into() })
+        Error::System(ErrorData::new(msg.into(), "System".into(), operation.into()))
     }
 
     pub fn external<S: Into<String>>(msg: S, operation: &str) -> Error {
-        Error::External(ErrorData { msg: msg.into(), source: "External".into(), operation: operation.into() })
+        Error::External(ErrorData::new(msg.into(), "External".into(), operation.into()))
     }
 
     pub fn error_message(self) -> String {
@@ -200,11 +393,24 @@ pub struct ErrorData {
     pub msg: String,
     pub source: String,
     pub operation: String,
+    #[serde(default)]
+    pub kind: ErrorKind,
+    #[serde(default)]
+    pub path: Option<Box<Path>>,
+    #[serde(default)]
+    pub causes: Box<[String]>,
 }
 
 impl ErrorData {
     pub fn new<S: Into<String>>(msg: S, source: S, operation: S) -> ErrorData {
-        ErrorData { msg: msg.into(), source: source.into(), operation: operation.into() }
+        ErrorData {
+            msg: msg.into(),
+            source: source.into(),
+            operation: operation.into(),
+            kind: ErrorKind::default(),
+            path: None,
+            causes: Box::default(),
+        }
     }
 
     pub fn builder() -> ErrorInfoBuilder {
@@ -240,10 +446,56 @@ impl ErrorInfoBuilder {
     }
 
     pub fn build(self) -> ErrorData {
-        ErrorData {
-            msg: self.msg.unwrap_or("N/A".into()),
-            source: self.source.unwrap_or("N/A".into()),
-            operation: self.operation.unwrap_or("N/A".into()),
-        }
+        ErrorData::new(
+            self.msg.unwrap_or("N/A".into()),
+            self.source.unwrap_or("N/A".into()),
+            self.operation.unwrap_or("N/A".into()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage_full() -> std::io::Error {
+        std::io::Error::from(std::io::ErrorKind::StorageFull)
+    }
+
+    #[test]
+    fn kind_suggestions_apply_without_an_explicit_one() {
+        let context = ErrorContext::from(storage_full());
+        assert_eq!(context.error.kind(), ErrorKind::DiskFull);
+        assert_eq!(context.suggested_action, "Free up disk space on the drive of the file and try again");
+
+        let context = ErrorContext::from(
+            Error::file_system("Could not save session", "Save Session")
+                .with_path("/data/session.json")
+                .with_source(&std::io::Error::from(std::io::ErrorKind::PermissionDenied)),
+        );
+        assert!(context.suggested_action.starts_with("Check the permissions of /data/session.json"));
+
+        let parse_error = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        let context = ErrorContext::from(
+            Error::file_system("Could not load the modpack", "Load Modpack")
+                .with_path("/packs/pack.json")
+                .with_source(&parse_error),
+        );
+        assert_eq!(context.error.kind(), ErrorKind::InvalidData);
+        assert_eq!(
+            context.suggested_action,
+            "Check that /packs/pack.json is complete and in the expected format"
+        );
+    }
+
+    #[test]
+    fn explicit_suggestions_win_over_kind_suggestions() {
+        let context = ErrorContext::builder()
+            .error(Error::file_system("Could not copy file", "Set Mods Dir").with_source(&storage_full()))
+            .suggested_action("Choose a mods directory on another drive")
+            .build();
+
+        assert_eq!(context.suggested_action, "Choose a mods directory on another drive");
+        assert_eq!(ErrorContext::from(Error::other("Unexpected", "Test")).suggested_action, "N/A");
     }
 }
@@ -0,0 +1,129 @@
+use iced::widget;
+
+use crate::app::message;
+use crate::core::compare;
+use crate::styled_container;
+
+pub fn compare_view(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
+    let theme = app.theme();
+    let palette_ext = theme.extended_palette();
+    let mut container_bg_color = palette_ext.primary.weak.color;
+    container_bg_color.a = 0.3;
+
+    let compare_state = &app.state.compare;
+
+    let choice_left =
+        widget::combo_box(&compare_state.choices, "Left instance", compare_state.left.as_ref(), |i| {
+            message::ConflictsMessage::SetCompareLeft(i).into()
+        });
+
+    let choice_right =
+        widget::combo_box(&compare_state.choices, "Right instance", compare_state.right.as_ref(), |i| {
+            message::ConflictsMessage::SetCompareRight(i).into()
+        });
+
+    let button_refresh = widget::button("Refresh").on_press(message::ConflictsMessage::OpenComparison.into());
+
+    let controls = styled_container!(
+        widget::row![choice_left, choice_right, button_refresh]
+            .spacing(10)
+            .align_y(iced::alignment::Vertical::Center),
+        border_width = 2.0,
+        border_radius = 4.0,
+        background = container_bg_color
+    )
+    .padding(10)
+    .center_x(iced::Length::Fill);
+
+    let Some(result) = compare_state.result.as_ref() else {
+        return widget::column![
+            controls,
+            widget::container(widget::text("Select two instances to compare")).center(iced::Length::Fill)
+        ]
+        .spacing(10)
+        .padding(10)
+        .into();
+    };
+
+    if result.is_identical() {
+        return widget::column![
+            controls,
+            widget::container(widget::text("Instances are identical")).center(iced::Length::Fill)
+        ]
+        .spacing(10)
+        .padding(10)
+        .into();
+    }
+
+    let mods_only_in = |names: &[String], in_left: bool| {
+        names
+            .iter()
+            .map(|name| match in_left {
+                true => [name.clone(), "installed".to_owned(), "-".to_owned()],
+                false => [name.clone(), "-".to_owned(), "installed".to_owned()],
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let sections = [
+        ("Mods only in left instance", mods_only_in(&result.only_left, true)),
+        ("Mods only in right instance", mods_only_in(&result.only_right, false)),
+        ("Enabled state", difference_rows(&result.enabled, |name| name.clone(), enabled_label)),
+        ("Load order", difference_rows(&result.load_order, |name| name.clone(), |p| p.to_string())),
+        (
+            "File toggles",
+            difference_rows(&result.file_toggles, |path| path.display().to_string(), enabled_label),
+        ),
+        (
+            "Effective winners",
+            difference_rows(&result.winners, |path| path.display().to_string(), String::clone),
+        ),
+    ];
+
+    let body = sections.into_iter().filter(|(_, rows)| !rows.is_empty()).fold(
+        widget::column![].spacing(10).padding(10),
+        |column, (title, rows)| {
+            let table = rows
+                .into_iter()
+                .fold(widget::column![table_row(["", "Left", "Right"])].spacing(5), |table, row| {
+                    table.push(table_row(row))
+                })
+                .padding(10);
+
+            column.push(widget::text!("{title}:")).push(styled_container!(
+                table,
+                border_width = 2.0,
+                border_radius = 4.0
+            ))
+        },
+    );
+
+    widget::column![controls, widget::scrollable(body)].spacing(10).padding(10).into()
+}
+
+fn difference_rows<K, V>(
+    differences: &[compare::Difference<K, V>],
+    key_label: impl Fn(&K) -> String,
+    value_label: impl Fn(&V) -> String,
+) -> Vec<[String; 3]> {
+    let value_or_missing = |value: &Option<V>| value.as_ref().map(&value_label).unwrap_or("-".to_owned());
+    differences
+        .iter()
+        .map(|d| [key_label(&d.key), value_or_missing(&d.left), value_or_missing(&d.right)])
+        .collect()
+}
+
+fn enabled_label(enabled: &bool) -> String {
+    if *enabled { "enabled".to_owned() } else { "disabled".to_owned() }
+}
+
+fn table_row<'a, S: ToString>(cells: [S; 3]) -> iced::Element<'a, message::Message> {
+    cells
+        .iter()
+        .enumerate()
+        .fold(widget::row![].spacing(10), |row, (index, cell)| {
+            let portion = if index == 0 { 3 } else { 2 };
+            row.push(widget::text(cell.to_string()).width(iced::Length::FillPortion(portion)))
+        })
+        .into()
+}
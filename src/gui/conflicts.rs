@@ -4,7 +4,7 @@ use iced::widget;
 
 use crate::styled_container;
 
-pub fn conflicts_view(app: &crate::app::GothicOrganizer) -> iced::Element<crate::app::message::Message> {
+pub fn conflicts_view(app: &crate::app::GothicOrganizer) -> iced::Element<'_, crate::app::message::Message> {
     if let Some(active_profile_name) = app.session.active_profile.as_ref()
         && let Some(active_instance_name) = app.session.active_instance.as_ref()
         && let Some(active_profile) = app.state.profile.profiles.get(active_profile_name)
@@ -39,11 +39,18 @@ pub fn conflicts_view(app: &crate::app::GothicOrganizer) -> iced::Element<crate:
         let files_from_other_mods = files_from_other_mods_data
             .iter()
             .fold(widget::column![], |col, (dst_path, parent_name)| {
-                col.push(widget::row![
-                    widget::text(dst_path.to_string_lossy().into_owned()),
-                    widget::horizontal_space(),
-                    widget::text(parent_name.clone())
-                ])
+                col.push(
+                    widget::row![
+                        widget::text(dst_path.to_string_lossy().into_owned()),
+                        widget::horizontal_space(),
+                        widget::text(parent_name.clone()),
+                        widget::button("Diff").on_press(
+                            crate::app::message::ConflictsMessage::OpenDiff(dst_path.clone()).into()
+                        )
+                    ]
+                    .spacing(10)
+                    .align_y(iced::alignment::Vertical::Center),
+                )
             })
             .spacing(5)
             .padding(10);
@@ -51,11 +58,18 @@ pub fn conflicts_view(app: &crate::app::GothicOrganizer) -> iced::Element<crate:
         let files_from_this_mod = files_from_this_mod_data
             .iter()
             .fold(widget::column![], |col, (dst_path, parent_name)| {
-                col.push(widget::row![
-                    widget::text(dst_path.to_string_lossy().into_owned()),
-                    widget::horizontal_space(),
-                    widget::text(parent_name.clone())
-                ])
+                col.push(
+                    widget::row![
+                        widget::text(dst_path.to_string_lossy().into_owned()),
+                        widget::horizontal_space(),
+                        widget::text(parent_name.clone()),
+                        widget::button("Diff").on_press(
+                            crate::app::message::ConflictsMessage::OpenDiff(dst_path.clone()).into()
+                        )
+                    ]
+                    .spacing(10)
+                    .align_y(iced::alignment::Vertical::Center),
+                )
             })
             .spacing(5)
             .padding(10);
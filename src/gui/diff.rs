@@ -0,0 +1,137 @@
+use iced::widget;
+
+use crate::app::message;
+use crate::core::diff;
+use crate::styled_container;
+
+pub fn diff_view(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
+    let theme = app.theme();
+    let palette_ext = theme.extended_palette();
+    let mut container_bg_color = palette_ext.primary.weak.color;
+    container_bg_color.a = 0.3;
+
+    let diff_state = &app.state.diff;
+    let target_label = match diff_state.target_path.as_ref() {
+        Some(path) => widget::text!("Comparing: {}", path.display()),
+        None => widget::text("Select a conflicting file in the conflicts window to compare its providers"),
+    };
+
+    let choice_left =
+        widget::combo_box(&diff_state.provider_choices, "Left", diff_state.left.as_ref(), |p| {
+            message::ConflictsMessage::SetDiffLeft(p).into()
+        });
+
+    let choice_right =
+        widget::combo_box(&diff_state.provider_choices, "Right", diff_state.right.as_ref(), |p| {
+            message::ConflictsMessage::SetDiffRight(p).into()
+        });
+
+    let choice_encoding =
+        widget::combo_box(&diff_state.encoding_choices, "Encoding", Some(&diff_state.encoding), |encoding| {
+            message::ConflictsMessage::SetDiffEncoding(encoding).into()
+        });
+
+    let button_swap = widget::button("Swap").on_press_maybe(
+        (diff_state.left.is_some() || diff_state.right.is_some())
+            .then_some(message::ConflictsMessage::SwapDiffSides.into()),
+    );
+
+    let controls = styled_container!(
+        widget::column![
+            target_label,
+            widget::row![choice_left, button_swap, choice_right, choice_encoding].spacing(10)
+        ]
+        .spacing(10),
+        border_width = 2.0,
+        border_radius = 4.0,
+        background = container_bg_color
+    )
+    .padding(10)
+    .center_x(iced::Length::Fill);
+
+    let summary = match diff_state.result.as_ref() {
+        Some(result) if result.is_identical() => "Files are identical",
+        Some(_) => "Files differ",
+        None => "",
+    };
+
+    let body: iced::Element<_> = match diff_state.result.as_ref() {
+        Some(diff::FileDiff::Text { left_encoding, right_encoding, lines }) => {
+            let summary = widget::text!("{summary} ({left_encoding} / {right_encoding})");
+
+            let rows = lines
+                .iter()
+                .fold(widget::column![], |column, line| {
+                    let (left_bg, right_bg) = match line.kind {
+                        diff::DiffLineKind::Equal => (iced::Color::TRANSPARENT, iced::Color::TRANSPARENT),
+                        diff::DiffLineKind::Removed => {
+                            (palette_ext.danger.weak.color.scale_alpha(0.5), iced::Color::TRANSPARENT)
+                        }
+                        diff::DiffLineKind::Added => {
+                            (iced::Color::TRANSPARENT, palette_ext.success.weak.color.scale_alpha(0.5))
+                        }
+                        diff::DiffLineKind::Changed => (
+                            palette_ext.danger.weak.color.scale_alpha(0.5),
+                            palette_ext.success.weak.color.scale_alpha(0.5),
+                        ),
+                    };
+
+                    column.push(
+                        widget::row![
+                            diff_cell(line.left_number, line.left.as_deref(), left_bg),
+                            diff_cell(line.right_number, line.right.as_deref(), right_bg)
+                        ]
+                        .spacing(5),
+                    )
+                })
+                .padding(10);
+
+            widget::column![summary, widget::scrollable(rows)].spacing(10).into()
+        }
+
+        Some(diff::FileDiff::Binary { left, right }) => {
+            let side = |title: &str,
+                        provider: Option<&crate::app::state::DiffProvider>,
+                        info: &diff::BinarySummary| {
+                widget::column![
+                    widget::text!("{title}: {}", provider.map(|p| p.to_string()).unwrap_or_default()),
+                    widget::text!("Size: {} bytes", info.size),
+                    widget::text!("SHA-256: {}", info.sha256).font(iced::Font::MONOSPACE),
+                ]
+                .spacing(5)
+            };
+
+            widget::column![
+                widget::text!("Binary file, compared by size and hash: {summary}"),
+                side("Left", diff_state.left.as_ref(), left),
+                side("Right", diff_state.right.as_ref(), right),
+            ]
+            .spacing(10)
+            .padding(10)
+            .into()
+        }
+
+        None => widget::column![].into(),
+    };
+
+    let body_container = styled_container!(body, border_width = 2.0, border_radius = 4.0)
+        .padding(10)
+        .center_x(iced::Length::Fill)
+        .align_top(iced::Length::Fill);
+
+    widget::column![controls, body_container].spacing(10).padding(10).into()
+}
+
+fn diff_cell<'a>(
+    number: Option<usize>,
+    content: Option<&str>,
+    background: iced::Color,
+) -> iced::Element<'a, message::Message> {
+    let number =
+        widget::text(number.map(|n| n.to_string()).unwrap_or_default()).font(iced::Font::MONOSPACE).width(48);
+    let content = widget::text(content.unwrap_or_default().to_owned()).font(iced::Font::MONOSPACE);
+
+    styled_container!(widget::row![number, content].spacing(5), border_width = 0.0, background = background)
+        .width(iced::Length::FillPortion(1))
+        .into()
+}
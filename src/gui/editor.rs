@@ -8,8 +8,9 @@ pub mod files;
 pub mod header;
 pub mod mods;
 pub mod profile;
+pub mod toasts;
 
-pub fn editor_view(app: &crate::app::GothicOrganizer) -> iced::Element<message::Message> {
+pub fn editor_view(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
     let current_profile = app.session.active_profile.as_ref().and_then(|s| app.state.profile.profiles.get(s));
 
     let instance_selected = app
@@ -27,5 +28,10 @@ pub fn editor_view(app: &crate::app::GothicOrganizer) -> iced::Element<message::
     let mods_menu = mods::mods_menu(app, palette_ext, instance_selected);
     let workspace = container(row!(mods_menu, files_menu).spacing(10)).align_top(iced::Length::Fill);
 
-    column![header, profile_controls, workspace].spacing(10).padding(10).into()
+    let editor = column![header, profile_controls, workspace].spacing(10).padding(10);
+
+    match toasts::toasts(app, palette_ext) {
+        Some(toasts) => iced::widget::stack![editor, toasts].into(),
+        None => editor.into(),
+    }
 }
@@ -71,8 +71,44 @@ pub fn file_view_controls<'a>(
     let button_toggle_all =
         widget::button("Toggle all").on_press(message::UiMessage::ToggleAllFileEntries.into());
 
+    let history = &app.state.history;
+    let history_button =
+        |label: &'static str, description: Option<&str>, shortcut: &str, message: message::HistoryMessage| {
+            let tooltip_text = match description {
+                Some(description) => format!("{label}: {description} ({shortcut})"),
+                None => format!("Nothing to {}", label.to_lowercase()),
+            };
+            widget::tooltip(
+                widget::button(label).on_press_maybe(description.map(|_| message.into())),
+                widget::text(tooltip_text),
+                widget::tooltip::Position::Bottom,
+            )
+        };
+
+    let button_undo =
+        history_button("Undo", history.undo_description(), "Ctrl+Z", message::HistoryMessage::Undo);
+    let button_redo =
+        history_button("Redo", history.redo_description(), "Ctrl+Y", message::HistoryMessage::Redo);
+    let button_clear_history = widget::tooltip(
+        widget::button("Clear history").on_press_maybe(
+            (!history.undo.is_empty() || !history.redo.is_empty())
+                .then_some(message::HistoryMessage::Clear.into()),
+        ),
+        widget::text("Forget all steps and delete uninstalled mods kept for undoing"),
+        widget::tooltip::Position::Bottom,
+    );
+
     styled_container!(
-        widget::row!(button_back, button_home, button_toggle_all).spacing(10),
+        widget::row!(
+            button_back,
+            button_home,
+            button_toggle_all,
+            widget::horizontal_space(),
+            button_undo,
+            button_redo,
+            button_clear_history
+        )
+        .spacing(10),
         border_width = 1.0,
         border_radius = 4.0
     )
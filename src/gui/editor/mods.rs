@@ -28,6 +28,9 @@ pub fn mods_menu<'a>(
     .height(20)
     .width(20);
 
+    // Installed mods must stay where they are while they are copied to a new mods directory.
+    let is_transferring = app.state.mod_management.storage_transfer.is_some();
+
     let button_add_mod: iced::Element<'a, message::Message> = styled_button!(
         icon_add,
         background = palette_ext.success.base.color,
@@ -35,7 +38,7 @@ pub fn mods_menu<'a>(
         pressed_background = palette_ext.success.base.color,
         disabled_background = palette_ext.success.weak.color,
     )
-    .on_press(message::ModMessage::Add(None).into())
+    .on_press_maybe((!is_transferring).then(|| message::ModMessage::Add(None).into()))
     .into();
 
     let button_remove: iced::Element<'a, message::Message> = styled_button!(
@@ -52,7 +55,10 @@ pub fn mods_menu<'a>(
         disabled_background = palette_ext.danger.weak.color
     )
     .on_press_maybe(
-        app.session.mod_selected.map(|mod_index| message::ModMessage::Uninstall(mod_index).into()),
+        app.session
+            .mod_selected
+            .filter(|_| !is_transferring)
+            .map(|mod_index| message::ModMessage::Uninstall(mod_index).into()),
     )
     .into();
 
@@ -99,8 +105,48 @@ pub fn mods_menu<'a>(
     )
     .into();
 
+    let button_overlaps: iced::Element<'a, message::Message> = styled_button!(
+        svg_with_color!(
+            "./resources/overwrites.svg",
+            color_idle = palette_ext.primary.strong.text,
+            color_hovered = palette_ext.primary.strong.text
+        )
+        .width(20)
+        .height(20),
+        background = palette_ext.secondary.base.color,
+        hover_background = palette_ext.secondary.strong.color,
+        pressed_background = palette_ext.secondary.base.color,
+        disabled_background = palette_ext.secondary.weak.color
+    )
+    .on_press_maybe(instance_selected.map(|_| message::ConflictsMessage::AnalyzeOverlaps.into()))
+    .into();
+
+    let button_compare: iced::Element<'a, message::Message> = styled_button!(
+        svg_with_color!(
+            "./resources/compare.svg",
+            color_idle = palette_ext.primary.strong.text,
+            color_hovered = palette_ext.primary.strong.text
+        )
+        .width(20)
+        .height(20),
+        background = palette_ext.secondary.base.color,
+        hover_background = palette_ext.secondary.strong.color,
+        pressed_background = palette_ext.secondary.base.color,
+        disabled_background = palette_ext.secondary.weak.color
+    )
+    .on_press(message::ConflictsMessage::OpenComparison.into())
+    .into();
+
     let group_mod_controls = styled_container!(
-        widget::row!(button_add_mod, button_remove, button_conflicts, button_browse).spacing(10),
+        widget::row!(
+            button_add_mod,
+            button_remove,
+            button_conflicts,
+            button_overlaps,
+            button_compare,
+            button_browse
+        )
+        .spacing(10),
         border_width = 1.0,
         border_radius = 4.0
     )
@@ -33,8 +33,28 @@ pub fn profile_controls<'a>(
         _ => widget::horizontal_space().into(),
     };
 
+    let fingerprint = current_profile.and_then(|p| p.fingerprint.as_ref()).map(|fingerprint| {
+        let color = if !fingerprint.is_valid() {
+            palette_ext.danger.base.color
+        } else if !fingerprint.warnings.is_empty() {
+            palette_ext.secondary.strong.color
+        } else {
+            palette_ext.background.base.text
+        };
+
+        let problems = fingerprint.errors.iter().chain(&fingerprint.warnings).cloned().collect::<Vec<_>>();
+        let label = widget::text(fingerprint.summary()).size(12).color(color);
+        let tooltip_text =
+            if problems.is_empty() { "Installation is valid".to_owned() } else { problems.join("\n") };
+        let tooltip_body =
+            styled_container!(widget::text(tooltip_text), border_width = 1.0, border_radius = 4.0).padding(5);
+        widget::tooltip(label, tooltip_body, widget::tooltip::Position::Bottom)
+    });
+
     styled_container!(
-        widget::row!(choice_profile, instance_element).spacing(10),
+        widget::column![widget::row!(choice_profile, instance_element).spacing(10)]
+            .push_maybe(fingerprint)
+            .spacing(5),
         border_width = 2.0,
         border_radius = 4.0,
         background = container_bg_color
@@ -71,6 +91,30 @@ pub fn instance_controls<'a>(
         Some(message::ProfileMessage::RemoveActiveInstance.into())
     });
 
+    let name_field = app.state.profile.instance_name_field.trim();
+    let can_copy_instance = app.session.active_instance.is_some()
+        && !name_field.is_empty()
+        && current_profile
+            .and_then(|p| p.instances.as_ref())
+            .is_some_and(|instances| !instances.contains_key(name_field));
+
+    let button_duplicate = styled_button!(
+        "Duplicate",
+        background = palette_ext.primary.base.color,
+        hover_background = palette_ext.primary.strong.color,
+        pressed_background = palette_ext.primary.base.color,
+        disabled_background = palette_ext.primary.weak.color,
+    )
+    .on_press_maybe(can_copy_instance.then_some(message::ProfileMessage::DuplicateActiveInstance.into()));
+    let button_rename = styled_button!(
+        "Rename",
+        background = palette_ext.primary.base.color,
+        hover_background = palette_ext.primary.strong.color,
+        pressed_background = palette_ext.primary.base.color,
+        disabled_background = palette_ext.primary.weak.color,
+    )
+    .on_press_maybe(can_copy_instance.then_some(message::ProfileMessage::RenameActiveInstance.into()));
+
     let button_add = styled_button!(
         "Add",
         background = palette_ext.success.base.color,
@@ -87,7 +131,36 @@ pub fn instance_controls<'a>(
         disabled_background = palette_ext.danger.weak.color,
     )
     .on_press_maybe(button_remove_message);
-    widget::container(widget::row!(choice_instance, button_add, button_remove)).into()
+
+    let button_deploy = styled_button!(
+        "Deploy",
+        background = palette_ext.primary.base.color,
+        hover_background = palette_ext.primary.strong.color,
+        pressed_background = palette_ext.primary.base.color,
+        disabled_background = palette_ext.primary.weak.color,
+    )
+    .on_press_maybe(
+        app.session.active_instance.is_some().then_some(message::ProfileMessage::DeployActiveInstance.into()),
+    );
+    let button_purge = styled_button!(
+        "Purge",
+        background = palette_ext.primary.base.color,
+        hover_background = palette_ext.primary.strong.color,
+        pressed_background = palette_ext.primary.base.color,
+        disabled_background = palette_ext.primary.weak.color,
+    )
+    .on_press_maybe(current_profile.is_some().then_some(message::ProfileMessage::PurgeDeployment.into()));
+
+    widget::container(widget::row!(
+        choice_instance,
+        button_add,
+        button_duplicate,
+        button_rename,
+        button_remove,
+        button_deploy,
+        button_purge
+    ))
+    .into()
 }
 
 fn no_profile_path(p: &crate::core::profile::Profile) -> bool {
@@ -0,0 +1,95 @@
+use iced::widget;
+
+use crate::app::message;
+use crate::app::window::ApplicationWindow;
+use crate::styled_container;
+
+/// Newest errors shown as toasts, the others are summed up below them.
+const SHOWN_TOASTS_LIMIT: usize = 3;
+const TOAST_WIDTH: f32 = 420.0;
+
+/// Toasts for the errors that have not been dismissed yet, or `None` if there are none or error
+/// notifications are turned off.
+pub fn toasts<'a>(
+    app: &'a crate::app::GothicOrganizer,
+    palette_ext: &iced::theme::palette::Extended,
+) -> Option<iced::Element<'a, message::Message>> {
+    if !app.session.error_notifications_enabled || app.state.errors.active_errors.is_empty() {
+        return None;
+    }
+
+    let mut errors = app.state.errors.active_errors.iter().collect::<Vec<_>>();
+    errors.sort_unstable_by_key(|(_, ctx)| std::cmp::Reverse(ctx.timestamp));
+
+    let toasts_column =
+        errors.iter().take(SHOWN_TOASTS_LIMIT).fold(widget::column![].spacing(8), |column, (id, ctx)| {
+            let title = widget::row![
+                widget::text(ctx.error.summary())
+                    .color(palette_ext.danger.strong.color)
+                    .width(iced::Length::Fill),
+                widget::button(widget::text("Dismiss").size(12))
+                    .padding([2, 6])
+                    .on_press(message::ErrorMessage::Dismiss(**id).into()),
+            ]
+            .spacing(10);
+
+            let mut details = widget::column![title].spacing(4);
+            if ctx.suggested_action != "N/A" {
+                details = details.push(
+                    widget::text(format!("Try this: {}", ctx.suggested_action))
+                        .size(14)
+                        .color(palette_ext.primary.strong.color),
+                );
+            }
+
+            column.push(
+                styled_container!(
+                    details,
+                    border_width = 2.0,
+                    border_color = palette_ext.danger.strong.color,
+                    border_radius = 4.0,
+                    background = palette_ext.background.base.color
+                )
+                .padding(10)
+                .width(iced::Length::Fill),
+            )
+        });
+
+    let hidden = errors.len().saturating_sub(SHOWN_TOASTS_LIMIT);
+    let toasts_column = match errors.len() {
+        1 => toasts_column,
+        _ => toasts_column.push(
+            styled_container!(
+                widget::row![
+                    widget::text(match hidden {
+                        0 => String::new(),
+                        _ => format!("{hidden} more"),
+                    })
+                    .size(14),
+                    widget::horizontal_space(),
+                    widget::button(widget::text("Show logs").size(12))
+                        .padding([2, 6])
+                        .on_press(message::WindowMessage::Open(ApplicationWindow::Logs).into()),
+                    widget::button(widget::text("Dismiss all").size(12))
+                        .padding([2, 6])
+                        .on_press(message::ErrorMessage::ClearAll.into()),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                border_width = 2.0,
+                border_radius = 4.0,
+                background = palette_ext.background.base.color
+            )
+            .padding(6)
+            .width(iced::Length::Fill),
+        ),
+    };
+
+    Some(
+        widget::container(toasts_column.width(TOAST_WIDTH))
+            .padding(20)
+            .align_right(iced::Length::Fill)
+            .align_bottom(iced::Length::Fill)
+            .into(),
+    )
+}
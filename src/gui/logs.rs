@@ -1,40 +1,166 @@
+use crate::app::message;
+use crate::app::session;
+use crate::app::state::LogsTab;
+use crate::logger;
 use crate::styled_container;
 use iced::widget;
 
-pub fn logs_view(app: &crate::app::GothicOrganizer) -> iced::Element<crate::app::message::Message> {
+/// Most recent matching entries rendered by the application log, older ones can still be copied.
+const SHOWN_ENTRIES_LIMIT: usize = 500;
+
+pub fn logs_view(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
+    let current_tab = app.state.logs.tab;
+    let tab_bar = [LogsTab::Errors, LogsTab::Log].into_iter().fold(widget::row![], |bar, tab| {
+        bar.push(
+            widget::button(widget::text!("{tab}"))
+                .on_press_maybe((current_tab != tab).then_some(message::LogsMessage::SetTab(tab).into()))
+                .width(iced::Length::Fill),
+        )
+    });
+    let tab_bar = tab_bar
+        .push(
+            widget::button("Export diagnostics")
+                .on_press(message::LogsMessage::ExportDiagnostics(None).into()),
+        )
+        .spacing(10);
+
+    let contents = match current_tab {
+        LogsTab::Errors => errors_view(app),
+        LogsTab::Log => application_log_view(app),
+    };
+
+    let logs_view = widget::column![tab_bar, contents].spacing(10);
+
+    styled_container!(logs_view, border_width = 4.0, border_radius = 4.0)
+        .padding(10)
+        .center(iced::Length::Fill)
+        .align_top(iced::Length::Fill)
+        .into()
+}
+
+fn errors_view(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
     let theme = app.theme();
     let palette_ext = theme.extended_palette();
-    let messages_column: widget::Column<crate::app::message::Message> = app
+    let messages_column: widget::Column<message::Message> = app
         .state
         .errors
         .error_history
         .iter()
         .fold(widget::column![], |column, ctx| {
-            let message_contents = format!("{}: ERROR: {}", ctx.timestamp_string(), ctx.error);
-            let message_text = widget::text(message_contents).color(palette_ext.danger.strong.color);
-            let message_container = styled_container!(message_text, border_width = 2.0, border_radius = 0.0)
-                .padding(10)
-                .center_x(iced::Length::Fill);
-
-            let tooltip_contents = format!("Try this: {}", ctx.suggested_action.clone());
-            let tooltip_text = widget::text(tooltip_contents);
-            let tooltip_body =
-                styled_container!(tooltip_text, border_width = 1.0, border_radius = 4.0).padding(5);
-
-            column.push(widget::tooltip(message_container, tooltip_body, widget::tooltip::Position::Bottom))
+            let message_contents = format!("{}: ERROR: {}", ctx.timestamp_string(), ctx.error.summary());
+            let mut details =
+                widget::column![widget::text(message_contents).color(palette_ext.danger.strong.color)]
+                    .spacing(4);
+
+            if let Some(path) = ctx.error.path() {
+                details = details.push(widget::text(format!("Path: {}", path.display())).size(14));
+            }
+
+            for cause in ctx.error.causes() {
+                details = details.push(widget::text(format!("Caused by: {cause}")).size(14));
+            }
+
+            if ctx.suggested_action != "N/A" {
+                details = details.push(
+                    widget::text(format!("Try this: {}", ctx.suggested_action))
+                        .color(palette_ext.primary.strong.color),
+                );
+            }
+
+            column.push(
+                styled_container!(details, border_width = 2.0, border_radius = 0.0)
+                    .padding(10)
+                    .width(iced::Length::Fill),
+            )
         })
         .padding(10)
         .spacing(10);
 
+    let history = &app.state.errors.error_history;
+    let controls = widget::row![
+        widget::text!("{} errors recorded", history.len()).size(12),
+        widget::horizontal_space(),
+        widget::button("Clear")
+            .on_press_maybe((!history.is_empty()).then_some(message::LogsMessage::ClearErrors.into())),
+    ]
+    .spacing(10)
+    .align_y(iced::Alignment::Center);
+
     let messages_container =
         styled_container!(widget::scrollable(messages_column), border_width = 4.0, border_radius = 4.0)
             .center_x(iced::Length::Fill)
             .align_top(iced::Length::Fill);
 
-    let logs_view = widget::column![messages_container];
+    widget::column![controls, messages_container].spacing(10).into()
+}
 
-    styled_container!(logs_view, border_width = 4.0, border_radius = 4.0)
-        .center(iced::Length::Fill)
-        .align_top(iced::Length::Fill)
-        .into()
+fn application_log_view(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
+    let theme = app.theme();
+    let palette_ext = theme.extended_palette();
+    let logs = &app.state.logs;
+
+    let filters = widget::row![
+        widget::text!("Show:"),
+        widget::pick_list(&logger::LOG_LEVELS[..], Some(logs.level_filter), |level| {
+            message::LogsMessage::SetLevelFilter(level).into()
+        }),
+        widget::text_input("Target", &logs.target_filter)
+            .on_input(|input| message::LogsMessage::UpdateTargetFilter(input).into()),
+        widget::text_input("Search", &logs.text_filter)
+            .on_input(|input| message::LogsMessage::UpdateTextFilter(input).into()),
+        widget::button("Copy shown").on_press(message::LogsMessage::CopyShown.into()),
+    ]
+    .spacing(10)
+    .align_y(iced::Alignment::Center);
+
+    let level_setting = widget::row![
+        widget::text!("Log level:"),
+        widget::pick_list(
+            session::LogLevel::into_iter().as_slice(),
+            Some(app.session.log_settings.level),
+            |level| message::SettingsMessage::SetLogLevel(level).into()
+        ),
+        widget::text!("{} entries recorded", logs.entries.len()).size(12),
+    ]
+    .spacing(10)
+    .align_y(iced::Alignment::Center);
+
+    let shown = logs.shown_entries().collect::<Vec<_>>();
+    let entries_column = shown
+        .iter()
+        .skip(shown.len().saturating_sub(SHOWN_ENTRIES_LIMIT))
+        .fold(widget::column![], |column, entry| {
+            let color = match entry.level {
+                tracing::Level::ERROR => palette_ext.danger.strong.color,
+                tracing::Level::WARN => palette_ext.primary.strong.color,
+                _ => palette_ext.background.base.text,
+            };
+
+            let entry_row = widget::row![
+                widget::text!("{} {:>5}", entry.timestamp.format("%H:%M:%S"), entry.level)
+                    .size(13)
+                    .color(color),
+                widget::text!("{}", entry.target).size(13).color(palette_ext.secondary.strong.color),
+                widget::text!("{}", entry.message).size(13).width(iced::Length::Fill),
+                widget::button(widget::text("Copy").size(12))
+                    .padding([2, 6])
+                    .on_press(message::LogsMessage::CopyEntry(entry.id).into()),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center);
+
+            column.push(entry_row)
+        })
+        .padding(10)
+        .spacing(4);
+
+    let entries_container = styled_container!(
+        widget::scrollable(entries_column).anchor_bottom(),
+        border_width = 4.0,
+        border_radius = 4.0
+    )
+    .center_x(iced::Length::Fill)
+    .align_top(iced::Length::Fill);
+
+    widget::column![filters, level_setting, entries_container].spacing(10).into()
 }
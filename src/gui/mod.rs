@@ -1,6 +1,10 @@
+pub mod compare;
 pub mod conflicts;
 pub mod custom_widgets;
+pub mod diff;
 pub mod editor;
 pub mod helpers;
 pub mod logs;
+pub mod modlist;
 pub mod options;
+pub mod overlaps;
@@ -0,0 +1,118 @@
+use iced::widget;
+
+use crate::app::message;
+use crate::core::modlist;
+use crate::styled_container;
+
+pub fn modlist_view(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
+    let theme = app.theme();
+    let palette_ext = theme.extended_palette();
+    let mut container_bg_color = palette_ext.primary.weak.color;
+    container_bg_color.a = 0.3;
+
+    let modlist_state = &app.state.modlist;
+    let button_load = widget::button("Load mod list").on_press(message::ModListMessage::Load(None).into());
+    let button_archives =
+        widget::button("Archive directory...").on_press(message::ModListMessage::SetArchiveDir(None).into());
+    let archive_dir_label = match modlist_state.archive_dir.as_ref() {
+        Some(path) => widget::text!("Archives: {}", path.display()),
+        None => widget::text("Archives: only installed mods are matched"),
+    };
+
+    let Some(modlist) = modlist_state.modlist.as_ref() else {
+        return styled_container!(
+            widget::column![widget::text("No mod list loaded"), button_load].spacing(10),
+            border_width = 4.0,
+            border_radius = 4.0
+        )
+        .padding(10)
+        .center(iced::Length::Fill)
+        .into();
+    };
+
+    let missing = modlist_state.matches.iter().filter(|m| **m == modlist::ModMatch::Missing).count();
+    let different =
+        modlist_state.matches.iter().filter(|m| matches!(m, modlist::ModMatch::DifferentVersion(_))).count();
+
+    let has_game_dir = app
+        .session
+        .active_profile
+        .as_ref()
+        .and_then(|name| app.state.profile.profiles.get(name))
+        .is_some_and(|profile| !profile.path.as_os_str().is_empty());
+    let button_build = widget::button("Build instance")
+        .on_press_maybe(has_game_dir.then_some(message::ModListMessage::BuildInstance.into()));
+
+    let header = styled_container!(
+        widget::column![
+            widget::row![
+                widget::text!(
+                    "{} ({}, {} mods)",
+                    modlist.instance_name,
+                    modlist.game_type,
+                    modlist.mods.len()
+                ),
+                widget::horizontal_space(),
+                button_load,
+                button_build
+            ]
+            .spacing(10)
+            .align_y(iced::alignment::Vertical::Center),
+            widget::row![archive_dir_label, widget::horizontal_space(), button_archives]
+                .spacing(10)
+                .align_y(iced::alignment::Vertical::Center),
+            widget::text!("{missing} missing, {different} with a different version"),
+        ]
+        .spacing(10),
+        border_width = 2.0,
+        border_radius = 4.0,
+        background = container_bg_color
+    )
+    .padding(10)
+    .center_x(iced::Length::Fill);
+
+    let rows = modlist.mods.iter().zip(&modlist_state.matches).fold(
+        widget::column![table_row("Mod", "Version", "Enabled", "Status", palette_ext.background.base.text)]
+            .spacing(5),
+        |column, (entry, mod_match)| {
+            let color = match mod_match {
+                modlist::ModMatch::Found(_) => palette_ext.success.base.color,
+                modlist::ModMatch::DifferentVersion(_) => palette_ext.secondary.strong.color,
+                modlist::ModMatch::Missing => palette_ext.danger.base.color,
+            };
+
+            column.push(table_row(
+                &entry.name,
+                entry.version.as_deref().unwrap_or("-"),
+                if entry.enabled { "Yes" } else { "No" },
+                &mod_match.to_string(),
+                color,
+            ))
+        },
+    );
+
+    let body =
+        styled_container!(widget::scrollable(rows.padding(10)), border_width = 2.0, border_radius = 4.0)
+            .padding(10)
+            .center_x(iced::Length::Fill)
+            .align_top(iced::Length::Fill);
+
+    widget::column![header, body].spacing(10).padding(10).into()
+}
+
+fn table_row<'a>(
+    name: &str,
+    version: &str,
+    enabled: &str,
+    status: &str,
+    status_color: iced::Color,
+) -> iced::Element<'a, message::Message> {
+    widget::row![
+        widget::text(name.to_owned()).width(iced::Length::FillPortion(3)),
+        widget::text(version.to_owned()).width(iced::Length::FillPortion(1)),
+        widget::text(enabled.to_owned()).width(iced::Length::FillPortion(1)),
+        widget::text(status.to_owned()).color(status_color).width(iced::Length::FillPortion(2)),
+    ]
+    .spacing(10)
+    .into()
+}
@@ -6,14 +6,16 @@ pub mod about;
 pub mod config;
 pub mod launch;
 pub mod menu;
+pub mod profiles;
 
-pub fn options_view(app: &crate::app::GothicOrganizer) -> iced::Element<message::Message> {
+pub fn options_view(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
     let theme = app.theme();
     let palette_ext = theme.extended_palette();
     let menu_bar = menu::menu_bar(app);
 
     let options_menu = match app.state.ui.active_options_menu {
         menu::OptionsMenu::Config => config::config_menu(app),
+        menu::OptionsMenu::Profiles => profiles::profiles_menu(app),
         menu::OptionsMenu::Launch => launch::launch_menu(app),
         menu::OptionsMenu::About => about::about_menu(palette_ext),
     };
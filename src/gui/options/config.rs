@@ -3,14 +3,15 @@ use iced::widget;
 use crate::app::message;
 use crate::styled_container;
 
-pub fn config_menu(app: &crate::app::GothicOrganizer) -> iced::Element<message::Message> {
+pub fn config_menu(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
     let theme_setting = theme_setting(app);
     let profile_setting = game_directory_setting(app);
     let mods_dir_setting = mods_dir_setting(app);
     let notifications_setting = notifications_setting(app);
+    let log_setting = log_setting(app);
 
     styled_container!(
-        widget::column!(theme_setting, profile_setting, mods_dir_setting, notifications_setting)
+        widget::column!(theme_setting, profile_setting, mods_dir_setting, notifications_setting, log_setting)
             .spacing(10)
             .padding(10),
         border_width = 4.0,
@@ -21,35 +22,63 @@ pub fn config_menu(app: &crate::app::GothicOrganizer) -> iced::Element<message::
     .into()
 }
 
-pub fn mods_dir_setting(app: &crate::app::GothicOrganizer) -> iced::Element<message::Message> {
+pub fn mods_dir_setting(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
+    let transfer = app.state.mod_management.storage_transfer.as_ref();
+    let can_change = app.session.active_profile.is_some() && transfer.is_none();
+
     let label_mods_dir = widget::text!("Mods directory:");
     let input_mods_dir: iced::Element<message::Message> =
         widget::text_input("Mods directory", app.state.mod_management.mods_dir_field.as_ref())
             .on_input_maybe(
-                app.session
-                    .active_profile
-                    .clone()
-                    .map(|_| |input| message::ModMessage::UpdateModsDirField(input).into()),
+                can_change.then_some(|input| message::ModMessage::UpdateModsDirField(input).into()),
             )
-            .on_submit(
-                message::ModMessage::SetModsDir(Some(app.state.mod_management.mods_dir_field.clone().into()))
+            .on_submit_maybe(
+                can_change.then_some(
+                    message::ModMessage::SetModsDir(Some(
+                        app.state.mod_management.mods_dir_field.clone().into(),
+                    ))
                     .into(),
+                ),
             )
             .into();
 
-    let button_browse_mods_dir =
-        widget::button("...").on_press_maybe(if app.session.active_profile.is_some() {
-            Some(message::ModMessage::SetModsDir(None).into())
-        } else {
-            None
-        });
+    let button_browse_mods_dir = widget::button("...")
+        .on_press_maybe(can_change.then_some(message::ModMessage::SetModsDir(None).into()));
 
-    widget::row!(label_mods_dir, iced::widget::horizontal_space(), input_mods_dir, button_browse_mods_dir)
-        .spacing(10)
-        .into()
+    let setting = widget::row!(
+        label_mods_dir,
+        iced::widget::horizontal_space(),
+        input_mods_dir,
+        button_browse_mods_dir
+    )
+    .spacing(10);
+
+    let Some(transfer) = transfer else {
+        return setting.into();
+    };
+
+    let progress = &app.state.mod_management.storage_transfer_progress;
+    let action = match transfer.mode {
+        crate::core::storage::TransferMode::Move => "Moving",
+        crate::core::storage::TransferMode::Copy => "Copying",
+    };
+
+    widget::column!(
+        setting,
+        widget::progress_bar(0.0..=progress.total.max(1) as f32, progress.done as f32).height(8),
+        widget::text!(
+            "{action} mods to {}: {}/{} files",
+            transfer.mods_dir.display(),
+            progress.done,
+            progress.total
+        )
+        .size(12)
+    )
+    .spacing(5)
+    .into()
 }
 
-pub fn game_directory_setting(app: &crate::app::GothicOrganizer) -> iced::Element<message::Message> {
+pub fn game_directory_setting(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
     let label_profile_dir = widget::text!("Game directory:");
     let input_profile_dir: iced::Element<message::Message> =
         widget::text_input("Game directory", app.state.profile.profile_dir_field.as_ref())
@@ -77,7 +106,7 @@ pub fn game_directory_setting(app: &crate::app::GothicOrganizer) -> iced::Elemen
         .into()
 }
 
-pub fn theme_setting(app: &crate::app::GothicOrganizer) -> iced::Element<message::Message> {
+pub fn theme_setting(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
     let label_theme = widget::text!("Application theme:");
 
     let choice_theme = widget::combo_box(
@@ -90,10 +119,41 @@ pub fn theme_setting(app: &crate::app::GothicOrganizer) -> iced::Element<message
     widget::row!(label_theme, iced::widget::horizontal_space(), choice_theme).spacing(10).into()
 }
 
-pub fn notifications_setting(app: &crate::app::GothicOrganizer) -> iced::Element<message::Message> {
+pub fn notifications_setting(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
     let notify_on_error_checkbox =
         widget::checkbox("Notify on error", app.session.error_notifications_enabled)
             .on_toggle(|new_state| message::SettingsMessage::ToggleErrorNotifications(new_state).into());
 
     widget::row!(notify_on_error_checkbox).spacing(10).into()
 }
+
+pub fn log_setting(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
+    let label_log_level = widget::text!("Log level:");
+    let pick_list_log_level = widget::pick_list(
+        crate::app::session::LogLevel::into_iter().as_slice(),
+        Some(app.session.log_settings.level),
+        |level| message::SettingsMessage::SetLogLevel(level).into(),
+    );
+
+    let label_log_dir = widget::text!("Log directory:");
+    let input_log_dir = widget::text_input("Default", &app.state.settings.log_dir_field)
+        .on_input(|input| message::SettingsMessage::UpdateLogDirField(input).into())
+        .on_submit(
+            message::SettingsMessage::SetLogDir(Some(app.state.settings.log_dir_field.clone().into())).into(),
+        );
+    let button_browse_log_dir =
+        widget::button("...").on_press(message::SettingsMessage::SetLogDir(None).into());
+
+    let log_file = crate::logger::log_file()
+        .map(|config| format!("Logging to {}", config.path.display()))
+        .unwrap_or_default();
+
+    widget::column!(
+        widget::row!(label_log_level, iced::widget::horizontal_space(), pick_list_log_level).spacing(10),
+        widget::row!(label_log_dir, iced::widget::horizontal_space(), input_log_dir, button_browse_log_dir)
+            .spacing(10),
+        widget::text(log_file).size(12)
+    )
+    .spacing(5)
+    .into()
+}
@@ -8,7 +8,7 @@ use crate::styled_container;
 fn parser_settings(
     app: &crate::app::GothicOrganizer,
     launch_options: Option<session::GameLaunchConfiguration>,
-) -> iced::Element<message::Message> {
+) -> iced::Element<'_, message::Message> {
     let theme = app.theme();
     let palette_ext = theme.extended_palette();
     let mut container_bg_color = palette_ext.primary.weak.color;
@@ -57,7 +57,7 @@ fn parser_settings(
 fn game_settings(
     app: &crate::app::GothicOrganizer,
     launch_options: Option<session::GameLaunchConfiguration>,
-) -> iced::Element<message::Message> {
+) -> iced::Element<'_, message::Message> {
     let theme = app.theme();
     let palette_ext = theme.extended_palette();
     let mut container_bg_color = palette_ext.primary.weak.color;
@@ -121,7 +121,7 @@ fn game_settings(
         .into()
 }
 
-pub fn launch_menu(app: &crate::app::GothicOrganizer) -> iced::Element<message::Message> {
+pub fn launch_menu(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
     let launch_options = &app.session.launch_options;
     styled_container!(
         widget::row![
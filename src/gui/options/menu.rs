@@ -10,16 +10,17 @@ use derive_more::Display;
 pub enum OptionsMenu {
     #[default]
     Config,
+    Profiles,
     Launch,
     About,
 }
 
 impl IntoIterator for OptionsMenu {
     type Item = OptionsMenu;
-    type IntoIter = std::array::IntoIter<OptionsMenu, 3>;
+    type IntoIter = std::array::IntoIter<OptionsMenu, 4>;
 
     fn into_iter(self) -> Self::IntoIter {
-        [OptionsMenu::Config, OptionsMenu::Launch, OptionsMenu::About].into_iter()
+        [OptionsMenu::Config, OptionsMenu::Profiles, OptionsMenu::Launch, OptionsMenu::About].into_iter()
     }
 }
 
@@ -34,7 +35,7 @@ impl<'a, Message: 'a> From<OptionsMenu> for iced::Element<'a, Message> {
     }
 }
 
-pub fn menu_bar(app: &crate::app::GothicOrganizer) -> iced::Element<message::Message> {
+pub fn menu_bar(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
     let current_menu = app.state.ui.active_options_menu;
     widget::container(OptionsMenu::into_iter(OptionsMenu::default()).fold(widget::row![], |bar, menu| {
         let menu_button = widget::button(menu)
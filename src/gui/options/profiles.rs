@@ -0,0 +1,341 @@
+use iced::widget;
+
+use crate::app::message;
+use crate::core::profile;
+use crate::styled_container;
+
+pub fn profiles_menu(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
+    let profile_management = profile_management(app);
+    let profile_settings = profile_settings(app);
+    let instance_transfer = instance_transfer(app);
+    let instance_snapshots = instance_snapshots(app);
+    let discovered_installations = discovered_installations(app);
+
+    styled_container!(
+        widget::scrollable(
+            widget::column!(
+                profile_management,
+                profile_settings,
+                instance_transfer,
+                instance_snapshots,
+                discovered_installations
+            )
+            .spacing(10)
+            .padding(10)
+        ),
+        border_width = 4.0,
+        border_radius = 4.0
+    )
+    .padding(10)
+    .align_top(iced::Length::Fill)
+    .into()
+}
+
+fn section_header<'a>(
+    app: &crate::app::GothicOrganizer,
+    title: &'a str,
+) -> iced::Element<'a, message::Message> {
+    let theme = app.theme();
+    let palette_ext = theme.extended_palette();
+    let mut container_bg_color = palette_ext.primary.weak.color;
+    container_bg_color.a = 0.3;
+
+    styled_container!(
+        widget::text(title),
+        border_width = 2.0,
+        border_radius = 4.0,
+        background = container_bg_color
+    )
+    .padding(10)
+    .align_left(iced::Length::Fill)
+    .into()
+}
+
+fn profile_management(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
+    let profile_state = &app.state.profile;
+    let active_profile =
+        app.session.active_profile.as_ref().and_then(|name| profile_state.profiles.get(name));
+    let is_custom = active_profile.is_some_and(|p| p.is_custom);
+    let has_name = !profile_state.profile_name_field.trim().is_empty();
+
+    let input_profile_name = widget::text_input("Profile name", &profile_state.profile_name_field)
+        .on_input(|input| message::ProfileMessage::UpdateProfileNameField(input).into())
+        .on_submit(message::ProfileMessage::CreateProfile.into());
+
+    let choice_game_type = widget::combo_box(
+        &profile_state.new_profile_game_type_choices,
+        "Game type",
+        Some(&profile_state.new_profile_game_type),
+        |game_type| message::ProfileMessage::SetNewProfileGameType(game_type).into(),
+    );
+
+    let button_create = widget::button("Create")
+        .on_press_maybe(has_name.then_some(message::ProfileMessage::CreateProfile.into()));
+
+    let button_rename = widget::button("Rename").on_press_maybe(
+        (has_name && is_custom).then_some(message::ProfileMessage::RenameActiveProfile.into()),
+    );
+
+    let button_duplicate = widget::button("Duplicate").on_press_maybe(
+        (has_name && active_profile.is_some())
+            .then_some(message::ProfileMessage::DuplicateActiveProfile.into()),
+    );
+
+    let button_delete = widget::button("Delete")
+        .style(widget::button::danger)
+        .on_press_maybe(is_custom.then_some(message::ProfileMessage::DeleteActiveProfile.into()));
+
+    let active_label = match active_profile {
+        Some(profile) if profile.is_custom => widget::text!("Active profile: {} (custom)", profile.name),
+        Some(profile) => widget::text!("Active profile: {}", profile.name),
+        None => widget::text("No active profile"),
+    };
+
+    styled_container!(
+        widget::column![
+            section_header(app, "Profiles"),
+            active_label,
+            widget::row![input_profile_name, choice_game_type].spacing(10),
+            widget::row![button_create, button_rename, button_duplicate, button_delete].spacing(10),
+        ]
+        .spacing(10)
+        .padding(10),
+        border_width = 2.0,
+        border_radius = 4.0
+    )
+    .padding(10)
+    .into()
+}
+
+fn profile_settings(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
+    let Some(active_profile) =
+        app.session.active_profile.as_ref().and_then(|name| app.state.profile.profiles.get(name))
+    else {
+        return widget::column![].into();
+    };
+
+    // The game settings of default profiles come from the built-in defaults and are read only.
+    let is_custom = active_profile.is_custom;
+    let choice_game_type: iced::Element<_> = match is_custom {
+        true => widget::combo_box(
+            &app.state.profile.game_type_choices,
+            "Game type",
+            Some(&active_profile.game_type),
+            |game_type| message::ProfileMessage::SetGameType(game_type).into(),
+        )
+        .into(),
+        false => widget::text(active_profile.game_type.to_string()).into(),
+    };
+
+    let executable = active_profile.executable.as_ref().map(|p| p.display().to_string()).unwrap_or_default();
+    let default_executable = active_profile.game_type.default_executable().display().to_string();
+    let input_executable = widget::text_input(&default_executable, &executable)
+        .on_input_maybe(is_custom.then_some(|input| message::ProfileMessage::SetExecutable(input).into()))
+        .on_submit(message::ProfileMessage::ValidateGameDir.into());
+
+    let layout_dirs = [
+        (profile::LayoutDir::System, "System directory:"),
+        (profile::LayoutDir::Data, "Data directory:"),
+        (profile::LayoutDir::Work, "Work directory:"),
+    ];
+
+    let layout = layout_dirs.into_iter().fold(widget::column![].spacing(10), |column, (dir, label)| {
+        let value = active_profile.layout.get(dir).display().to_string();
+        column.push(
+            widget::row![
+                widget::text(label),
+                widget::horizontal_space(),
+                widget::text_input(label, &value).on_input_maybe(
+                    is_custom
+                        .then_some(move |input| message::ProfileMessage::SetLayoutDir(dir, input).into())
+                )
+            ]
+            .spacing(10),
+        )
+    });
+
+    let has_game_dir = !active_profile.path.as_os_str().is_empty();
+    let layout = layout.push_maybe(
+        (has_game_dir && active_profile.fingerprint.is_none())
+            .then(|| widget::text("Press Enter in a changed field to validate the game directory").size(12)),
+    );
+
+    let button_rescan = widget::button("Rescan game directory")
+        .on_press_maybe(has_game_dir.then_some(message::ProfileMessage::RescanGameDir.into()));
+
+    let rescan_result = match &app.state.profile.base_file_changes {
+        Some(changes) => widget::text(changes.summary()).size(12),
+        None => widget::text("Rescan after patching the game to pick up new or replaced base files").size(12),
+    };
+
+    styled_container!(
+        widget::column![
+            section_header(app, "Profile Settings"),
+            widget::row![widget::text("Game type:"), widget::horizontal_space(), choice_game_type]
+                .spacing(10),
+            widget::row![widget::text("Executable:"), widget::horizontal_space(), input_executable]
+                .spacing(10),
+            layout,
+            widget::row![rescan_result, widget::horizontal_space(), button_rescan]
+                .spacing(10)
+                .align_y(iced::alignment::Vertical::Center)
+        ]
+        .spacing(10)
+        .padding(10),
+        border_width = 2.0,
+        border_radius = 4.0
+    )
+    .padding(10)
+    .into()
+}
+
+fn instance_transfer(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
+    let has_profile_path = app
+        .session
+        .active_profile
+        .as_ref()
+        .and_then(|name| app.state.profile.profiles.get(name))
+        .is_some_and(|profile| !profile.path.as_os_str().is_empty());
+
+    let checkbox_mod_files =
+        widget::checkbox("Include mod files", app.state.profile.export_include_mod_files).on_toggle(
+            |include_mod_files| message::ProfileMessage::ToggleExportModFiles(include_mod_files).into(),
+        );
+
+    let button_export = widget::button("Export active instance").on_press_maybe(
+        app.session.active_instance.is_some().then_some(message::ProfileMessage::ExportActiveInstance.into()),
+    );
+
+    let button_import = widget::button("Import instance")
+        .on_press_maybe(has_profile_path.then_some(message::ProfileMessage::ImportInstance(None).into()));
+
+    let button_export_modlist = widget::button("Export mod list").on_press_maybe(
+        app.session.active_instance.is_some().then_some(message::ModListMessage::Export.into()),
+    );
+
+    let button_load_modlist = widget::button("Load mod list")
+        .on_press_maybe(has_profile_path.then_some(message::ModListMessage::Load(None).into()));
+
+    let button_apply_modpack = widget::button("Apply modpack").on_press_maybe(
+        app.session.active_instance.is_some().then_some(message::ModListMessage::ApplyModpack(None).into()),
+    );
+
+    styled_container!(
+        widget::column![
+            section_header(app, "Sharing"),
+            widget::row![button_export, checkbox_mod_files, widget::horizontal_space(), button_import]
+                .spacing(10)
+                .align_y(iced::alignment::Vertical::Center),
+            widget::text("Mod lists reference mods by name, version and hash without their files").size(12),
+            widget::row![button_export_modlist, widget::horizontal_space(), button_load_modlist].spacing(10),
+            widget::text(
+                "Modpacks declare the mods of an instance, applying one makes the instance match it"
+            )
+            .size(12),
+            widget::row![widget::horizontal_space(), button_apply_modpack].spacing(10)
+        ]
+        .spacing(10)
+        .padding(10),
+        border_width = 2.0,
+        border_radius = 4.0
+    )
+    .padding(10)
+    .into()
+}
+
+fn instance_snapshots(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
+    let profile_state = &app.state.profile;
+    let Some(active_instance_name) = app.session.active_instance.as_ref() else {
+        return widget::column![].into();
+    };
+
+    let input_name = widget::text_input("Snapshot name", &profile_state.snapshot_name_field)
+        .on_input(|input| message::ProfileMessage::UpdateSnapshotNameField(input).into())
+        .on_submit(message::ProfileMessage::CreateSnapshot.into());
+
+    let input_note = widget::text_input("Note", &profile_state.snapshot_note_field)
+        .on_input(|input| message::ProfileMessage::UpdateSnapshotNoteField(input).into())
+        .on_submit(message::ProfileMessage::CreateSnapshot.into());
+
+    let button_create = widget::button("Take snapshot").on_press_maybe(
+        (!profile_state.snapshot_name_field.trim().is_empty())
+            .then_some(message::ProfileMessage::CreateSnapshot.into()),
+    );
+
+    let snapshots = profile_state.snapshots.iter().fold(widget::column![].spacing(10), |column, snapshot| {
+        let mut details = widget::column![
+            widget::text!("{} ({} mods)", snapshot.name, snapshot.mod_count),
+            widget::text(snapshot.created.format("%Y-%m-%d %H:%M:%S").to_string()).size(12)
+        ]
+        .spacing(2);
+
+        if !snapshot.note.is_empty() {
+            details = details.push(widget::text(snapshot.note.clone()).size(12));
+        }
+
+        column.push(
+            widget::row![
+                details,
+                widget::horizontal_space(),
+                widget::button("Restore")
+                    .on_press(message::ProfileMessage::RestoreSnapshot(snapshot.path.clone()).into()),
+                widget::button("Delete")
+                    .style(widget::button::danger)
+                    .on_press(message::ProfileMessage::DeleteSnapshot(snapshot.path.clone()).into())
+            ]
+            .spacing(10)
+            .align_y(iced::alignment::Vertical::Center),
+        )
+    });
+
+    styled_container!(
+        widget::column![
+            section_header(app, "Snapshots"),
+            widget::text!("Snapshots of instance \"{active_instance_name}\"").size(12),
+            widget::row![input_name, input_note, button_create].spacing(10),
+            snapshots
+        ]
+        .spacing(10)
+        .padding(10),
+        border_width = 2.0,
+        border_radius = 4.0
+    )
+    .padding(10)
+    .into()
+}
+
+fn discovered_installations(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
+    let button_detect = widget::button("Detect installations")
+        .on_press(message::ProfileMessage::DiscoverInstallations.into());
+
+    let installations = app.state.profile.discovered_installations.iter().enumerate().fold(
+        widget::column![].spacing(10),
+        |column, (index, installation)| {
+            let suggestion =
+                installation.suggested_profile.map(|p| p.to_string()).unwrap_or("Unknown".into());
+            column.push(
+                widget::row![
+                    widget::column![
+                        widget::text!("{suggestion} [{}]", installation.source),
+                        widget::text(installation.path.display().to_string()).size(12)
+                    ]
+                    .spacing(2),
+                    widget::horizontal_space(),
+                    widget::button("Use").on_press(message::ProfileMessage::UseInstallation(index).into())
+                ]
+                .spacing(10)
+                .align_y(iced::alignment::Vertical::Center),
+            )
+        },
+    );
+
+    styled_container!(
+        widget::column![section_header(app, "Installations"), button_detect, installations]
+            .spacing(10)
+            .padding(10),
+        border_width = 2.0,
+        border_radius = 4.0
+    )
+    .padding(10)
+    .into()
+}
@@ -0,0 +1,115 @@
+use iced::widget;
+
+use crate::app::message;
+use crate::styled_container;
+
+pub fn overlaps_view(app: &crate::app::GothicOrganizer) -> iced::Element<'_, message::Message> {
+    let theme = app.theme();
+    let palette_ext = theme.extended_palette();
+    let mut container_bg_color = palette_ext.primary.weak.color;
+    container_bg_color.a = 0.3;
+
+    let button_refresh =
+        widget::button("Refresh").on_press(message::ConflictsMessage::AnalyzeOverlaps.into());
+
+    let Some(report) = app.state.overlaps.report.as_ref() else {
+        return styled_container!(
+            widget::column![widget::text("No overlap analysis available"), button_refresh].spacing(10),
+            border_width = 4.0,
+            border_radius = 4.0
+        )
+        .padding(10)
+        .center(iced::Length::Fill)
+        .into();
+    };
+
+    let header = styled_container!(
+        widget::row![
+            widget::text!("Instance: {}", report.instance_name),
+            widget::horizontal_space(),
+            button_refresh
+        ]
+        .align_y(iced::alignment::Vertical::Center),
+        border_width = 2.0,
+        border_radius = 4.0,
+        background = container_bg_color
+    )
+    .padding(10)
+    .center_x(iced::Length::Fill);
+
+    let warnings = report
+        .fully_overridden_mods()
+        .map(|m| format!("\"{}\" is fully overridden by higher priority mods", m.name))
+        .chain(report.heavy_vanilla_overriders().map(|m| {
+            format!("\"{}\" replaces {} of {} base game files", m.name, m.vanilla_overrides, m.total_files)
+        }))
+        .fold(widget::column![].spacing(5), |column, warning| {
+            column.push(widget::text(warning).color(palette_ext.danger.strong.color))
+        });
+
+    let mods_table = report
+        .mods
+        .iter()
+        .fold(
+            widget::column![table_row(["Mod", "Priority", "Files", "Overridden", "Replaces vanilla"], 1)]
+                .spacing(5),
+            |column, summary| {
+                column.push(table_row(
+                    [
+                        summary.name.clone(),
+                        summary.priority.to_string(),
+                        summary.total_files.to_string(),
+                        summary.overridden_files.to_string(),
+                        summary.vanilla_overrides.to_string(),
+                    ],
+                    1,
+                ))
+            },
+        )
+        .padding(10);
+
+    let pairs_table = report
+        .pairs
+        .iter()
+        .fold(
+            widget::column![table_row(["Mod", "Mod", "Shared files", "Won by first", "Won by second"], 2)]
+                .spacing(5),
+            |column, overlap| {
+                column.push(table_row(
+                    [
+                        overlap.first.clone(),
+                        overlap.second.clone(),
+                        overlap.shared_files.to_string(),
+                        overlap.first_wins.to_string(),
+                        overlap.second_wins.to_string(),
+                    ],
+                    2,
+                ))
+            },
+        )
+        .padding(10);
+
+    let body = widget::column![
+        warnings,
+        widget::text("Mods:"),
+        styled_container!(mods_table, border_width = 2.0, border_radius = 4.0),
+        widget::text("Overlapping mod pairs:"),
+        styled_container!(pairs_table, border_width = 2.0, border_radius = 4.0),
+    ]
+    .spacing(10)
+    .padding(10);
+
+    widget::column![header, widget::scrollable(body)].spacing(10).padding(10).into()
+}
+
+/// Lays out a table row, giving the leading `name_columns` cells more room than the numeric ones.
+fn table_row<'a, S: ToString>(cells: [S; 5], name_columns: usize) -> iced::Element<'a, message::Message> {
+    cells
+        .iter()
+        .enumerate()
+        .fold(widget::row![].spacing(10), |row, (index, cell)| {
+            let portion = if index < name_columns { 3 } else { 1 };
+            row.push(widget::text(cell.to_string()).width(iced::Length::FillPortion(portion)))
+        })
+        .into()
+}
@@ -1,49 +1,475 @@
+use std::collections::VecDeque;
+use std::fmt::Write as _;
 use std::fs::OpenOptions;
-use std::path::Path;
+use std::io::IsTerminal as _;
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::OnceLock;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 
+use chrono::DateTime;
+use chrono::Local;
+use chrono::NaiveDate;
+use iced::futures::channel::mpsc;
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::Layer;
 use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::reload;
 use tracing_subscriber::util::SubscriberInitExt;
 
+use crate::error::Error;
+use crate::error::ErrorContext;
+
+/// Number of entries kept for the log viewer, the oldest are dropped first.
+pub const LOG_BUFFER_CAPACITY: usize = 5000;
+
+/// Levels the application log can be set to, most severe first.
+pub const LOG_LEVELS: [LevelFilter; 5] =
+    [LevelFilter::ERROR, LevelFilter::WARN, LevelFilter::INFO, LevelFilter::DEBUG, LevelFilter::TRACE];
+
+/// Location and rotation of the log file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFileConfig {
+    pub path: PathBuf,
+    /// Size in bytes after which the file is rotated.
+    pub max_size: u64,
+    /// Number of rotated files kept next to the current one.
+    pub retained_files: usize,
+}
+
+impl LogFileConfig {
+    /// Path of the `index`-th rotated file, `1` being the newest, e.g. `app.1.log` for `app.log`.
+    pub fn rotated_path(&self, index: usize) -> PathBuf {
+        let stem = self.path.file_stem().unwrap_or_default().to_string_lossy();
+        let file_name = match self.path.extension() {
+            Some(extension) => format!("{stem}.{index}.{}", extension.to_string_lossy()),
+            None => format!("{stem}.{index}"),
+        };
+        self.path.with_file_name(file_name)
+    }
+
+    /// The current file followed by the rotated ones that exist, newest first.
+    pub fn files(&self) -> Vec<PathBuf> {
+        std::iter::once(self.path.clone())
+            .chain((1..=self.retained_files).map(|index| self.rotated_path(index)))
+            .filter(|path| path.is_file())
+            .collect()
+    }
+}
+
+/// Log file rotated once it grows past its maximum size or a new day starts.
+struct RotatingFile {
+    config: LogFileConfig,
+    file: std::fs::File,
+    size: u64,
+    date: NaiveDate,
+    /// Set once a failed rotation was reported, later failures only keep appending.
+    rotation_failed: bool,
+}
+
+impl RotatingFile {
+    fn open(config: LogFileConfig) -> std::io::Result<Self> {
+        if let Some(parent) = config.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&config.path)?;
+        let metadata = file.metadata()?;
+        let date = metadata
+            .modified()
+            .map(|modified| DateTime::<Local>::from(modified).date_naive())
+            .unwrap_or_else(|_| Local::now().date_naive());
+
+        Ok(Self { config, file, size: metadata.len(), date, rotation_failed: false })
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+        if self.config.retained_files == 0 {
+            self.file = OpenOptions::new().create(true).write(true).truncate(true).open(&self.config.path)?;
+        } else {
+            let oldest = self.config.rotated_path(self.config.retained_files);
+            if oldest.exists() {
+                std::fs::remove_file(&oldest)?;
+            }
+
+            for index in (1..self.config.retained_files).rev() {
+                let rotated = self.config.rotated_path(index);
+                if rotated.exists() {
+                    std::fs::rename(&rotated, self.config.rotated_path(index + 1))?;
+                }
+            }
+
+            std::fs::rename(&self.config.path, self.config.rotated_path(1))?;
+            self.file = OpenOptions::new().create(true).append(true).open(&self.config.path)?;
+        }
+
+        self.size = 0;
+        self.date = Local::now().date_naive();
+        Ok(())
+    }
+}
+
+impl std::io::Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let is_full = self.size > 0 && self.size + buf.len() as u64 > self.config.max_size;
+        if (is_full || self.date != Local::now().date_naive())
+            && let Err(err) = self.rotate()
+        {
+            // Logging must go on, so the current file keeps growing until the next rotation is due.
+            self.size = 0;
+            self.date = Local::now().date_naive();
+            if !std::mem::replace(&mut self.rotation_failed, true) {
+                writeln!(self.file, "Could not rotate the log file, appending to it instead: {err}")?;
+            }
+        }
+
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+static LOG_FILE: Mutex<Option<RotatingFile>> = Mutex::new(None);
+
+/// Writer of the file layer, forwarding to the current log file.
+struct LogFileWriter;
+
+impl std::io::Write for LogFileWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match LOG_FILE.lock().as_deref_mut() {
+            Ok(Some(file)) => file.write(buf),
+            _ => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match LOG_FILE.lock().as_deref_mut() {
+            Ok(Some(file)) => file.flush(),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Event recorded for the log viewer.
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub id: u64,
+    pub timestamp: DateTime<Local>,
+    pub level: tracing::Level,
+    pub target: String,
+    pub message: String,
+}
+
+impl LogEntry {
+    /// The entry formatted like a line of the log file.
+    pub fn line(&self) -> String {
+        format!(
+            "{} {:>5} {}: {}",
+            self.timestamp.format("%Y-%m-%d %H:%M:%S%.3f"),
+            self.level,
+            self.target,
+            self.message
+        )
+    }
+}
+
+struct LogBuffer {
+    entries: VecDeque<LogEntry>,
+    next_id: u64,
+    listeners: Vec<mpsc::UnboundedSender<()>>,
+}
+
+static LOG_BUFFER: Mutex<LogBuffer> =
+    Mutex::new(LogBuffer { entries: VecDeque::new(), next_id: 0, listeners: Vec::new() });
+
+/// Set while the listeners were told about new entries they have not acknowledged yet, so that
+/// a burst of events wakes them only once.
+static UPDATE_PENDING: AtomicBool = AtomicBool::new(false);
+
+static LEVEL_CONTROL: OnceLock<LevelControl> = OnceLock::new();
+
+type ReloadFilter = Box<dyn Fn(EnvFilter) -> Result<(), reload::Error> + Send + Sync>;
+
+/// Level of the log file and viewer, which unlike the console can be changed while running.
+struct LevelControl {
+    package_name: String,
+    reload_filters: Vec<ReloadFilter>,
+}
+
+/// Layer recording events into the buffer read by the log viewer.
+struct BufferLayer;
+
+impl<S: tracing::Subscriber> Layer<S> for BufferLayer {
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: tracing_subscriber::layer::Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let Ok(mut buffer) = LOG_BUFFER.lock() else {
+            return;
+        };
+
+        let id = buffer.next_id;
+        buffer.next_id += 1;
+        if buffer.entries.len() == LOG_BUFFER_CAPACITY {
+            buffer.entries.pop_front();
+        }
+
+        buffer.entries.push_back(LogEntry {
+            id,
+            timestamp: Local::now(),
+            level: *event.metadata().level(),
+            target: event.metadata().target().to_owned(),
+            message: visitor.message,
+        });
+
+        if !UPDATE_PENDING.swap(true, Ordering::AcqRel) {
+            buffer.listeners.retain(|listener| listener.unbounded_send(()).is_ok());
+        }
+    }
+}
+
+/// Collects the message of an event followed by its other fields as `key=value`. The message
+/// field comes first in events created by the `tracing` macros.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl tracing::field::Visit for MessageVisitor {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        match field.name() {
+            "message" => self.message.push_str(value),
+            name => {
+                let _ = write!(self.message, " {name}={value}");
+            }
+        }
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        match field.name() {
+            "message" => {
+                let _ = write!(self.message, "{value:?}");
+            }
+            name => {
+                let _ = write!(self.message, " {name}={value:?}");
+            }
+        }
+    }
+}
+
 pub fn setup_logger(
     verbosity: LevelFilter,
-    file_path: Option<&Path>,
+    log_level: LevelFilter,
+    log_file: LogFileConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let package_name = env!("CARGO_PKG_NAME").replace('-', "_");
-    let log_file =
-        OpenOptions::new().create(true).append(true).open(file_path.unwrap_or(Path::new("log.log")))?;
+    *LOG_FILE.lock().map_err(|_| "log file lock poisoned")? = Some(RotatingFile::open(log_file)?);
 
+    // Stdout is reserved for the output of CLI commands, which scripts parse.
     let console_layer = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
         .with_target(true)
         .with_file(true)
         .with_level(true)
         .with_line_number(true)
-        .with_ansi(true)
+        .with_ansi(std::io::stderr().is_terminal())
         .without_time()
-        .with_filter(
-            EnvFilter::new("")
-                .add_directive(format!("{}={verbosity}", &package_name).parse()?)
-                .add_directive("error".parse()?),
-        );
+        .with_filter(app_filter(&package_name, verbosity)?);
 
+    let (file_filter, file_filter_handle) = reload::Layer::new(app_filter(&package_name, log_level)?);
     let file_layer = tracing_subscriber::fmt::layer()
-        .with_writer(log_file)
+        .with_writer(|| LogFileWriter)
         .with_target(true)
         .with_file(true)
         .with_level(true)
         .with_line_number(true)
         .with_ansi(false)
         .with_timer(tracing_subscriber::fmt::time::time())
-        .with_filter(
-            EnvFilter::new("")
-                .add_directive(format!("{package_name}={verbosity}").parse()?)
-                .add_directive("error".parse()?),
-        );
+        .with_filter(file_filter);
 
-    tracing_subscriber::registry().with(console_layer).with(file_layer).init();
+    let (buffer_filter, buffer_filter_handle) = reload::Layer::new(app_filter(&package_name, log_level)?);
+    let buffer_layer = BufferLayer.with_filter(buffer_filter);
 
-    tracing::debug!("Logger initialized with level: {verbosity}");
+    tracing_subscriber::registry().with(console_layer).with(file_layer).with(buffer_layer).init();
+
+    let _ = LEVEL_CONTROL.set(LevelControl {
+        package_name,
+        reload_filters: vec![
+            Box::new(move |filter| file_filter_handle.reload(filter)),
+            Box::new(move |filter| buffer_filter_handle.reload(filter)),
+        ],
+    });
+
+    tracing::debug!("Logger initialized with level: {verbosity}, application log level: {log_level}");
     Ok(())
 }
+
+/// Filter letting events of the application through up to `level`, and errors of its
+/// dependencies.
+fn app_filter(
+    package_name: &str,
+    level: LevelFilter,
+) -> Result<EnvFilter, tracing_subscriber::filter::ParseError> {
+    Ok(EnvFilter::new("")
+        .add_directive(format!("{package_name}={level}").parse()?)
+        .add_directive("error".parse()?))
+}
+
+/// Changes the level of the log file and viewer. The console keeps the level it was started with.
+pub fn set_level(level: LevelFilter) -> Result<(), ErrorContext> {
+    let Some(control) = LEVEL_CONTROL.get() else {
+        return Err(ErrorContext::builder()
+            .error(Error::system("Logger is not initialized", "Set Log Level"))
+            .build());
+    };
+
+    for reload_filter in &control.reload_filters {
+        let filter = app_filter(&control.package_name, level).map_err(|err| {
+            ErrorContext::builder()
+                .error(Error::system("Invalid log level", "Set Log Level").with_source(&err))
+                .build()
+        })?;
+        reload_filter(filter).map_err(|err| {
+            ErrorContext::builder()
+                .error(Error::system("Could not change the log level", "Set Log Level").with_source(&err))
+                .build()
+        })?;
+    }
+
+    tracing::info!("Log level set to {level}");
+    Ok(())
+}
+
+/// Configuration of the current log file, `None` before the logger is set up.
+pub fn log_file() -> Option<LogFileConfig> {
+    LOG_FILE.lock().ok().and_then(|file| file.as_ref().map(|file| file.config.clone()))
+}
+
+/// Switches to another log file. The current one is kept open if the new one cannot be opened.
+pub fn set_log_file(config: LogFileConfig) -> Result<(), ErrorContext> {
+    let path = config.path.clone();
+    let file = RotatingFile::open(config).map_err(|err| {
+        ErrorContext::builder()
+            .error(
+                Error::file_system("Could not open the log file", "Set Log File")
+                    .with_path(&path)
+                    .with_source(&err),
+            )
+            .build()
+    })?;
+
+    if let Ok(mut current) = LOG_FILE.lock() {
+        *current = Some(file);
+    }
+
+    tracing::info!("Logging to {}", path.display());
+    Ok(())
+}
+
+/// Entries recorded so far, oldest first.
+pub fn entries() -> Vec<LogEntry> {
+    LOG_BUFFER.lock().map(|buffer| buffer.entries.iter().cloned().collect()).unwrap_or_default()
+}
+
+/// Stream yielding right away and then once new entries were recorded. After each item, the
+/// entries have to be acknowledged with [`acknowledge_updates`] before the stream yields again.
+pub fn updates() -> mpsc::UnboundedReceiver<()> {
+    let (sender, receiver) = mpsc::unbounded();
+    if let Ok(mut buffer) = LOG_BUFFER.lock() {
+        UPDATE_PENDING.store(true, Ordering::Release);
+        let _ = sender.unbounded_send(());
+        buffer.listeners.push(sender);
+    }
+
+    receiver
+}
+
+pub fn acknowledge_updates() {
+    UPDATE_PENDING.store(false, Ordering::Release);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::testing::TempDir;
+
+    fn config(root: &TempDir, max_size: u64, retained_files: usize) -> LogFileConfig {
+        LogFileConfig { path: root.path().join("logs/app.log"), max_size, retained_files }
+    }
+
+    fn read(path: &std::path::Path) -> String {
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn files_are_rotated_by_size_and_only_the_retained_ones_are_kept() {
+        let root = TempDir::new();
+        let config = config(&root, 10, 2);
+        let mut file = RotatingFile::open(config.clone()).unwrap();
+
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+
+        assert_eq!(read(&config.path), "fourth\n");
+        assert_eq!(read(&config.rotated_path(1)), "third\n");
+        assert_eq!(read(&config.rotated_path(2)), "second\n");
+        assert!(!config.rotated_path(3).exists());
+        assert_eq!(config.files().len(), 3);
+    }
+
+    #[test]
+    fn files_are_rotated_when_a_new_day_starts() {
+        let root = TempDir::new();
+        let config = config(&root, u64::MAX, 1);
+        let mut file = RotatingFile::open(config.clone()).unwrap();
+        file.write_all(b"yesterday\n").unwrap();
+
+        file.date = file.date.pred_opt().unwrap();
+        file.write_all(b"today\n").unwrap();
+
+        assert_eq!(read(&config.path), "today\n");
+        assert_eq!(read(&config.rotated_path(1)), "yesterday\n");
+    }
+
+    #[test]
+    fn without_retained_files_the_file_is_truncated() {
+        let root = TempDir::new();
+        let config = config(&root, 10, 0);
+        let mut file = RotatingFile::open(config.clone()).unwrap();
+
+        file.write_all(b"first\n").unwrap();
+        file.write_all(b"second\n").unwrap();
+
+        assert_eq!(read(&config.path), "second\n");
+        assert!(!config.rotated_path(1).exists());
+    }
+
+    #[test]
+    fn a_failed_rotation_keeps_appending_and_is_reported_once() {
+        let root = TempDir::new();
+        let config = config(&root, 10, 1);
+        // A directory in place of the rotated file can not be removed as a file.
+        root.file("logs/app.1.log/blocker", "");
+        let mut file = RotatingFile::open(config.clone()).unwrap();
+
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        file.date = file.date.pred_opt().unwrap();
+        file.write_all(b"fifth\n").unwrap();
+
+        let contents = read(&config.path);
+        assert!(contents.starts_with("first\nCould not rotate the log file"), "{contents}");
+        assert!(contents.ends_with("second\nthird\nfourth\nfifth\n"), "{contents}");
+        assert_eq!(contents.matches("Could not rotate").count(), 1);
+    }
+}
@@ -182,11 +182,11 @@ macro_rules! save_app_session {
 #[macro_export]
 macro_rules! load_app_session {
     () => {
-        $crate::core::helpers::load_app_session::<String>(None)
+        $crate::core::helpers::load_app_session::<String>(None, $crate::core::helpers::Recovery::Ask)
     };
 
     ($custom_path: expr) => {
-        $crate::core::helpers::load_app_session($custom_path)
+        $crate::core::helpers::load_app_session($custom_path, $crate::core::helpers::Recovery::Ask)
     };
 }
 
@@ -203,10 +203,10 @@ macro_rules! save_profile {
 #[macro_export]
 macro_rules! load_profile {
     ($name: expr) => {
-        $crate::core::helpers::load_profile::<String>($name, None)
+        $crate::core::helpers::load_profile::<String>($name, None, $crate::core::helpers::Recovery::Ask)
     };
     ($name: expr, $custom_path: expr) => {
-        $crate::core::helpers::load_profile($name, $custom_path)
+        $crate::core::helpers::load_profile($name, $custom_path, $crate::core::helpers::Recovery::Ask)
     };
 }
 
@@ -1,4 +1,5 @@
 mod app;
+mod cli;
 mod core;
 mod error;
 mod gui;
@@ -6,6 +7,7 @@ mod logger;
 mod macros;
 
 use std::path::PathBuf;
+use std::process::ExitCode;
 
 use clap::Parser;
 use iced::daemon;
@@ -20,15 +22,34 @@ struct CliArgs {
     log_file: Option<PathBuf>,
     #[clap(short, long, default_value = None)]
     user_data_dir: Option<PathBuf>,
+    /// Print command results as JSON
+    #[clap(long, global = true)]
+    json: bool,
+    #[clap(subcommand)]
+    command: Option<cli::Command>,
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
     let args = CliArgs::parse();
-    logger::setup_logger(args.verbosity.unwrap_or(LevelFilter::ERROR), args.log_file.as_deref())?;
+    let log_settings = core::helpers::load_log_settings(args.user_data_dir.as_deref());
+    let mut log_file = core::helpers::log_file_config(&log_settings, args.user_data_dir.as_deref());
+    if let Some(path) = args.log_file {
+        log_file.path = path;
+    }
+
+    logger::setup_logger(
+        args.verbosity.unwrap_or(LevelFilter::ERROR),
+        args.verbosity.unwrap_or(log_settings.level.into()),
+        log_file,
+    )?;
+
+    if let Some(command) = args.command {
+        return Ok(cli::run(command, args.user_data_dir.as_deref(), args.json));
+    }
 
     daemon(core::constants::APP_TITLE, app::GothicOrganizer::update, app::GothicOrganizer::view)
         .theme(|state, _| app::GothicOrganizer::theme(state))
         .subscription(app::GothicOrganizer::subscription)
         .run_with(move || app::GothicOrganizer::new(args.user_data_dir))?;
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }